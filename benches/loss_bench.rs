@@ -0,0 +1,45 @@
+//! # 損失関数のベンチマーク
+//!
+//! `NdArray`バックエンドで`tuning_fork_loss`の処理時間を計測します。
+//! リファクタリング前後の比較の基準として使用します。
+//!
+//! ```bash
+//! cargo bench --bench loss_bench
+//! ```
+
+use burn::backend::ndarray::{NdArray, NdArrayDevice};
+use criterion::{BenchmarkId, Criterion, criterion_group, criterion_main};
+use std::hint::black_box;
+
+use burn_tuningfork_pinn::constants::physics::Material;
+use burn_tuningfork_pinn::physics::{sample_loss_inputs, tuning_fork_loss};
+
+type B = NdArray<f32>;
+
+fn bench_tuning_fork_loss(c: &mut Criterion) {
+    let device: NdArrayDevice = Default::default();
+    let mut group = c.benchmark_group("tuning_fork_loss");
+
+    for batch_size in [1024, 8192] {
+        let (dims, targets) = sample_loss_inputs::<B>(batch_size, &device);
+        group.bench_with_input(
+            BenchmarkId::from_parameter(batch_size),
+            &(dims, targets),
+            |b, (dims, targets)| {
+                b.iter(|| {
+                    let loss = tuning_fork_loss(
+                        black_box(dims.clone()),
+                        black_box(targets.clone()),
+                        Material::Steel304,
+                    );
+                    // NdArrayは即時評価だが、結果を取り出して計算を確定させる
+                    black_box(loss.into_scalar())
+                })
+            },
+        );
+    }
+    group.finish();
+}
+
+criterion_group!(benches, bench_tuning_fork_loss);
+criterion_main!(benches);
@@ -0,0 +1,43 @@
+/*
+ * burn-tuningfork-pinn の C API (`capi` フィーチャー)。
+ *
+ * `cargo build --release --features capi` でビルドした共有ライブラリ
+ * (libburn_tuningfork_pinn.so など) とリンクして使用します。
+ */
+#ifndef TFPINN_H
+#define TFPINN_H
+
+#ifdef __cplusplus
+extern "C" {
+#endif
+
+/* 成功 */
+#define TFPINN_OK 0
+/* 引数にヌルポインタが渡された */
+#define TFPINN_ERR_NULL_POINTER 1
+/* 周波数が正の有限値でない */
+#define TFPINN_ERR_INVALID_INPUT 2
+/* 推論中に内部エラーが発生した */
+#define TFPINN_ERR_INTERNAL 3
+
+/* 読み込み済みの学習済みモデル (不透明な型) */
+typedef struct Model Model;
+
+/* 成果物ディレクトリからモデルを読み込む。失敗した場合は NULL を返す */
+Model *tfpinn_load(const char *dir);
+
+/*
+ * 周波数 freq (Hz) に対する寸法 (m) を out_dims に書き込む。
+ * 順序: handle_length, handle_diameter, prong_length, prong_diameter, prong_gap
+ * 成功した場合は TFPINN_OK、失敗した場合は 0 以外を返す。
+ */
+int tfpinn_predict(const Model *model, float freq, float out_dims[5]);
+
+/* モデルを解放する。NULL の場合は何もしない */
+void tfpinn_free(Model *model);
+
+#ifdef __cplusplus
+}
+#endif
+
+#endif /* TFPINN_H */
@@ -0,0 +1,208 @@
+//! # バックエンドの選択
+//!
+//! コマンドラインで指定された名前と精度からバックエンドとデバイスを選択し、アクション
+//! (学習や推論) をそのバックエンドで実行します。
+//!
+//! アクションは[`BackendAction`]を実装し、バックエンドの型をジェネリクスとして受け取ります。
+//! 使用できるバックエンドは名前と精度をキーとする登録表 (`registry`) にまとめているため、
+//! バックエンドを追加する場合は登録表に1行を加えるだけで済みます。
+
+use burn::backend::{Autodiff, Cuda, NdArray, wgpu::Wgpu};
+#[cfg(feature = "candle")]
+use burn::backend::Candle;
+#[cfg(feature = "tch")]
+use burn::backend::{LibTorch, libtorch::LibTorchDevice};
+use burn::tensor::backend::{AutodiffBackend, Backend};
+use std::{fmt, str::FromStr};
+
+use crate::device::{self, DeviceError};
+
+/// 計算に使用する浮動小数点の精度。
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Dtype {
+    /// 単精度 (デフォルト)。
+    #[default]
+    F32,
+    /// 倍精度。
+    F64,
+}
+
+impl Dtype {
+    /// コマンドラインで指定する名前 (`f32`、`f64`) を返します。
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Dtype::F32 => "f32",
+            Dtype::F64 => "f64",
+        }
+    }
+}
+
+impl fmt::Display for Dtype {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+impl FromStr for Dtype {
+    type Err = String;
+
+    /// `f32`、`f64`を解析します。
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_ascii_lowercase().as_str() {
+            "f32" => Ok(Dtype::F32),
+            "f64" => Ok(Dtype::F64),
+            _ => Err(format!("unknown dtype '{s}' (expected f32 or f64)")),
+        }
+    }
+}
+
+/// バックエンドのデバイスと精度の選択に使用するオプション。
+#[derive(Debug, Clone, Default)]
+pub struct BackendOptions {
+    /// 使用するGPUのインデックス (`wgpu`のみ、[`device::wgpu_device`]を参照)。
+    pub device_index: Option<usize>,
+    /// 計算に使用する浮動小数点の精度。
+    pub dtype: Dtype,
+}
+
+/// バックエンドの選択で発生するエラー。
+#[derive(Debug)]
+pub enum BackendError {
+    /// 指定された名前のバックエンドがこのビルドに組み込まれていない。
+    Unknown(String),
+    /// バックエンドが指定された精度に対応していない。
+    UnsupportedDtype { backend: String, dtype: Dtype },
+    /// デバイスを使用できない。
+    Device(DeviceError),
+}
+
+impl fmt::Display for BackendError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            BackendError::Unknown(name) => write!(
+                f,
+                "Unknown backend '{name}' (available: {})",
+                device::available_backends().join(", ")
+            ),
+            BackendError::UnsupportedDtype { backend, dtype } => {
+                write!(f, "--dtype {dtype} is not supported by the {backend} backend")
+            }
+            BackendError::Device(err) => write!(f, "{err}"),
+        }
+    }
+}
+
+impl std::error::Error for BackendError {}
+
+/// 選択されたバックエンドで実行するアクション。
+///
+/// 学習には自動微分のバックエンド`B`を、推論には`B::InnerBackend`を使用します。
+/// どちらも同じデバイスの型を使うため、`run`には1つのデバイスを渡します。
+pub trait BackendAction {
+    /// アクションの結果。
+    type Output;
+
+    /// `B`のバックエンドと`device`でアクションを実行します。
+    fn run<B: AutodiffBackend>(self, device: B::Device) -> Self::Output;
+}
+
+/// コマンドラインのオプションからデバイスを選択できるバックエンド。
+trait SelectDevice: Backend {
+    /// 使用するデバイスを返します。デフォルトでは`Self::Device::default()`を返します。
+    fn select_device(_options: &BackendOptions) -> Result<Self::Device, DeviceError> {
+        Ok(Self::Device::default())
+    }
+}
+
+impl SelectDevice for Wgpu {
+    fn select_device(options: &BackendOptions) -> Result<Self::Device, DeviceError> {
+        device::wgpu_device(options.device_index)
+    }
+}
+
+impl SelectDevice for NdArray<f32> {}
+
+impl SelectDevice for NdArray<f64> {}
+
+impl SelectDevice for Cuda {}
+
+#[cfg(feature = "tch")]
+impl SelectDevice for LibTorch {
+    fn select_device(_options: &BackendOptions) -> Result<Self::Device, DeviceError> {
+        Ok(LibTorchDevice::Cpu)
+    }
+}
+
+#[cfg(feature = "candle")]
+impl SelectDevice for Candle {}
+
+/// 登録表の1行。名前と精度の組に、そのバックエンドでアクションを実行する関数を対応させます。
+struct Registration<A: BackendAction> {
+    name: &'static str,
+    dtype: Dtype,
+    run: fn(A, &BackendOptions) -> Result<A::Output, DeviceError>,
+}
+
+impl<A: BackendAction> Registration<A> {
+    /// `B`のバックエンドを`name`・`dtype`で登録します。学習用に`Autodiff`で包んで実行します。
+    fn new<B: SelectDevice>(name: &'static str, dtype: Dtype) -> Self {
+        Self {
+            name,
+            dtype,
+            run: run_on::<B, A>,
+        }
+    }
+}
+
+/// `B`のデバイスを選択し、`Autodiff<B>`で`action`を実行します。
+fn run_on<B: SelectDevice, A: BackendAction>(
+    action: A,
+    options: &BackendOptions,
+) -> Result<A::Output, DeviceError> {
+    let device = B::select_device(options)?;
+    Ok(action.run::<Autodiff<B>>(device))
+}
+
+/// このビルドに組み込まれたバックエンドの登録表を返します。
+///
+/// `tch`・`candle`はそれぞれ同名のフィーチャーを有効にしてビルドした場合にのみ登録されます。
+fn registry<A: BackendAction>() -> Vec<Registration<A>> {
+    let mut registrations = vec![
+        Registration::new::<Wgpu>("wgpu", Dtype::F32),
+        Registration::new::<NdArray<f32>>("ndarray", Dtype::F32),
+        Registration::new::<NdArray<f64>>("ndarray", Dtype::F64),
+        Registration::new::<Cuda>("cuda", Dtype::F32),
+    ];
+    #[cfg(feature = "tch")]
+    registrations.push(Registration::new::<LibTorch>("tch", Dtype::F32));
+    #[cfg(feature = "candle")]
+    registrations.push(Registration::new::<Candle>("candle", Dtype::F32));
+    registrations
+}
+
+/// `name`のバックエンドで`action`を実行します。
+///
+/// # Errors
+///
+/// * `name`のバックエンドがこのビルドに組み込まれていない場合は`BackendError::Unknown`
+/// * バックエンドが`options.dtype`の精度に対応していない場合は
+///   `BackendError::UnsupportedDtype`
+/// * デバイスを使用できない場合は`BackendError::Device`
+pub fn dispatch<A: BackendAction>(
+    name: &str,
+    options: &BackendOptions,
+    action: A,
+) -> Result<A::Output, BackendError> {
+    let registrations = registry::<A>();
+    if !registrations.iter().any(|registration| registration.name == name) {
+        return Err(BackendError::Unknown(name.to_string()));
+    }
+    let registration = registrations
+        .into_iter()
+        .find(|registration| registration.name == name && registration.dtype == options.dtype)
+        .ok_or_else(|| BackendError::UnsupportedDtype {
+            backend: name.to_string(),
+            dtype: options.dtype,
+        })?;
+    (registration.run)(action, options).map_err(BackendError::Device)
+}
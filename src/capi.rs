@@ -0,0 +1,123 @@
+//! # C API
+//!
+//! C/C++のアプリケーション (CADのプラグインなど) に推論を組み込むための`extern "C"`関数を
+//! 提供します (`capi`フィーチャー)。推論には`ndarray`バックエンド (CPU) を使用します。
+//!
+//! モデルは不透明なポインタ ([`Model`]) として扱い、[`tfpinn_load`]で読み込み、
+//! [`tfpinn_predict`]で推論し、[`tfpinn_free`]で解放します。パニックはFFIの境界を越えないよう
+//! 捕捉し、失敗はヌルポインタまたは0以外の戻り値で通知します。宣言は`include/tfpinn.h`に
+//! あります。
+
+use crate::constants::model_dims;
+use crate::infer;
+use crate::model::TuningForkPINN;
+use crate::physics::PhysicsConfig;
+use burn::backend::ndarray::{NdArray, NdArrayDevice};
+use std::{
+    ffi::{CStr, c_char},
+    panic::{self, AssertUnwindSafe},
+};
+
+type CapiBackend = NdArray<f32>;
+
+/// 成功。
+pub const TFPINN_OK: i32 = 0;
+/// 引数にヌルポインタが渡された。
+pub const TFPINN_ERR_NULL_POINTER: i32 = 1;
+/// 周波数が正の有限値でない。
+pub const TFPINN_ERR_INVALID_INPUT: i32 = 2;
+/// 推論中に内部エラー (パニック) が発生した。
+pub const TFPINN_ERR_INTERNAL: i32 = 3;
+
+/// 読み込み済みの学習済みモデルと、推論に使う学習時の材質・温度。C側からは不透明な
+/// ポインタとして扱います。
+pub struct Model {
+    model: TuningForkPINN<CapiBackend>,
+    physics: PhysicsConfig,
+}
+
+/// 成果物ディレクトリ`dir`から学習済みモデルを読み込みます。
+///
+/// 材質や温度を入力とするモデルでは、学習時に保存された設定の材質・温度で推論します。
+/// 失敗した場合 (ディレクトリがヌル・UTF-8でない、モデルが読み込めないなど) は
+/// ヌルポインタを返します。返されたポインタは[`tfpinn_free`]で解放してください。
+///
+/// # Safety
+///
+/// `dir`はヌルポインタか、ヌル終端された有効な文字列を指している必要があります。
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn tfpinn_load(dir: *const c_char) -> *mut Model {
+    if dir.is_null() {
+        return std::ptr::null_mut();
+    }
+    // SAFETY: 呼び出し側がヌル終端された有効な文字列であることを保証する
+    let Ok(dir) = unsafe { CStr::from_ptr(dir) }.to_str() else {
+        return std::ptr::null_mut();
+    };
+    let loaded = panic::catch_unwind(|| {
+        let model = infer::load_model::<CapiBackend>(dir, &NdArrayDevice::Cpu).ok()?;
+        infer::ensure_circular_output(model.output_size()).ok()?;
+        let physics = infer::physics_config_for(dir).ok()?;
+        Some(Model { model, physics })
+    });
+    match loaded {
+        Ok(Some(model)) => Box::into_raw(Box::new(model)),
+        _ => std::ptr::null_mut(),
+    }
+}
+
+/// 周波数`freq` (Hz) に対する寸法 (m) を推論し、`out_dims`に書き込みます。
+///
+/// 寸法は`handle_length, handle_diameter, prong_length, prong_diameter, prong_gap`の順に
+/// 5個書き込みます。成功した場合は[`TFPINN_OK`]、失敗した場合は0以外のエラーコードを返し、
+/// `out_dims`は変更しません。
+///
+/// # Safety
+///
+/// `model`はヌルポインタか[`tfpinn_load`]が返した未解放のポインタ、`out_dims`はヌルポインタか
+/// 5個の`float`を書き込める領域を指している必要があります。
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn tfpinn_predict(
+    model: *const Model,
+    freq: f32,
+    out_dims: *mut f32,
+) -> i32 {
+    if model.is_null() || out_dims.is_null() {
+        return TFPINN_ERR_NULL_POINTER;
+    }
+    if infer::validate_frequencies(&[freq]).is_err() {
+        return TFPINN_ERR_INVALID_INPUT;
+    }
+    // SAFETY: 呼び出し側が`tfpinn_load`の返した有効なポインタであることを保証する
+    let model = unsafe { &*model };
+    let predicted = panic::catch_unwind(AssertUnwindSafe(|| {
+        model
+            .model
+            .predict_batch_with_physics(&[freq], &model.physics, &NdArrayDevice::Cpu)
+            .map(|dims| dims[0].to_array())
+    }));
+    match predicted {
+        Ok(Ok(dims)) => {
+            // SAFETY: 呼び出し側が`NUM_DIMS`個の書き込み可能な領域であることを保証する
+            unsafe {
+                std::ptr::copy_nonoverlapping(dims.as_ptr(), out_dims, model_dims::NUM_DIMS);
+            }
+            TFPINN_OK
+        }
+        Ok(Err(_)) | Err(_) => TFPINN_ERR_INTERNAL,
+    }
+}
+
+/// [`tfpinn_load`]で読み込んだモデルを解放します。ヌルポインタの場合は何もしません。
+///
+/// # Safety
+///
+/// `model`はヌルポインタか[`tfpinn_load`]が返した未解放のポインタである必要があり、
+/// 解放後は使用できません。
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn tfpinn_free(model: *mut Model) {
+    if !model.is_null() {
+        // SAFETY: 呼び出し側が`tfpinn_load`の返した未解放のポインタであることを保証する
+        drop(unsafe { Box::from_raw(model) });
+    }
+}
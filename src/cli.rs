@@ -0,0 +1,318 @@
+//! # コマンドライン引数
+//!
+//! `train`サブコマンドのオプションと、そこから学習設定を構築する処理を定義します。
+
+use crate::constants::physics::Material;
+use crate::model::{Activation, InitKind};
+use crate::physics::{CrossSectionKind, FrequencyLossKind};
+use crate::record::RecordFormat;
+use crate::train::{LrSchedule, Optimizer, SamplingMode, TrainError, TrainingConfig};
+use burn::config::Config;
+use clap::Args;
+use std::path::PathBuf;
+
+/// `train`サブコマンドのオプション。
+///
+/// 指定されなかった項目は`--config`の設定ファイル、それもなければ`TrainingConfig`の
+/// デフォルト値を使用します。
+#[derive(Args, Debug)]
+pub struct TrainArgs {
+    /// 学習設定を読み込むJSONファイル (`config.json`と同じ形式)。他のフラグはこの値を上書きします
+    #[arg(long)]
+    pub config: Option<PathBuf>,
+    /// 学習エポック数
+    #[arg(long)]
+    pub epochs: Option<usize>,
+    /// バッチサイズ
+    #[arg(long)]
+    pub batch_size: Option<usize>,
+    /// 学習率 (スケジュールの初期値)
+    #[arg(long)]
+    pub learning_rate: Option<f64>,
+    /// 学習用の周波数範囲の下限 (Hz)。検証データはこの範囲から取り置かれます
+    #[arg(long)]
+    pub train_freq_min: Option<f32>,
+    /// 学習用の周波数範囲の上限 (Hz)
+    #[arg(long)]
+    pub train_freq_max: Option<f32>,
+    /// 学習用の周波数の分布 (`uniform`、`log-uniform`: 対数で一様、デフォルトは`uniform`)
+    #[arg(long)]
+    pub sampling: Option<SamplingMode>,
+    /// 乱数のシード (データの生成・重みの初期化・ドロップアウト)
+    #[arg(long)]
+    pub seed: Option<u64>,
+    /// データローダーのワーカー数 (0は同期的に読み込み)。同じシードで完全に同じモデルを
+    /// 得るには0か1を指定します
+    #[arg(long, visible_alias = "workers")]
+    pub num_workers: Option<usize>,
+    /// 検証損失が改善しないまま許容するエポック数 (早期終了)
+    #[arg(long)]
+    pub patience: Option<usize>,
+    /// 改善とみなす検証損失の最小減少量
+    #[arg(long)]
+    pub min_delta: Option<f64>,
+    /// 物理的制約のペナルティの重みを段階的に上げるエポック数 (0で無効)
+    #[arg(long)]
+    pub penalty_warmup_epochs: Option<usize>,
+    /// 隠れ層のユニット数 (デフォルトは128)
+    #[arg(long)]
+    pub hidden_size: Option<usize>,
+    /// 隠れ層の数 (デフォルトは3)
+    #[arg(long)]
+    pub num_hidden_layers: Option<usize>,
+    /// 隠れ層の活性化関数 (`relu`、`tanh`、`gelu`、`silu`、デフォルトは`relu`)
+    #[arg(long)]
+    pub activation: Option<Activation>,
+    /// 全結合層の重みの初期化方法 (`default`、`kaiming-normal`、`xavier-uniform`)
+    #[arg(long)]
+    pub init_kind: Option<InitKind>,
+    /// 各隠れ層の全結合層と活性化関数の間にバッチ正規化を挿入します
+    #[arg(long)]
+    pub batch_norm: bool,
+    /// 2層目以降の隠れ層に残差接続 (スキップ接続) を加えます
+    #[arg(long)]
+    pub residual: bool,
+    /// 隠れ層のドロップアウトの確率 (0.0以上1.0未満)
+    #[arg(long)]
+    pub dropout: Option<f64>,
+    /// 出力層の`softplus`の鋭さ (β、正の値)。大きくするほど小さな寸法を出力しやすくなります
+    #[arg(long)]
+    pub softplus_beta: Option<f64>,
+    /// オプティマイザ (`adam`、`adamw:<weight_decay>`、`sgd:<momentum>`)
+    #[arg(long)]
+    pub optimizer: Option<Optimizer>,
+    /// 学習率スケジュール (`constant`、`exponential:<gamma>`、`cosine:<min_lr>`、`step:<size>:<gamma>`、
+    /// `warmup-cosine:<warmup_epochs>:<min_lr>`)
+    #[arg(long)]
+    pub lr_schedule: Option<LrSchedule>,
+    /// 周波数損失の種類 (`squared`、`huber:<delta>`、`abs-relative`)
+    #[arg(long)]
+    pub frequency_loss: Option<FrequencyLossKind>,
+    /// 寸法ごとの範囲ペナルティの重み。柄の長さ・柄の直径・プロングの長さ・プロングの直径・
+    /// プロングの間隔の順にカンマ区切りで5つ指定します (例: `0,0,1,1,1`)
+    #[arg(long, value_delimiter = ',', num_args = 5)]
+    pub dimension_weights: Option<Vec<f32>>,
+    /// 近傍の周波数に対する予測寸法の自己一貫性項の重み (0で無効)
+    #[arg(long)]
+    pub consistency_weight: Option<f32>,
+    /// 倍音 (1つ上の振動モード) の周波数損失の重み (0で無効)
+    #[arg(long)]
+    pub overtone_weight: Option<f32>,
+    /// 基本周波数に対する倍音の周波数の目標比 (デフォルトはEuler-Bernoulli梁の約6.267)
+    #[arg(long)]
+    pub overtone_ratio: Option<f32>,
+    /// 寸法に加えて音叉のQ値を予測します (出力が1次元増えます)
+    #[arg(long)]
+    pub q_factor: bool,
+    /// Q値の損失の重み (`--q-factor`指定時のみ有効、デフォルトは1.0)
+    #[arg(long)]
+    pub q_factor_weight: Option<f32>,
+    /// 勾配ノルムの上限 (勾配クリッピング)
+    #[arg(long)]
+    pub grad_clip: Option<f32>,
+    /// L2正則化 (重み減衰) の係数 (推奨: `1e-5`〜`1e-4`)
+    #[arg(long)]
+    pub weight_decay: Option<f32>,
+    /// 音叉の材質 (`steel304`、`aluminum`、`brass`、`titanium`)
+    #[arg(long)]
+    pub material: Option<Material>,
+    /// すべての材質で1つのモデルを学習し、材質をモデルの入力に加えます
+    #[arg(long)]
+    pub material_input: bool,
+    /// プロングの断面形状 (`circular`、`rectangular`)
+    #[arg(long)]
+    pub cross_section: Option<CrossSectionKind>,
+    /// 調律する曲げ振動モードの次数 (1〜3)
+    #[arg(long)]
+    pub mode: Option<u8>,
+    /// 物理モデルにTimoshenko補正 (回転慣性・せん断変形) を適用します
+    #[arg(long)]
+    pub timoshenko: bool,
+    /// プロングの先端と付け根の直径の比 (線形テーパー)。1.0で一様な梁になります
+    #[arg(long)]
+    pub taper_ratio: Option<f32>,
+    /// 音叉の温度 (℃)。ヤング率を温度で補正します (デフォルトは基準温度の20℃)
+    #[arg(long)]
+    pub temperature: Option<f32>,
+    /// 温度をモデルの入力に加え、`--train-temp-min`〜`--train-temp-max`の温度で学習します
+    #[arg(long)]
+    pub temperature_input: bool,
+    /// 学習する温度範囲の下限 (℃、`--temperature-input`指定時のみ有効、デフォルトは-10)
+    #[arg(long)]
+    pub train_temp_min: Option<f32>,
+    /// 学習する温度範囲の上限 (℃、`--temperature-input`指定時のみ有効、デフォルトは50)
+    #[arg(long)]
+    pub train_temp_max: Option<f32>,
+    /// モデルとチェックポイントの保存形式 (`mpk`、`bincode`、`json`)
+    #[arg(long)]
+    pub record_format: Option<RecordFormat>,
+    /// 学習を再開するモデルレコードのパス (例: `./artifacts/model.mpk`)
+    #[arg(long)]
+    pub resume: Option<String>,
+    /// 隠れ層を凍結し、出力層だけを学習します (`--resume`と組み合わせたファインチューニング用)
+    #[arg(long)]
+    pub freeze_hidden: bool,
+    /// 出力層を物理モデルの解析解に近い寸法から始めます (新規に学習する場合のみ)
+    #[arg(long)]
+    pub warm_start: bool,
+    /// 学習後の評価で許容する周波数の誤差 (Hz、デフォルトは1)
+    #[arg(long)]
+    pub tolerance_hz: Option<f32>,
+    /// 設定を検証し、解決されたハイパーパラメータとモデルの概要を表示して終了します (学習しません)
+    #[arg(long)]
+    pub dry_run: bool,
+}
+
+impl TrainArgs {
+    /// コマンドライン引数から学習設定を構築します。
+    ///
+    /// `--config`が指定された場合はそのファイルの設定を、指定されなければ
+    /// `TrainingConfig`のデフォルト値を基にし、指定されたフラグの値で上書きします。
+    ///
+    /// # Errors
+    ///
+    /// 設定ファイルを読み込めない、またはJSONとして解釈できない場合に
+    /// `TrainError::InvalidConfig`を返します。
+    pub fn to_config(&self) -> Result<TrainingConfig, TrainError> {
+        let mut config = match &self.config {
+            Some(path) => TrainingConfig::load(path).map_err(|err| {
+                TrainError::InvalidConfig(format!("{}: {err}", path.display()))
+            })?,
+            None => TrainingConfig::new(),
+        };
+        if let Some(value) = self.epochs {
+            config.num_epochs = value;
+        }
+        if let Some(value) = self.batch_size {
+            config.batch_size = value;
+        }
+        if let Some(value) = self.learning_rate {
+            config.learning_rate = value;
+        }
+        if let Some(value) = self.train_freq_min {
+            config.train_freq_min = value;
+        }
+        if let Some(value) = self.train_freq_max {
+            config.train_freq_max = value;
+        }
+        if let Some(value) = self.sampling {
+            config.sampling = value;
+        }
+        if let Some(value) = self.seed {
+            config.seed = value;
+        }
+        if let Some(value) = self.num_workers {
+            config.num_workers = value;
+        }
+        if let Some(value) = self.patience {
+            config.patience = value;
+        }
+        if let Some(value) = self.min_delta {
+            config.min_delta = value;
+        }
+        if let Some(value) = self.penalty_warmup_epochs {
+            config.penalty_warmup_epochs = value;
+        }
+        if let Some(value) = self.hidden_size {
+            config.hidden_size = value;
+        }
+        if let Some(value) = self.num_hidden_layers {
+            config.num_hidden_layers = value;
+        }
+        if let Some(value) = &self.activation {
+            config.activation = value.clone();
+        }
+        if let Some(value) = &self.init_kind {
+            config.init_kind = value.clone();
+        }
+        if self.batch_norm {
+            config.batch_norm = true;
+        }
+        if self.residual {
+            config.residual = true;
+        }
+        if let Some(value) = self.dropout {
+            config.dropout = value;
+        }
+        if let Some(value) = self.softplus_beta {
+            config.softplus_beta = value;
+        }
+        if let Some(value) = &self.optimizer {
+            config.optimizer = value.clone();
+        }
+        if let Some(value) = &self.lr_schedule {
+            config.lr_schedule = value.clone();
+        }
+        if let Some(value) = &self.frequency_loss {
+            config.frequency_loss = value.clone();
+        }
+        // 値の個数はclapの`num_args`で5つに制限されている
+        if let Some(Ok(weights)) = self.dimension_weights.as_deref().map(<[f32; 5]>::try_from) {
+            config.dimension_weights = weights;
+        }
+        if let Some(value) = self.consistency_weight {
+            config.consistency_weight = value;
+        }
+        if let Some(value) = self.overtone_weight {
+            config.overtone_weight = value;
+        }
+        if let Some(value) = self.overtone_ratio {
+            config.overtone_ratio = value;
+        }
+        if self.q_factor {
+            config.predict_q_factor = true;
+        }
+        if let Some(value) = self.q_factor_weight {
+            config.q_factor_weight = value;
+        }
+        if self.grad_clip.is_some() {
+            config.grad_clip = self.grad_clip;
+        }
+        if self.weight_decay.is_some() {
+            config.weight_decay = self.weight_decay;
+        }
+        if let Some(value) = self.material {
+            config.material = value;
+        }
+        if self.material_input {
+            config.material_input = true;
+        }
+        if let Some(value) = self.cross_section {
+            config.cross_section = value;
+        }
+        if let Some(value) = self.mode {
+            config.mode = value;
+        }
+        if self.timoshenko {
+            config.timoshenko = true;
+        }
+        if let Some(value) = self.taper_ratio {
+            config.taper_ratio = value;
+        }
+        if let Some(value) = self.temperature {
+            config.temperature = value;
+        }
+        if self.temperature_input {
+            config.temperature_input = true;
+        }
+        if let Some(value) = self.train_temp_min {
+            config.train_temp_min = value;
+        }
+        if let Some(value) = self.train_temp_max {
+            config.train_temp_max = value;
+        }
+        if let Some(value) = self.record_format {
+            config.record_format = value;
+        }
+        if self.freeze_hidden {
+            config.freeze_hidden = true;
+        }
+        if self.warm_start {
+            config.warm_start = true;
+        }
+        if let Some(value) = self.tolerance_hz {
+            config.tolerance_hz = value;
+        }
+        Ok(config)
+    }
+}
@@ -4,18 +4,49 @@
 
 /// 物理計算に関する定数
 pub mod physics {
-    // 材質: ステンレス鋼 (SUS304) を想定
+    use burn::prelude::Config;
+    use std::str::FromStr;
+
+    // 材質: ステンレス鋼 (SUS304) を想定 (`Material::Steel304`の値)
     /// ヤング率 (Pa)。材料の硬さを示す指標。
     pub const YOUNGS_MODULUS: f32 = 193.0e9;
     /// 密度 (kg/m^3)。
     pub const DENSITY: f32 = 8000.0;
-    /// ポアソン比。材料が引張られた際の横方向の縮みを示す。今回は未使用。
-    pub const POISSON_RATIO: f32 = 0.29; // ポアソン比
+    /// ポアソン比。材料が引張られた際の横方向の縮みを示す。Timoshenko補正で使用。
+    pub const POISSON_RATIO: f32 = 0.29;
 
-    /// 振動モードの係数
+    /// 振動モードの係数 (1次モード、`(β₁L)² = 1.8751²`)
     pub const K_FACTOR: f32 = 3.5160;
+    /// 片持ち梁の1〜3次の曲げ振動モードの係数 `(βₙL)²` (`1.8751²`、`4.6941²`、`7.8548²`)。
+    pub const MODE_FACTORS: [f32; 3] = [K_FACTOR, 22.0345, 61.6972];
+    /// 1次モードに対する2次モードの周波数比 (倍音比) のデフォルト値。
+    /// Euler-Bernoulli梁では寸法によらず `22.0345 / 3.5160 ≈ 6.267` になります。
+    pub const OVERTONE_RATIO: f32 = 22.0345 / K_FACTOR;
+
+    /// 片持ち梁の1〜3次のモード形状の係数 `σₙ`。
+    /// モード形状は `φ(ξ) = cosh(βξ) − cos(βξ) − σ(sinh(βξ) − sin(βξ))` (`β = βₙL`、`ξ = x / L`) です。
+    pub const MODE_SHAPE_SIGMAS: [f32; 3] = [0.734_096, 1.018_466, 0.999_225];
+
+    /// 振動モードの次数 (1始まり) に対応する係数を返します。
+    /// 対応していない次数の場合は`None`を返します。
+    pub fn mode_factor(mode: u8) -> Option<f32> {
+        let index = usize::from(mode.checked_sub(1)?);
+        MODE_FACTORS.get(index).copied()
+    }
 
-    
+    /// プロング間隔のうち、プロングの有効長に加算される割合。
+    /// プロングの付け根 (U字部) のたわみにより、振動する長さが間隔の約半分だけ延びると仮定します。
+    pub const GAP_LENGTH_FACTOR: f32 = 0.5;
+    /// 片持ち梁の等価モード質量比 (33/140)。
+    /// 振動するプロングのうち、柄に負荷として作用する質量の割合です。
+    pub const MODAL_MASS_RATIO: f32 = 0.2357;
+    /// 片持ち梁の付け根から支持部へ逃げる振動エネルギーによる損失 (アンカー損失) の係数。
+    /// アンカー損失によるQ値を `Q_anchor = ANCHOR_LOSS_COEFFICIENT · (L / t)³` と近似します
+    /// (`L`はプロングの長さ、`t`は振動方向の厚さ)。
+    pub const ANCHOR_LOSS_COEFFICIENT: f32 = 2.17;
+
+    /// 材料定数の基準温度 (℃)。ヤング率などの値はこの温度での値です。
+    pub const REFERENCE_TEMPERATURE: f32 = 20.0;
 
     /// 損失関数におけるペナルティ項の重み。
     /// `ratio_penalty`（プロング長 > 柄長）に対する重み。
@@ -24,12 +55,150 @@ pub mod physics {
     pub const PENALTY_WEIGHT_RANGE: f32 = 10.0;
     /// `range_penalty`（その他の寸法の範囲）に対する重み。
     pub const PENALTY_WEIGHT_OTHER: f32 = 5.0;
+
+    /// 音叉の材質。
+    #[derive(Config, Debug, Copy, PartialEq)]
+    pub enum Material {
+        /// ステンレス鋼 SUS304 (デフォルト)。
+        Steel304,
+        /// アルミニウム合金。
+        Aluminum,
+        /// 真鍮。
+        Brass,
+        /// チタン合金 (Ti-6Al-4V)。
+        Titanium,
+    }
+
+    impl Material {
+        /// すべての材質。
+        pub const ALL: [Material; 4] = [
+            Material::Steel304,
+            Material::Aluminum,
+            Material::Brass,
+            Material::Titanium,
+        ];
+
+        /// ヤング率 (Pa)。
+        pub fn youngs_modulus(&self) -> f32 {
+            match self {
+                Material::Steel304 => YOUNGS_MODULUS,
+                Material::Aluminum => 69.0e9,
+                Material::Brass => 100.0e9,
+                Material::Titanium => 114.0e9,
+            }
+        }
+
+        /// 密度 (kg/m^3)。
+        pub fn density(&self) -> f32 {
+            match self {
+                Material::Steel304 => DENSITY,
+                Material::Aluminum => 2700.0,
+                Material::Brass => 8500.0,
+                Material::Titanium => 4430.0,
+            }
+        }
+
+        /// ポアソン比。
+        pub fn poisson_ratio(&self) -> f32 {
+            match self {
+                Material::Steel304 => POISSON_RATIO,
+                Material::Aluminum => 0.33,
+                Material::Brass => 0.34,
+                Material::Titanium => 0.34,
+            }
+        }
+
+        /// ヤング率の温度係数 (1/K、代表値)。温度が上がるとヤング率は下がります
+        /// ([`Self::youngs_modulus_at`]を参照)。
+        pub fn modulus_temperature_coefficient(&self) -> f32 {
+            match self {
+                Material::Steel304 => -4.0e-4,
+                Material::Aluminum => -5.0e-4,
+                Material::Brass => -4.0e-4,
+                Material::Titanium => -4.5e-4,
+            }
+        }
+
+        /// 温度 `temperature` (℃) でのヤング率 (Pa)。
+        /// 基準温度 ([`REFERENCE_TEMPERATURE`]) からの線形近似 `E₀ · (1 + α · (T − T₀))` です。
+        pub fn youngs_modulus_at(&self, temperature: f32) -> f32 {
+            let delta = temperature - REFERENCE_TEMPERATURE;
+            self.youngs_modulus() * (1.0 + self.modulus_temperature_coefficient() * delta)
+        }
+
+        /// 棒を伝わる縦波の速さ `√(E / ρ)` (m/s)。
+        /// 同じ寸法の音叉の曲げ振動の周波数は、この値に比例します (Euler-Bernoulli梁)。
+        pub fn wave_speed(&self) -> f32 {
+            (self.youngs_modulus() / self.density()).sqrt()
+        }
+
+        /// 材料の内部摩擦による損失係数 `η` (代表値)。材料だけで決まるQ値の上限は `1 / η` です。
+        pub fn loss_factor(&self) -> f32 {
+            match self {
+                Material::Steel304 => 2.0e-4,
+                Material::Aluminum => 1.0e-4,
+                Material::Brass => 1.0e-3,
+                Material::Titanium => 5.0e-4,
+            }
+        }
+    }
+
+    impl FromStr for Material {
+        type Err = String;
+
+        /// `steel304`、`aluminum`、`brass`、`titanium` を解析します (大文字・小文字は区別しません)。
+        fn from_str(s: &str) -> Result<Self, Self::Err> {
+            match s.to_ascii_lowercase().as_str() {
+                "steel304" | "sus304" => Ok(Material::Steel304),
+                "aluminum" | "aluminium" => Ok(Material::Aluminum),
+                "brass" => Ok(Material::Brass),
+                "titanium" => Ok(Material::Titanium),
+                _ => Err(format!(
+                    "unknown material '{s}' (expected steel304, aluminum, brass or titanium)"
+                )),
+            }
+        }
+    }
+}
+
+/// 製造可能とみなす寸法の範囲 (単位: m)
+///
+/// 損失関数の範囲ペナルティ、物理的妥当性のメトリクス、推論結果の検証で共通して使用します。
+pub mod bounds {
+    /// 柄の長さの下限
+    pub const HANDLE_LENGTH_MIN: f32 = 0.03;
+    /// 柄の長さの上限
+    pub const HANDLE_LENGTH_MAX: f32 = 0.15;
+    /// 柄の直径の下限
+    pub const HANDLE_DIAMETER_MIN: f32 = 0.005;
+    /// 柄の直径の上限
+    pub const HANDLE_DIAMETER_MAX: f32 = 0.02;
+    /// プロングの長さの下限
+    pub const PRONG_LENGTH_MIN: f32 = 0.01;
+    /// プロングの長さの上限
+    pub const PRONG_LENGTH_MAX: f32 = 0.2;
+    /// プロングの直径 (矩形断面では幅) の下限
+    pub const PRONG_DIAMETER_MIN: f32 = 0.002;
+    /// プロングの直径 (矩形断面では幅) の上限
+    pub const PRONG_DIAMETER_MAX: f32 = 0.02;
+    /// 矩形断面のプロングの厚さの下限 (直径と同じ範囲)
+    pub const PRONG_HEIGHT_MIN: f32 = PRONG_DIAMETER_MIN;
+    /// 矩形断面のプロングの厚さの上限 (直径と同じ範囲)
+    pub const PRONG_HEIGHT_MAX: f32 = PRONG_DIAMETER_MAX;
+    /// プロングの間隔の下限
+    pub const PRONG_GAP_MIN: f32 = 0.002;
+    /// プロングの間隔の上限
+    pub const PRONG_GAP_MAX: f32 = 0.02;
 }
 
 /// モデルの寸法に関する定数
 pub mod model_dims {
-    /// 出力次元の総数
+    /// 出力次元の総数 (円形断面)
     pub const NUM_DIMS: usize = 5;
+    /// 矩形断面のプロングを使用する場合の出力次元の総数
+    pub const NUM_DIMS_RECTANGULAR: usize = 6;
+    /// Q値を予測する場合に、寸法の後 (最後の列) に追加する出力の数。値は `log10(Q)` です。
+    pub const NUM_Q_FACTOR_OUTPUTS: usize = 1;
     /// 柄の長さのインデックス
     pub const HANDLE_LENGTH_IDX: usize = 0;
     /// 柄の直径のインデックス
@@ -40,4 +209,8 @@ pub mod model_dims {
     pub const PRONG_DIAMETER_IDX: usize = 3;
     /// プロングの間隔のインデックス
     pub const PRONG_GAP_IDX: usize = 4;
+    /// 矩形断面のプロングの幅 (振動方向に垂直) のインデックス。円形断面の直径と同じ位置
+    pub const PRONG_WIDTH_IDX: usize = PRONG_DIAMETER_IDX;
+    /// 矩形断面のプロングの厚さ (振動方向) のインデックス
+    pub const PRONG_HEIGHT_IDX: usize = 5;
 }
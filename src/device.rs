@@ -0,0 +1,83 @@
+//! # デバイスの選択
+//!
+//! コマンドラインで指定されたインデックスから計算デバイスを構築し、使用可能かを検証します。
+//! また、このビルドに組み込まれたバックエンドの一覧を提供します。
+
+use burn::backend::wgpu::{Wgpu, WgpuDevice};
+use burn::prelude::*;
+use std::{fmt, panic};
+
+/// デバイスの選択で発生するエラー。
+#[derive(Debug)]
+pub enum DeviceError {
+    /// 指定されたインデックスのデバイスが存在しない、または初期化できない。
+    Unavailable(String),
+}
+
+impl fmt::Display for DeviceError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            DeviceError::Unavailable(msg) => write!(f, "Device is not available: {msg}"),
+        }
+    }
+}
+
+impl std::error::Error for DeviceError {}
+
+/// このビルドに組み込まれたバックエンドの名前 (`--backend`に指定する値) を返します。
+///
+/// `wgpu`・`ndarray`・`cuda`は常に含まれ、`tch`・`candle`はそれぞれ同名のフィーチャーを
+/// 有効にしてビルドした場合にのみ含まれます。[`crate::backend`]の登録表と同じ
+/// フィーチャーの条件を使用しています。
+pub fn available_backends() -> Vec<&'static str> {
+    #[allow(unused_mut)]
+    let mut backends = vec!["wgpu", "ndarray", "cuda"];
+    #[cfg(feature = "tch")]
+    backends.push("tch");
+    #[cfg(feature = "candle")]
+    backends.push("candle");
+    backends
+}
+
+/// `wgpu`バックエンドで使用するデバイスを返します。
+///
+/// `index`が`None`の場合は`WgpuDevice::default()`を返します。指定された場合は
+/// `index`番目のディスクリートGPU (`WgpuDevice::DiscreteGpu`) を選択し、
+/// [`probe_device`]で使用可能かを検証します。
+///
+/// # Errors
+///
+/// 指定されたインデックスのGPUが存在しない場合に`DeviceError::Unavailable`を返します。
+pub fn wgpu_device(index: Option<usize>) -> Result<WgpuDevice, DeviceError> {
+    match index {
+        None => Ok(WgpuDevice::default()),
+        Some(index) => {
+            let device = WgpuDevice::DiscreteGpu(index);
+            probe_device::<Wgpu>(&device)
+                .map_err(|msg| DeviceError::Unavailable(format!("GPU index {index}: {msg}")))?;
+            Ok(device)
+        }
+    }
+}
+
+/// デバイス上で小さなテンソルを作成して読み戻し、デバイスが使用可能かを検証します。
+///
+/// `wgpu`は存在しないアダプタを指定すると、最初のテンソル演算の時点で内部でパニックします。
+/// 学習や推論の途中で分かりにくいパニックが起きないよう、ここでパニックを捕捉して
+/// エラーメッセージとして返します。
+///
+/// パニックフックはプロセス全体で共有されるため置き換えません。そのため、捕捉したパニックの
+/// メッセージも通常どおり標準エラー出力に表示されます。
+pub fn probe_device<B: Backend>(device: &B::Device) -> Result<(), String> {
+    let result = panic::catch_unwind(panic::AssertUnwindSafe(|| {
+        Tensor::<B, 1>::zeros([1], device).into_data();
+    }));
+
+    result.map_err(|payload| {
+        payload
+            .downcast_ref::<String>()
+            .cloned()
+            .or_else(|| payload.downcast_ref::<&str>().map(|msg| msg.to_string()))
+            .unwrap_or_else(|| "device initialization failed".to_string())
+    })
+}
@@ -0,0 +1,434 @@
+//! # モデルのエクスポート
+//!
+//! 学習済みモデルを、Rust以外の実行環境で利用できる形式に書き出します。
+//!
+//! - ONNX: BurnにはONNXの読み込み (`burn-import`) はありますが書き出しの機能はないため、
+//!   モデルの構造 (全結合層 + 活性化関数 + `softplus`) に合わせてprotobufを直接エンコードします。
+//! - SafeTensors: 各全結合層の重みとバイアスを、Pythonのツールから読める形式で書き出します。
+//! - OpenSCAD: 指定した周波数で推論した音叉の形状 ([`crate::geometry`]) をスクリプトとして
+//!   書き出します。
+//! - STL: 同じ形状を三角形メッシュに分割し、ASCII形式のSTLとして書き出します。
+
+use crate::geometry;
+use crate::infer::{self, InferError};
+use crate::model::{Activation, TuningForkPINN};
+use burn::prelude::*;
+use safetensors::tensor::{Dtype, TensorView};
+use std::{collections::HashMap, fmt, fs, path::Path};
+
+/// ONNXのIRバージョン (opset 17に対応する8)。
+const ONNX_IR_VERSION: i64 = 8;
+/// 使用するONNXのopsetのバージョン。
+const ONNX_OPSET_VERSION: i64 = 17;
+/// `TensorProto.DataType.FLOAT`。
+const ONNX_FLOAT: i64 = 1;
+
+/// ONNXモデルの入力テンソル名 (形状 `[batch, 1]` の周波数。材質や温度を入力とするモデルでは
+/// その特徴量の列が続きます ([`crate::model::TuningForkPINN::with_conditions`]を参照))。
+pub const ONNX_INPUT_NAME: &str = "frequency";
+/// ONNXモデルの出力テンソル名 (形状 `[batch, output_size]` の寸法)。
+pub const ONNX_OUTPUT_NAME: &str = "dimensions";
+
+/// エクスポートで発生するエラー。
+#[derive(Debug)]
+pub enum ExportError {
+    /// 学習済みモデルの読み込みに失敗した。
+    Load(InferError),
+    /// ファイルの書き込みに失敗した。
+    Write(String),
+}
+
+impl fmt::Display for ExportError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ExportError::Load(err) => write!(f, "{err}"),
+            ExportError::Write(msg) => write!(f, "Failed to write exported model: {msg}"),
+        }
+    }
+}
+
+impl std::error::Error for ExportError {}
+
+impl From<InferError> for ExportError {
+    fn from(err: InferError) -> Self {
+        ExportError::Load(err)
+    }
+}
+
+/// 成果物ディレクトリの学習済みモデルを読み込み、ONNX形式で`output`に書き出します。
+///
+/// # Errors
+///
+/// モデルが読み込めない、またはファイルを書き込めない場合に`ExportError`を返します。
+pub fn run_onnx<B: Backend>(
+    artifact_dir: &str,
+    output: &Path,
+    device: B::Device,
+) -> Result<(), ExportError> {
+    let model = infer::load_model::<B>(artifact_dir, &device)?;
+    fs::write(output, to_onnx(&model))
+        .map_err(|err| ExportError::Write(format!("{}: {err}", output.display())))
+}
+
+/// モデルをONNX (opset 17) のバイト列にエンコードします。
+///
+/// 推論時の計算グラフを書き出すため、ドロップアウトは含みません。各演算は次のように
+/// 対応します。
+///
+/// - 入力の正規化: `Sub`・`Div` (材質や温度を入力とするモデルでは、周波数の列のみを正規化)
+/// - 全結合層: `Gemm` (Burnの重みは`[d_input, d_output]`で、`Gemm`の`B`と同じ配置)
+/// - バッチ正規化: 推論時の移動平均を使う`BatchNormalization` (`epsilon`はBurnと同じ既定値)
+/// - 残差接続: 2層目以降の隠れ層の`Gemm`の後に`Add`
+/// - 活性化関数: `Relu`・`Tanh`はそのまま、opset 17に単独の演算がない`SiLU`は
+///   `Sigmoid`と`Mul`、`GELU`は`Erf`を使った厳密な式 `0.5·x·(1 + erf(x/√2))` に分解
+/// - 出力: `Softplus` (`softplus_beta`が1でないモデルでは、`Mul`で`β`倍してから適用し`Div`で
+///   `β`で割る)
+pub fn to_onnx<B: Backend>(model: &TuningForkPINN<B>) -> Vec<u8> {
+    let mut graph = GraphBuilder::default();
+    let (freq_mean, freq_std) = model.input_normalization();
+    // 材質・温度の特徴量の列は、平均0・標準偏差1として正規化せずに通す
+    let input_size = model.input_size();
+    let mut means = vec![0.0; input_size];
+    let mut stds = vec![1.0; input_size];
+    means[0] = freq_mean;
+    stds[0] = freq_std;
+    graph.initializer("freq_mean", &[input_size], &means);
+    graph.initializer("freq_std", &[input_size], &stds);
+    let centered = graph.node("Sub", &[ONNX_INPUT_NAME, "freq_mean"]);
+    let mut x = graph.node("Div", &[&centered, "freq_std"]);
+    if *model.activation() == Activation::Gelu {
+        graph.initializer("gelu_sqrt2", &[], &[std::f32::consts::SQRT_2]);
+        graph.initializer("gelu_one", &[], &[1.0]);
+        graph.initializer("gelu_half", &[], &[0.5]);
+    }
+
+    let layers: Vec<_> = model.linear_layers().collect();
+    let num_layers = layers.len();
+    let mut output_size = 0;
+    for (i, layer) in layers.into_iter().enumerate() {
+        let [d_input, d_output] = layer.weight.val().dims();
+        let weight_name = format!("{}.weight", layer_name(i));
+        graph.initializer(&weight_name, &[d_input, d_output], &to_vec(layer.weight.val()));
+        let bias_name = format!("{}.bias", layer_name(i));
+        let bias = match &layer.bias {
+            Some(bias) => to_vec(bias.val()),
+            None => vec![0.0; d_output],
+        };
+        graph.initializer(&bias_name, &[d_output], &bias);
+        let residual = x.clone();
+        x = graph.node("Gemm", &[&x, &weight_name, &bias_name]);
+        if let Some(batch_norm) = model.batch_norms().get(i) {
+            let prefix = format!("{}.batch_norm", layer_name(i));
+            let names = ["scale", "bias", "mean", "var"].map(|name| format!("{prefix}.{name}"));
+            let values = [
+                batch_norm.gamma.val(),
+                batch_norm.beta.val(),
+                batch_norm.running_mean.value(),
+                batch_norm.running_var.value(),
+            ];
+            for (name, value) in names.iter().zip(values) {
+                graph.initializer(name, &[d_output], &to_vec(value));
+            }
+            let [scale, bias, mean, var] = names.each_ref().map(String::as_str);
+            x = graph.node("BatchNormalization", &[&x, scale, bias, mean, var]);
+        }
+        if model.residual() && i > 0 && i + 1 < num_layers {
+            x = graph.node("Add", &[&x, &residual]);
+        }
+
+        if i + 1 < num_layers {
+            x = graph.activation(model.activation(), &x);
+        }
+        output_size = d_output;
+    }
+    let beta = model.softplus_beta();
+    if beta == 1.0 {
+        graph.output_node("Softplus", &[&x], ONNX_OUTPUT_NAME);
+    } else {
+        graph.initializer("softplus_beta", &[], &[beta as f32]);
+        let scaled = graph.node("Mul", &[&x, "softplus_beta"]);
+        let activated = graph.node("Softplus", &[&scaled]);
+        graph.output_node("Div", &[&activated, "softplus_beta"], ONNX_OUTPUT_NAME);
+    }
+
+    graph.into_model(input_size, output_size)
+}
+
+/// 成果物ディレクトリの学習済みモデルを読み込み、重みをSafeTensors形式で`output`に書き出します。
+///
+/// # Errors
+///
+/// モデルが読み込めない、またはファイルを書き込めない場合に`ExportError`を返します。
+pub fn run_safetensors<B: Backend>(
+    artifact_dir: &str,
+    output: &Path,
+    device: B::Device,
+) -> Result<(), ExportError> {
+    let model = infer::load_model::<B>(artifact_dir, &device)?;
+    let bytes = to_safetensors(&model)?;
+    fs::write(output, bytes)
+        .map_err(|err| ExportError::Write(format!("{}: {err}", output.display())))
+}
+
+/// モデルの重みをSafeTensors形式のバイト列にエンコードします。
+///
+/// 入力側から`i`番目 (1始まり) の全結合層の重みとバイアスを`layer_{i}.weight`
+/// (形状 `[d_input, d_output]`)・`layer_{i}.bias` (形状 `[d_output]`) として書き出します。
+/// 重みはBurnの配置のままのため、PyTorchの`nn.Linear` (`[d_output, d_input]`) に読み込む
+/// 場合は転置してください。バッチ正規化が有効な場合は、隠れ層ごとに
+/// `layer_{i}.batch_norm.{gamma,beta,running_mean,running_var}`も書き出します。
+/// 入力正規化の定数は`input.freq_mean`・`input.freq_std`、
+/// 活性化関数と出力層の`softplus`の`β`はメタデータの`activation`・`softplus_beta`に
+/// 格納します。
+///
+/// # Errors
+///
+/// エンコードに失敗した場合に`ExportError::Write`を返します。
+pub fn to_safetensors<B: Backend>(model: &TuningForkPINN<B>) -> Result<Vec<u8>, ExportError> {
+    let (freq_mean, freq_std) = model.input_normalization();
+    let mut tensors = vec![
+        ("input.freq_mean".to_string(), vec![1], vec![freq_mean]),
+        ("input.freq_std".to_string(), vec![1], vec![freq_std]),
+    ];
+    for (i, layer) in model.linear_layers().enumerate() {
+        let [d_input, d_output] = layer.weight.val().dims();
+        let bias = match &layer.bias {
+            Some(bias) => to_vec(bias.val()),
+            None => vec![0.0; d_output],
+        };
+        tensors.push((
+            format!("{}.weight", layer_name(i)),
+            vec![d_input, d_output],
+            to_vec(layer.weight.val()),
+        ));
+        tensors.push((format!("{}.bias", layer_name(i)), vec![d_output], bias));
+        if let Some(batch_norm) = model.batch_norms().get(i) {
+            let prefix = format!("{}.batch_norm", layer_name(i));
+            for (name, value) in [
+                ("gamma", batch_norm.gamma.val()),
+                ("beta", batch_norm.beta.val()),
+                ("running_mean", batch_norm.running_mean.value()),
+                ("running_var", batch_norm.running_var.value()),
+            ] {
+                tensors.push((format!("{prefix}.{name}"), vec![d_output], to_vec(value)));
+            }
+        }
+    }
+
+    let raw: Vec<(String, Vec<usize>, Vec<u8>)> = tensors
+        .into_iter()
+        .map(|(name, shape, values)| {
+            let bytes = values.iter().flat_map(|value| value.to_le_bytes()).collect();
+            (name, shape, bytes)
+        })
+        .collect();
+    let views = raw
+        .iter()
+        .map(|(name, shape, bytes)| {
+            TensorView::new(Dtype::F32, shape.clone(), bytes).map(|view| (name.as_str(), view))
+        })
+        .collect::<Result<Vec<_>, _>>()
+        .map_err(|err| ExportError::Write(err.to_string()))?;
+    let metadata = HashMap::from([
+        ("activation".to_string(), format!("{:?}", model.activation())),
+        ("softplus_beta".to_string(), model.softplus_beta().to_string()),
+    ]);
+
+    safetensors::serialize(views, &Some(metadata))
+        .map_err(|err| ExportError::Write(err.to_string()))
+}
+
+/// 成果物ディレクトリの学習済みモデルで`freq` (Hz) の寸法を推論し、音叉の形状を
+/// OpenSCADのスクリプトとして`output`に書き出します ([`geometry::to_openscad`]を参照)。
+///
+/// # Errors
+///
+/// モデルが読み込めない、周波数が不正、またはファイルを書き込めない場合に`ExportError`を返します。
+pub fn run_scad<B: Backend>(
+    artifact_dir: &str,
+    freq: f32,
+    output: &Path,
+    device: B::Device,
+) -> Result<(), ExportError> {
+    let dims = infer::run_batch::<B>(artifact_dir, &[freq], None, 1, device, None)?.remove(0);
+    fs::write(output, geometry::to_openscad(&dims))
+        .map_err(|err| ExportError::Write(format!("{}: {err}", output.display())))
+}
+
+/// 成果物ディレクトリの学習済みモデルで`freq` (Hz) の寸法を推論し、音叉の形状を
+/// 円周を`segments`分割したASCII形式のSTLとして`output`に書き出します
+/// ([`geometry::to_stl`]を参照)。
+///
+/// # Errors
+///
+/// `segments`が3未満、モデルが読み込めない、周波数が不正、またはファイルを書き込めない場合に
+/// `ExportError`を返します。
+pub fn run_stl<B: Backend>(
+    artifact_dir: &str,
+    freq: f32,
+    segments: usize,
+    output: &Path,
+    device: B::Device,
+) -> Result<(), ExportError> {
+    if segments < 3 {
+        return Err(InferError::InvalidInput(format!(
+            "segments must be at least 3, got {segments}"
+        ))
+        .into());
+    }
+    let dims = infer::run_batch::<B>(artifact_dir, &[freq], None, 1, device, None)?.remove(0);
+    fs::write(output, geometry::to_stl(&dims, segments))
+        .map_err(|err| ExportError::Write(format!("{}: {err}", output.display())))
+}
+
+/// 入力側から`index`番目 (0始まり) の全結合層の、エクスポート時の名前 (`layer_1`など) を返します。
+fn layer_name(index: usize) -> String {
+    format!("layer_{}", index + 1)
+}
+
+/// テンソルの値を`f32`のベクトルとして取り出します。
+fn to_vec<B: Backend, const D: usize>(tensor: Tensor<B, D>) -> Vec<f32> {
+    tensor.into_data().convert::<f32>().into_vec().unwrap()
+}
+
+/// ONNXの計算グラフ (`GraphProto`) を組み立てます。
+#[derive(Default)]
+struct GraphBuilder {
+    nodes: Vec<ProtoMessage>,
+    initializers: Vec<ProtoMessage>,
+}
+
+impl GraphBuilder {
+    /// 定数テンソル (`TensorProto`) を追加します。
+    fn initializer(&mut self, name: &str, dims: &[usize], values: &[f32]) {
+        let raw: Vec<u8> = values.iter().flat_map(|value| value.to_le_bytes()).collect();
+        let tensor = dims
+            .iter()
+            .fold(ProtoMessage::default(), |tensor, &dim| tensor.int(1, dim as i64))
+            .int(2, ONNX_FLOAT)
+            .string(8, name)
+            .bytes(9, &raw);
+        self.initializers.push(tensor);
+    }
+
+    /// 演算ノードを追加し、自動で名付けた出力テンソル名を返します。
+    fn node(&mut self, op_type: &str, inputs: &[&str]) -> String {
+        let output = format!("{}_{}", op_type.to_ascii_lowercase(), self.nodes.len());
+        self.output_node(op_type, inputs, &output);
+        output
+    }
+
+    /// 出力テンソル名を指定して演算ノード (`NodeProto`) を追加します。
+    fn output_node(&mut self, op_type: &str, inputs: &[&str], output: &str) {
+        let node = inputs
+            .iter()
+            .fold(ProtoMessage::default(), |node, input| node.string(1, input))
+            .string(2, output)
+            .string(3, &format!("node_{}", self.nodes.len()))
+            .string(4, op_type);
+        self.nodes.push(node);
+    }
+
+    /// 活性化関数のノードを追加し、出力テンソル名を返します。
+    fn activation(&mut self, activation: &Activation, x: &str) -> String {
+        match activation {
+            Activation::Relu => self.node("Relu", &[x]),
+            Activation::Tanh => self.node("Tanh", &[x]),
+            Activation::SiLU => {
+                let sigmoid = self.node("Sigmoid", &[x]);
+                self.node("Mul", &[x, &sigmoid])
+            }
+            Activation::Gelu => {
+                // 定数 (`gelu_sqrt2`など) は`to_onnx`で一度だけ追加される
+                let scaled = self.node("Div", &[x, "gelu_sqrt2"]);
+                let erf = self.node("Erf", &[&scaled]);
+                let shifted = self.node("Add", &[&erf, "gelu_one"]);
+                let product = self.node("Mul", &[x, &shifted]);
+                self.node("Mul", &[&product, "gelu_half"])
+            }
+        }
+    }
+
+    /// グラフを`ModelProto`にまとめ、バイト列を返します。
+    fn into_model(self, input_size: usize, output_size: usize) -> Vec<u8> {
+        let graph = self
+            .nodes
+            .into_iter()
+            .fold(ProtoMessage::default(), |graph, node| graph.message(1, node))
+            .string(2, "tuning_fork_pinn");
+        let graph = self
+            .initializers
+            .into_iter()
+            .fold(graph, |graph, tensor| graph.message(5, tensor))
+            .message(11, value_info(ONNX_INPUT_NAME, input_size))
+            .message(12, value_info(ONNX_OUTPUT_NAME, output_size));
+
+        let opset = ProtoMessage::default()
+            .string(1, "")
+            .int(2, ONNX_OPSET_VERSION);
+        ProtoMessage::default()
+            .int(1, ONNX_IR_VERSION)
+            .string(2, env!("CARGO_PKG_NAME"))
+            .string(3, env!("CARGO_PKG_VERSION"))
+            .message(7, graph)
+            .message(8, opset)
+            .0
+    }
+}
+
+/// 形状 `[batch, width]` (`batch`は可変) の`float`テンソルの`ValueInfoProto`を作成します。
+fn value_info(name: &str, width: usize) -> ProtoMessage {
+    let batch = ProtoMessage::default().string(2, "batch");
+    let width = ProtoMessage::default().int(1, width as i64);
+    let shape = ProtoMessage::default().message(1, batch).message(1, width);
+    let tensor_type = ProtoMessage::default()
+        .int(1, ONNX_FLOAT)
+        .message(2, shape);
+    let type_proto = ProtoMessage::default().message(1, tensor_type);
+    ProtoMessage::default().string(1, name).message(2, type_proto)
+}
+
+/// protobufのメッセージをエンコードする最小限のビルダー。
+///
+/// ONNXのエクスポートに必要な可変長整数・長さ付きフィールドのみに対応します。
+#[derive(Default)]
+struct ProtoMessage(Vec<u8>);
+
+impl ProtoMessage {
+    /// 可変長整数 (varint) を追加します。
+    fn varint(mut self, mut value: u64) -> Self {
+        while value >= 0x80 {
+            self.0.push((value & 0x7f) as u8 | 0x80);
+            value >>= 7;
+        }
+        self.0.push(value as u8);
+        self
+    }
+
+    /// フィールド番号とワイヤー型からなるキーを追加します。
+    fn key(self, field: u32, wire_type: u8) -> Self {
+        self.varint((u64::from(field) << 3) | u64::from(wire_type))
+    }
+
+    /// 整数フィールド (`int32`・`int64`・列挙型) を追加します。
+    fn int(self, field: u32, value: i64) -> Self {
+        self.key(field, 0).varint(value as u64)
+    }
+
+    /// バイト列フィールドを追加します。
+    fn bytes(self, field: u32, data: &[u8]) -> Self {
+        let mut message = self.key(field, 2).varint(data.len() as u64);
+        message.0.extend_from_slice(data);
+        message
+    }
+
+    /// 文字列フィールドを追加します。
+    fn string(self, field: u32, value: &str) -> Self {
+        self.bytes(field, value.as_bytes())
+    }
+
+    /// 埋め込みメッセージのフィールドを追加します。
+    fn message(self, field: u32, message: ProtoMessage) -> Self {
+        self.bytes(field, &message.0)
+    }
+}
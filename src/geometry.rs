@@ -0,0 +1,246 @@
+//! # 音叉の形状
+//!
+//! 推論した寸法から、音叉の3次元形状を可視化・造形するためのデータを生成します。
+//!
+//! 形状は次の円柱を組み合わせた簡略化モデルです (単位はmm、z軸が音叉の長手方向)。
+//!
+//! - 柄: 原点を底面の中心とする、長さ`handle_length`・直径`handle_diameter`の円柱。
+//! - ブリッジ: 柄の上端で2本のプロングをつなぐ、幅`prong_gap + 2 · prong_diameter`・
+//!   奥行きと高さが`prong_diameter`の直方体。
+//! - プロング: ブリッジの上に、中心間の距離が`prong_gap + prong_diameter`
+//!   (内側の間隔が`prong_gap`) となるよう並べた、長さ`prong_length`・直径`prong_diameter`の円柱。
+
+use crate::infer::ForkDimensions;
+
+/// 円柱を近似する多角形の分割数のデフォルト (OpenSCADの`$fn`、STLの円周方向の分割数)。
+pub const DEFAULT_SEGMENTS: usize = 64;
+
+/// 3次元の点またはベクトル (mm)。
+pub type Vec3 = [f32; 3];
+
+/// メッシュを構成する三角形。頂点は外側から見て反時計回りの順に並びます。
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Triangle {
+    /// 3つの頂点。
+    pub vertices: [Vec3; 3],
+}
+
+impl Triangle {
+    /// 頂点の順序から求めた外向きの単位法線ベクトル。面積が0の場合はゼロベクトルです。
+    pub fn normal(&self) -> Vec3 {
+        let [a, b, c] = self.vertices;
+        let u = [b[0] - a[0], b[1] - a[1], b[2] - a[2]];
+        let v = [c[0] - a[0], c[1] - a[1], c[2] - a[2]];
+        let n = [
+            u[1] * v[2] - u[2] * v[1],
+            u[2] * v[0] - u[0] * v[2],
+            u[0] * v[1] - u[1] * v[0],
+        ];
+        let length = (n[0] * n[0] + n[1] * n[1] + n[2] * n[2]).sqrt();
+        if length > 0.0 {
+            n.map(|x| x / length)
+        } else {
+            [0.0; 3]
+        }
+    }
+}
+
+/// 寸法をメートルからミリメートルに換算した、形状の生成に使う値。
+struct ForkGeometry {
+    handle_length: f32,
+    handle_diameter: f32,
+    prong_length: f32,
+    prong_diameter: f32,
+    prong_gap: f32,
+}
+
+impl ForkGeometry {
+    fn from_dims(dims: &ForkDimensions) -> Self {
+        let mm = |meters: f32| meters * 1000.0;
+        Self {
+            handle_length: mm(dims.handle_length),
+            handle_diameter: mm(dims.handle_diameter),
+            prong_length: mm(dims.prong_length),
+            prong_diameter: mm(dims.prong_diameter),
+            prong_gap: mm(dims.prong_gap),
+        }
+    }
+
+    /// 各プロングの中心のx座標。
+    fn prong_offsets(&self) -> [f32; 2] {
+        let offset = (self.prong_gap + self.prong_diameter) / 2.0;
+        [-offset, offset]
+    }
+
+    /// プロングの底面 (ブリッジの上面) のz座標。
+    fn prong_base(&self) -> f32 {
+        self.handle_length + self.prong_diameter
+    }
+}
+
+/// 寸法から、音叉を構築するOpenSCADのスクリプトを生成します。
+///
+/// 寸法はミリメートルに換算し、小数点以下3桁 (1µm) で出力します。形状はモジュールの
+/// ドキュメントを参照してください。
+pub fn to_openscad(dims: &ForkDimensions) -> String {
+    let fork = ForkGeometry::from_dims(dims);
+    let bridge_width = fork.prong_gap + 2.0 * fork.prong_diameter;
+
+    let mut script =
+        String::from("// Tuning fork generated by burn-tuningfork-pinn (units: mm)\n");
+    script.push_str(&format!("$fn = {DEFAULT_SEGMENTS};\n\n"));
+    script.push_str("union() {\n");
+    script.push_str("    // handle\n");
+    script.push_str(&format!(
+        "    cylinder(h = {:.3}, d = {:.3});\n",
+        fork.handle_length, fork.handle_diameter
+    ));
+    script.push_str("    // bridge\n");
+    script.push_str(&format!(
+        "    translate([{:.3}, {:.3}, {:.3}]) cube([{:.3}, {:.3}, {:.3}]);\n",
+        -bridge_width / 2.0,
+        -fork.prong_diameter / 2.0,
+        fork.handle_length,
+        bridge_width,
+        fork.prong_diameter,
+        fork.prong_diameter
+    ));
+    script.push_str("    // prongs\n");
+    for offset in fork.prong_offsets() {
+        script.push_str(&format!(
+            "    translate([{offset:.3}, 0, {:.3}]) cylinder(h = {:.3}, d = {:.3});\n",
+            fork.prong_base(),
+            fork.prong_length,
+            fork.prong_diameter
+        ));
+    }
+    script.push_str("}\n");
+    script
+}
+
+/// 寸法から、音叉の形状を近似する三角形メッシュを生成します。
+///
+/// 柄と2本のプロングは、円周を`segments`分割した側面と、中心からの扇形で閉じた上下の面を
+/// 持つ円柱 (1本あたり`4 · segments`個の三角形)、ブリッジは12個の三角形の直方体で表します。
+/// 三角形の総数は`12 · segments + 12`です。
+///
+/// # Panics
+///
+/// `segments`が3未満の場合にパニックします。
+pub fn to_mesh(dims: &ForkDimensions, segments: usize) -> Vec<Triangle> {
+    assert!(segments >= 3, "segments must be at least 3, got {segments}");
+    let fork = ForkGeometry::from_dims(dims);
+    let bridge_width = fork.prong_gap + 2.0 * fork.prong_diameter;
+
+    let mut triangles = Vec::with_capacity(12 * segments + 12);
+    push_cylinder(
+        &mut triangles,
+        [0.0, 0.0, 0.0],
+        fork.handle_diameter / 2.0,
+        fork.handle_length,
+        segments,
+    );
+    push_box(
+        &mut triangles,
+        [
+            -bridge_width / 2.0,
+            -fork.prong_diameter / 2.0,
+            fork.handle_length,
+        ],
+        [
+            bridge_width / 2.0,
+            fork.prong_diameter / 2.0,
+            fork.prong_base(),
+        ],
+    );
+    for offset in fork.prong_offsets() {
+        push_cylinder(
+            &mut triangles,
+            [offset, 0.0, fork.prong_base()],
+            fork.prong_diameter / 2.0,
+            fork.prong_length,
+            segments,
+        );
+    }
+    triangles
+}
+
+/// 寸法から、音叉の形状をASCII形式のSTLとして生成します ([`to_mesh`]を参照)。
+///
+/// # Panics
+///
+/// `segments`が3未満の場合にパニックします。
+pub fn to_stl(dims: &ForkDimensions, segments: usize) -> String {
+    let mut stl = String::from("solid tuning_fork\n");
+    for triangle in to_mesh(dims, segments) {
+        let [nx, ny, nz] = triangle.normal();
+        stl.push_str(&format!("  facet normal {nx:e} {ny:e} {nz:e}\n    outer loop\n"));
+        for [x, y, z] in triangle.vertices {
+            stl.push_str(&format!("      vertex {x:e} {y:e} {z:e}\n"));
+        }
+        stl.push_str("    endloop\n  endfacet\n");
+    }
+    stl.push_str("endsolid tuning_fork\n");
+    stl
+}
+
+/// 底面の中心が`base`でz軸方向に伸びる、上下の面を閉じた円柱の三角形を追加します。
+fn push_cylinder(
+    triangles: &mut Vec<Triangle>,
+    base: Vec3,
+    radius: f32,
+    height: f32,
+    segments: usize,
+) {
+    let [x, y, z0] = base;
+    let z1 = z0 + height;
+    let ring = |i: usize, z: f32| {
+        let theta = std::f32::consts::TAU * (i % segments) as f32 / segments as f32;
+        [x + radius * theta.cos(), y + radius * theta.sin(), z]
+    };
+    for i in 0..segments {
+        let (b0, b1) = (ring(i, z0), ring(i + 1, z0));
+        let (t0, t1) = (ring(i, z1), ring(i + 1, z1));
+        triangles.push(Triangle {
+            vertices: [b0, b1, t1],
+        });
+        triangles.push(Triangle {
+            vertices: [b0, t1, t0],
+        });
+        triangles.push(Triangle {
+            vertices: [[x, y, z1], t0, t1],
+        });
+        triangles.push(Triangle {
+            vertices: [[x, y, z0], b1, b0],
+        });
+    }
+}
+
+/// 対角の頂点が`min`と`max`である、軸に平行な直方体の三角形を追加します。
+fn push_box(triangles: &mut Vec<Triangle>, min: Vec3, max: Vec3) {
+    let corner = |i: usize, j: usize, k: usize| {
+        [
+            if i == 0 { min[0] } else { max[0] },
+            if j == 0 { min[1] } else { max[1] },
+            if k == 0 { min[2] } else { max[2] },
+        ]
+    };
+    // 各面の4頂点を外側から見て反時計回りに並べる
+    let faces = [
+        [(0, 0, 0), (0, 1, 0), (1, 1, 0), (1, 0, 0)],
+        [(0, 0, 1), (1, 0, 1), (1, 1, 1), (0, 1, 1)],
+        [(0, 0, 0), (1, 0, 0), (1, 0, 1), (0, 0, 1)],
+        [(0, 1, 0), (0, 1, 1), (1, 1, 1), (1, 1, 0)],
+        [(0, 0, 0), (0, 0, 1), (0, 1, 1), (0, 1, 0)],
+        [(1, 0, 0), (1, 1, 0), (1, 1, 1), (1, 0, 1)],
+    ];
+    for face in faces {
+        let [a, b, c, d] = face.map(|(i, j, k)| corner(i, j, k));
+        triangles.push(Triangle {
+            vertices: [a, b, c],
+        });
+        triangles.push(Triangle {
+            vertices: [a, c, d],
+        });
+    }
+}
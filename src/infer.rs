@@ -3,49 +3,1425 @@
 //! 学習済みのモデルを読み込み、指定された周波数に対する音叉の寸法を推論します。
 //! このモジュールは、任意のバックエンドで動作するようにジェネリックになっています。
 
-use crate::model::TuningForkPINN;
-use burn::{
-    prelude::*,
-    record::{CompactRecorder, Recorder},
+use crate::constants::{bounds, model_dims, physics::Material};
+use crate::metadata::{METADATA_FILE, ModelMetadata};
+use crate::metrics::EvaluationSummary;
+use crate::model::{ModelConfig, TuningForkPINN};
+use crate::physics::{PhysicsConfig, frequency_from_dims, frequency_from_dims_with_config};
+use crate::record::{self, RecordFormat};
+use crate::train::TrainingConfig;
+use burn::prelude::*;
+use burn::tensor::backend::AutodiffBackend;
+use log::warn;
+use serde::{Deserialize, Serialize};
+use std::{
+    cmp::Ordering,
+    fmt,
+    io::{BufRead, Read, Write},
+    path::Path,
+    str::FromStr,
 };
 
+/// 学習済みモデルの成果物が保存されるデフォルトのディレクトリ。
+pub const DEFAULT_ARTIFACT_DIR: &str = "./artifacts";
+
+/// 学習設定を保存するファイル名 (成果物ディレクトリからの相対パス)。
+pub const TRAINING_CONFIG_FILE: &str = "config.json";
+
+/// 実現周波数の誤差に対して警告を表示するデフォルトの閾値 (Hz)。
+pub const DEFAULT_FREQ_ERROR_WARN_HZ: f32 = 5.0;
+
+/// バッチ推論 ([`run_batch`]) で一度のフォワードパスに渡す周波数の数のデフォルト値。
+///
+/// 周波数の数が非常に多い場合でも、GPUのメモリ使用量がこの数の入力分に抑えられます。
+pub const DEFAULT_CHUNK_SIZE: usize = 4096;
+
+/// バッチ推論の進捗を受け取るコールバック。引数は処理済みの周波数の数と、周波数の総数です。
+pub type ProgressCallback<'a> = &'a mut dyn FnMut(usize, usize);
+
+/// 対話的な推論 ([`repl`]) で、次の周波数の入力を待つ間に表示するプロンプト。
+pub const REPL_PROMPT: &str = "freq (Hz)> ";
+
+/// 推論処理で発生するエラー。
+#[derive(Debug)]
+pub enum InferError {
+    /// モデルファイルが見つからない。値は探索したパス。
+    ModelNotFound(String),
+    /// モデルレコードの読み込みに失敗した。
+    RecordLoad(String),
+    /// モデルレコードの保存に失敗した。
+    RecordSave(String),
+    /// 入力値が不正。
+    InvalidInput(String),
+    /// CSVの読み書きに失敗した。
+    CsvParse(String),
+    /// 保存された学習設定の読み込みに失敗した。
+    ConfigLoad(String),
+    /// 保存されたモデルのメタデータの読み込みに失敗した。
+    MetadataLoad(String),
+    /// 対話的な推論で入出力に失敗した。
+    Io(String),
+}
+
+impl fmt::Display for InferError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            InferError::ModelNotFound(path) => write!(
+                f,
+                "Model file not found at '{path}'. Run training first via `cargo run --release -- train`"
+            ),
+            InferError::RecordLoad(msg) => write!(f, "Failed to load model record: {msg}"),
+            InferError::RecordSave(msg) => write!(f, "Failed to save model record: {msg}"),
+            InferError::InvalidInput(msg) => write!(f, "Invalid input: {msg}"),
+            InferError::CsvParse(msg) => write!(f, "CSV error: {msg}"),
+            InferError::ConfigLoad(msg) => write!(f, "Failed to load training config: {msg}"),
+            InferError::MetadataLoad(msg) => write!(f, "Failed to load model metadata: {msg}"),
+            InferError::Io(msg) => write!(f, "I/O error: {msg}"),
+        }
+    }
+}
+
+impl std::error::Error for InferError {}
+
+/// 寸法を表示する際の単位。内部では常にメートルで保持します。
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Unit {
+    /// メートル (デフォルト)。
+    #[default]
+    Meters,
+    /// ミリメートル。
+    Millimeters,
+}
+
+impl Unit {
+    /// メートルからこの単位への換算係数。
+    pub fn scale(&self) -> f32 {
+        match self {
+            Unit::Meters => 1.0,
+            Unit::Millimeters => 1000.0,
+        }
+    }
+
+    /// 表示用の単位名。
+    pub fn label(&self) -> &'static str {
+        match self {
+            Unit::Meters => "meters",
+            Unit::Millimeters => "millimeters",
+        }
+    }
+}
+
+impl FromStr for Unit {
+    type Err = String;
+
+    /// `m`/`meters`、`mm`/`millimeters` を解析します。
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_ascii_lowercase().as_str() {
+            "m" | "meters" => Ok(Unit::Meters),
+            "mm" | "millimeters" => Ok(Unit::Millimeters),
+            _ => Err(format!("unknown unit '{s}' (expected m or mm)")),
+        }
+    }
+}
+
+/// 推論結果の出力形式。
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum OutputFormat {
+    /// 人が読むための表 (デフォルト)。
+    #[default]
+    Text,
+    /// スクリプトから扱うためのJSON ([`to_json`]を参照)。
+    Json,
+}
+
+impl FromStr for OutputFormat {
+    type Err = String;
+
+    /// `text`、`json`を解析します。
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_ascii_lowercase().as_str() {
+            "text" => Ok(OutputFormat::Text),
+            "json" => Ok(OutputFormat::Json),
+            _ => Err(format!("unknown output format '{s}' (expected text or json)")),
+        }
+    }
+}
+
+/// 推論された音叉の寸法 (単位: メートル)。
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct ForkDimensions {
+    /// 柄の長さ。
+    pub handle_length: f32,
+    /// 柄の直径。
+    pub handle_diameter: f32,
+    /// プロングの長さ。
+    pub prong_length: f32,
+    /// プロングの直径。
+    pub prong_diameter: f32,
+    /// プロングの間隔。
+    pub prong_gap: f32,
+}
+
+impl ForkDimensions {
+    /// モデル出力の1行分 (`model_dims::NUM_DIMS`個の値) から寸法を構築します。
+    pub(crate) fn from_slice(values: &[f32]) -> Self {
+        Self {
+            handle_length: values[model_dims::HANDLE_LENGTH_IDX],
+            handle_diameter: values[model_dims::HANDLE_DIAMETER_IDX],
+            prong_length: values[model_dims::PRONG_LENGTH_IDX],
+            prong_diameter: values[model_dims::PRONG_DIAMETER_IDX],
+            prong_gap: values[model_dims::PRONG_GAP_IDX],
+        }
+    }
+
+    /// 寸法をモデル出力と同じ順序 (`model_dims`のインデックス順) の配列として返します。
+    pub fn to_array(&self) -> [f32; model_dims::NUM_DIMS] {
+        let mut values = [0.0; model_dims::NUM_DIMS];
+        values[model_dims::HANDLE_LENGTH_IDX] = self.handle_length;
+        values[model_dims::HANDLE_DIAMETER_IDX] = self.handle_diameter;
+        values[model_dims::PRONG_LENGTH_IDX] = self.prong_length;
+        values[model_dims::PRONG_DIAMETER_IDX] = self.prong_diameter;
+        values[model_dims::PRONG_GAP_IDX] = self.prong_gap;
+        values
+    }
+
+    /// 各寸法が製造可能な範囲に収まっているかを検証します。
+    ///
+    /// `softplus`は寸法が正であることしか保証しないため、範囲を外れた寸法を
+    /// [`ConstraintViolation`]の一覧として返します。すべて範囲内であれば空です。
+    pub fn validate(&self) -> Vec<ConstraintViolation> {
+        self.bounded_fields()
+            .into_iter()
+            .filter(|&(_, value, min, max)| !(min..=max).contains(&value))
+            .map(|(field, value, min, max)| ConstraintViolation {
+                field,
+                value,
+                min,
+                max,
+            })
+            .collect()
+    }
+
+    /// 各寸法を製造可能な範囲 ([`crate::constants::bounds`]) に収めた値を返します。
+    ///
+    /// 範囲を外れた寸法は近い方の境界値になります。どの寸法が変更されたかは、元の寸法の
+    /// [`Self::validate`]で確認できます。
+    pub fn clamp(&self) -> Self {
+        let values = self.bounded_fields().map(|(_, value, min, max)| value.clamp(min, max));
+        Self::from_slice(&values)
+    }
+
+    /// 寸法ごとの名前・値・下限・上限を、[`Self::to_array`]と同じ順序で返します。
+    fn bounded_fields(&self) -> [(&'static str, f32, f32, f32); model_dims::NUM_DIMS] {
+        [
+            (
+                "handle_length",
+                self.handle_length,
+                bounds::HANDLE_LENGTH_MIN,
+                bounds::HANDLE_LENGTH_MAX,
+            ),
+            (
+                "handle_diameter",
+                self.handle_diameter,
+                bounds::HANDLE_DIAMETER_MIN,
+                bounds::HANDLE_DIAMETER_MAX,
+            ),
+            (
+                "prong_length",
+                self.prong_length,
+                bounds::PRONG_LENGTH_MIN,
+                bounds::PRONG_LENGTH_MAX,
+            ),
+            (
+                "prong_diameter",
+                self.prong_diameter,
+                bounds::PRONG_DIAMETER_MIN,
+                bounds::PRONG_DIAMETER_MAX,
+            ),
+            (
+                "prong_gap",
+                self.prong_gap,
+                bounds::PRONG_GAP_MIN,
+                bounds::PRONG_GAP_MAX,
+            ),
+        ]
+    }
+
+    /// 寸法をJSON文字列 (フィールド名はこの構造体と同じ、単位はメートル) に変換します。
+    ///
+    /// 設計結果を保存し、[`Self::from_json`]で読み込み直す (`forward --dims-json`など) ために
+    /// 使用します。
+    pub fn to_json(&self) -> String {
+        serde_json::to_string_pretty(self).expect("fork dimensions are always serializable")
+    }
+
+    /// [`Self::to_json`]の形式のJSON文字列から寸法を読み込みます。
+    ///
+    /// # Errors
+    ///
+    /// JSONとして解析できない場合、または寸法のフィールドが欠けている場合に
+    /// `InferError::InvalidInput`を返します。
+    pub fn from_json(json: &str) -> Result<Self, InferError> {
+        serde_json::from_str(json)
+            .map_err(|err| InferError::InvalidInput(format!("fork dimensions JSON: {err}")))
+    }
+
+    /// 各寸法を指定された単位に換算した値を返します。
+    pub fn to_unit(&self, unit: Unit) -> Self {
+        let scale = unit.scale();
+        Self {
+            handle_length: self.handle_length * scale,
+            handle_diameter: self.handle_diameter * scale,
+            prong_length: self.prong_length * scale,
+            prong_diameter: self.prong_diameter * scale,
+            prong_gap: self.prong_gap * scale,
+        }
+    }
+}
+
+/// 寸法が製造可能な範囲 ([`crate::constants::bounds`]) を外れていることを表します。
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct ConstraintViolation {
+    /// 範囲を外れた寸法の名前 (例: `prong_diameter`)。
+    pub field: &'static str,
+    /// 予測された値 (m)。
+    pub value: f32,
+    /// 許容される下限 (m)。
+    pub min: f32,
+    /// 許容される上限 (m)。
+    pub max: f32,
+}
+
+impl fmt::Display for ConstraintViolation {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{} = {:.6} m is outside the allowed range [{}, {}] m",
+            self.field, self.value, self.min, self.max
+        )
+    }
+}
+
+/// 推論結果と、その寸法が実際に生み出す周波数をまとめたレポート。
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct InferenceReport {
+    /// 推論の入力とした目標周波数 (Hz)。
+    pub target_frequency: f32,
+    /// 予測された寸法。
+    pub dimensions: ForkDimensions,
+    /// 予測寸法から物理モデルで逆算した実現周波数 (Hz)。
+    pub realized_frequency: f32,
+    /// 目標周波数と実現周波数の絶対誤差 (Hz)。
+    pub frequency_error: f32,
+    /// 予測されたQ値。Q値を出力しないモデルでは`None`です。
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub q_factor: Option<f32>,
+    /// 寸法を製造可能な範囲に収める前の、モデルが予測したままの寸法
+    /// ([`Self::with_clamped_dimensions`]を適用した場合のみ)。
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub unclamped_dimensions: Option<ForkDimensions>,
+}
+
+impl InferenceReport {
+    /// 目標周波数・予測寸法・実現周波数からレポートを作成します。
+    pub fn new(target_frequency: f32, dimensions: ForkDimensions, realized_frequency: f32) -> Self {
+        Self {
+            target_frequency,
+            dimensions,
+            realized_frequency,
+            frequency_error: (realized_frequency - target_frequency).abs(),
+            q_factor: None,
+            unclamped_dimensions: None,
+        }
+    }
+
+    /// 予測されたQ値を設定します。
+    pub fn with_q_factor(mut self, q_factor: Option<f32>) -> Self {
+        self.q_factor = q_factor;
+        self
+    }
+
+    /// 予測寸法を製造可能な範囲に収め ([`ForkDimensions::clamp`])、実現周波数と誤差を
+    /// 収めた後の寸法で計算し直します (`infer --clamp`)。
+    ///
+    /// 元の寸法は[`Self::unclamped_dimensions`]に残ります。
+    pub fn with_clamped_dimensions(self, physics: &PhysicsConfig) -> Self {
+        let unclamped = self.unclamped_dimensions.unwrap_or(self.dimensions);
+        let dimensions = unclamped.clamp();
+        let realized_frequency = frequency_from_dims_with_config(&dimensions, physics);
+        Self {
+            unclamped_dimensions: Some(unclamped),
+            ..Self::new(self.target_frequency, dimensions, realized_frequency)
+                .with_q_factor(self.q_factor)
+        }
+    }
+
+    /// 範囲に収めるために変更された寸法を、元の値とともに返します。
+    /// [`Self::with_clamped_dimensions`]を適用していない場合は空です。
+    pub fn clamped_fields(&self) -> Vec<ConstraintViolation> {
+        self.unclamped_dimensions
+            .map(|dims| dims.validate())
+            .unwrap_or_default()
+    }
+
+    /// 周波数誤差が閾値 (Hz) を超えているかを返します。
+    pub fn exceeds(&self, threshold_hz: f32) -> bool {
+        self.frequency_error > threshold_hz
+    }
+}
+
+/// ネットワークの出力を、出力層の`softplus`の適用前と適用後で並べたもの (`debug`サブコマンド)。
+#[derive(Debug, Clone, PartialEq)]
+pub struct RawOutputs {
+    /// 入力とした周波数 (Hz)。
+    pub frequency: f32,
+    /// 出力の列ごとの名前。
+    pub labels: Vec<&'static str>,
+    /// `softplus`の適用前の値 ([`TuningForkPINN::forward_raw`])。
+    pub raw: Vec<f32>,
+    /// `softplus`の適用後の値 ([`TuningForkPINN::forward`])。
+    pub activated: Vec<f32>,
+}
+
+/// モンテカルロ・ドロップアウトで推定した、寸法ごとの平均と標準偏差 (単位: メートル)。
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct DimensionUncertainty {
+    /// 各寸法のサンプル平均。
+    pub mean: ForkDimensions,
+    /// 各寸法のサンプル標準偏差 (母標準偏差)。
+    pub std_dev: ForkDimensions,
+}
+
+/// JSON出力の内容。レポートの各項目と、指定された場合は不確かさ・感度・メタデータを含みます。
+#[derive(Serialize)]
+struct JsonOutput<'a> {
+    #[serde(flatten)]
+    report: &'a InferenceReport,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    uncertainty: Option<&'a DimensionUncertainty>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    sensitivity: Option<&'a ForkDimensions>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    metadata: Option<&'a ModelMetadata>,
+}
+
+/// 推論レポートを整形されたJSONに変換します。
+///
+/// 寸法は`--unit`に関わらず常にメートルで出力します。`uncertainty`を指定した場合は
+/// `uncertainty`フィールド (`mean`・`std_dev`) が、`sensitivity` ([`sensitivity`]の結果、
+/// m/Hz) を指定した場合は寸法ごとの`sensitivity`フィールドが、`metadata`を指定した場合は
+/// `metadata`フィールドが追加されます。
+pub fn to_json(
+    report: &InferenceReport,
+    uncertainty: Option<&DimensionUncertainty>,
+    sensitivity: Option<&[f32; model_dims::NUM_DIMS]>,
+    metadata: Option<&ModelMetadata>,
+) -> String {
+    let sensitivity = sensitivity.map(|gradients| ForkDimensions::from_slice(gradients));
+    let output = JsonOutput {
+        report,
+        uncertainty,
+        sensitivity: sensitivity.as_ref(),
+        metadata,
+    };
+    serde_json::to_string_pretty(&output).expect("inference report is always serializable")
+}
+
+/// 推論結果を指定された単位に換算し、整形して標準出力に表示します。
+pub fn print_dimensions(dims: &ForkDimensions, unit: Unit) {
+    let dims = dims.to_unit(unit);
+    // ミリメートルでは小数点以下3桁で、メートルと同じ1µmの分解能になる
+    let precision = match unit {
+        Unit::Meters => 6,
+        Unit::Millimeters => 3,
+    };
+    println!("\n--- Predicted Dimensions (in {}) ---", unit.label());
+    println!("  - Handle Length:     {:.precision$}", dims.handle_length);
+    println!("  - Handle Diameter:   {:.precision$}", dims.handle_diameter);
+    println!("  - Prong Length:      {:.precision$}", dims.prong_length);
+    println!("  - Prong Diameter:    {:.precision$}", dims.prong_diameter);
+    println!("  - Prong Gap:         {:.precision$}", dims.prong_gap);
+    println!("----------------------------------------");
+}
+
+/// 推論レポートを表示します。
+///
+/// 予測寸法を`unit`で表示し、実現周波数と誤差を併せて表示します。
+/// 誤差が`warn_threshold_hz`を超える場合や、寸法が製造可能な範囲を外れている場合は
+/// 警告を表示します。
+pub fn print_report(report: &InferenceReport, warn_threshold_hz: f32, unit: Unit) {
+    print_dimensions(&report.dimensions, unit);
+    println!("  - Realized Frequency: {:.3} Hz", report.realized_frequency);
+    println!("  - Frequency Error:    {:.3} Hz", report.frequency_error);
+    if let Some(q_factor) = report.q_factor {
+        println!("  - Q Factor:           {q_factor:.0}");
+    }
+    if report.exceeds(warn_threshold_hz) {
+        warn!(
+            "⚠️  Frequency error exceeds {warn_threshold_hz} Hz; the model may need more training."
+        );
+    }
+    for violation in report.clamped_fields() {
+        let clamped = violation.value.clamp(violation.min, violation.max);
+        println!(
+            "  - 📌 Clamped {} from {:.6} m to {clamped:.6} m",
+            violation.field, violation.value
+        );
+    }
+    for violation in report.dimensions.validate() {
+        warn!("⚠️  {violation}");
+    }
+}
+
+/// [`forward`]で計算した周波数と、寸法が製造可能な範囲を外れている項目を表示します。
+pub fn print_forward(dims: &ForkDimensions, frequency: f32) {
+    println!("\n--- Frequency from Dimensions ---");
+    println!("  - Frequency:          {frequency:.3} Hz");
+    let violations = dims.validate();
+    if violations.is_empty() {
+        println!("  - Constraints:        all dimensions are within range");
+    }
+    for violation in violations {
+        println!("  - ⚠️  {violation}");
+    }
+    println!("----------------------------------------");
+}
+
+/// 目標周波数の一覧に対する評価結果 (`eval`サブコマンド) を表示します。
+pub fn print_evaluation(summary: &EvaluationSummary) {
+    println!("\n--- Evaluation ---");
+    println!("  - Predictions:        {}", summary.num_predictions);
+    println!("  - Mean error:         {:.3} Hz", summary.mean_error_hz);
+    println!("  - Max error:          {:.3} Hz", summary.max_error_hz);
+    println!("  - p50 error:          {:.3} Hz", summary.p50_error_hz);
+    println!("  - p90 error:          {:.3} Hz", summary.p90_error_hz);
+    println!("  - p99 error:          {:.3} Hz", summary.p99_error_hz);
+    println!("  - Physical validity:  {:.1}%", summary.validity_percent);
+    println!("----------------------------------------");
+}
+
+/// ネットワークの出力を列ごとに表示します。`show_raw`が`true`の場合は`softplus`の適用前の
+/// 値も並べて表示します。
+pub fn print_raw_outputs(outputs: &RawOutputs, show_raw: bool) {
+    println!("\n--- Network Outputs for {} Hz ---", outputs.frequency);
+    for (i, label) in outputs.labels.iter().enumerate() {
+        let label = format!("{label}:");
+        let activated = outputs.activated[i];
+        if show_raw {
+            println!("  - {label:<18} raw {:>12.6}  softplus {activated:.6}", outputs.raw[i]);
+        } else {
+            println!("  - {label:<18} {activated:.6}");
+        }
+    }
+    println!("----------------------------------------");
+}
+
+/// 予測寸法の周波数に対する感度 ([`sensitivity`]) を、`unit`で指定した単位/Hzで表示します。
+pub fn print_sensitivity(gradients: &[f32; model_dims::NUM_DIMS], unit: Unit) {
+    let gradients = ForkDimensions::from_slice(gradients).to_unit(unit);
+    let rows = [
+        ("Handle Length", gradients.handle_length),
+        ("Handle Diameter", gradients.handle_diameter),
+        ("Prong Length", gradients.prong_length),
+        ("Prong Diameter", gradients.prong_diameter),
+        ("Prong Gap", gradients.prong_gap),
+    ];
+    println!("\n--- Sensitivity (in {}/Hz) ---", unit.label());
+    for (label, gradient) in rows {
+        println!("  - {:<18} {gradient:+.3e}", format!("{label}:"));
+    }
+    println!("----------------------------------------");
+}
+
+/// 不確かさの推定結果を、寸法ごとに「平均 ± 標準偏差」の形式で表示します。
+pub fn print_uncertainty(uncertainty: &DimensionUncertainty, unit: Unit) {
+    let mean = uncertainty.mean.to_unit(unit);
+    let std_dev = uncertainty.std_dev.to_unit(unit);
+    let precision = match unit {
+        Unit::Meters => 6,
+        Unit::Millimeters => 3,
+    };
+    let rows = [
+        ("Handle Length", mean.handle_length, std_dev.handle_length),
+        ("Handle Diameter", mean.handle_diameter, std_dev.handle_diameter),
+        ("Prong Length", mean.prong_length, std_dev.prong_length),
+        ("Prong Diameter", mean.prong_diameter, std_dev.prong_diameter),
+        ("Prong Gap", mean.prong_gap, std_dev.prong_gap),
+    ];
+    println!("\n--- Predicted Dimensions (in {}, mean ± std) ---", unit.label());
+    for (label, mean, std_dev) in rows {
+        println!(
+            "  - {:<18} {mean:.precision$} ± {std_dev:.precision$}",
+            format!("{label}:")
+        );
+    }
+    println!("------------------------------------------------");
+}
+
 /// 推論プロセスを実行します。
 ///
+/// 予測寸法から物理モデルで逆算した実現周波数も併せて計算します。Q値を出力するモデル
+/// ([`ModelConfig::q_factor`]) では、予測されたQ値もレポートに含めます。
+///
 /// # Arguments
 ///
+/// * `artifact_dir` - 学習済みモデルが保存されているディレクトリ (例: [`DEFAULT_ARTIFACT_DIR`])。
 /// * `freq` - 推論したい音叉の周波数 (Hz)。
+/// * `physics` - 実現周波数の計算に使用する物理モデル (材質・振動モードなど)。
+///   `None`の場合は、学習時に保存された設定 ([`physics_config_for`]) を使用します。
 /// * `device` - 推論に使用するデバイス（例: `WgpuDevice`、`NdArrayDevice`）。
 ///
-/// # Panics
+/// # Errors
 ///
-/// モデルファイルの読み込みに失敗した場合にパニックします。
-pub fn run<B: Backend>(freq: f32, device: B::Device) {
-    let artifact_dir = "./artifacts";
-    let model_path = format!("{artifact_dir}/model");
+/// モデルファイルが存在しない、モデルや学習設定の読み込みに失敗した、または
+/// 周波数・物理モデルの設定が不正な場合に`InferError`を返します。
+pub fn run<B: Backend>(
+    artifact_dir: &str,
+    freq: f32,
+    physics: Option<&PhysicsConfig>,
+    device: B::Device,
+) -> Result<InferenceReport, InferError> {
+    let physics = match physics {
+        Some(physics) => physics.clone(),
+        None => physics_config_for(artifact_dir)?,
+    };
+    physics.validate().map_err(InferError::InvalidInput)?;
+    validate_frequencies(&[freq])?;
 
-    // 保存されたモデルのレコードを読み込む
-    let record = CompactRecorder::new()
-        .load(model_path.into(), &device)
-        .expect(
-            "Failed to load model record. Run training first via `cargo run --release -- train`",
-        );
+    let model = load_model::<B>(artifact_dir, &device)?;
+    ensure_circular_output(model.output_size())?;
+    let dims = model
+        .predict_batch_with_physics(&[freq], &physics, &device)?
+        .remove(0);
+    let q_factor = model
+        .predict_q_factor_batch(&[freq], &physics, &device)
+        .map(|q_factors| q_factors[0]);
+    let realized_frequency = frequency_from_dims_with_config(&dims, &physics);
+    Ok(InferenceReport::new(freq, dims, realized_frequency).with_q_factor(q_factor))
+}
+
+/// 指定された寸法から、ネットワークを使わずに物理モデルだけで音叉の周波数 (Hz) を計算します
+/// (`forward`サブコマンド)。
+///
+/// 推論とは逆方向の計算で、[`frequency_from_dims`]に寸法をそのまま渡します。寸法が製造可能な
+/// 範囲を外れていてもエラーにはしないため、範囲は[`ForkDimensions::validate`]で確認してください。
+///
+/// # Errors
+///
+/// 寸法が正の有限値でない、または振動モードの次数が対応範囲外の場合に
+/// `InferError::InvalidInput`を返します。
+pub fn forward(dims: &ForkDimensions, material: Material, mode: u8) -> Result<f32, InferError> {
+    PhysicsConfig::new()
+        .with_mode(mode)
+        .validate()
+        .map_err(InferError::InvalidInput)?;
+    if let Some(value) = dims.to_array().iter().find(|v| !v.is_finite() || **v <= 0.0) {
+        return Err(InferError::InvalidInput(format!(
+            "dimensions must be positive finite numbers, got {value}"
+        )));
+    }
+    Ok(frequency_from_dims(dims, material, mode))
+}
+
+/// モンテカルロ・ドロップアウトにより、予測寸法の平均と標準偏差を推定します。
+///
+/// 学習済みモデルを自動微分が有効なバックエンド`B`で読み込み、ドロップアウトを有効にしたまま
+/// 同じ周波数を`samples`回推論します。ドロップアウトの確率は学習時に保存された設定
+/// ([`TRAINING_CONFIG_FILE`]) から復元されます。`physics`を省略した場合は、学習時に
+/// 保存された設定 ([`physics_config_for`]) の材質・温度として推論します。
+///
+/// ドロップアウトを0.0で学習したモデルでは、すべてのサンプルが同じ値になり標準偏差は0に
+/// なります。意味のある不確かさを得るには、0より大きいドロップアウトで学習してください。
+///
+/// # Errors
+///
+/// モデルや学習設定が読み込めない、周波数が不正、または`samples`が0の場合に
+/// `InferError`を返します。
+pub fn run_with_uncertainty<B: AutodiffBackend>(
+    artifact_dir: &str,
+    freq: f32,
+    samples: usize,
+    physics: Option<&PhysicsConfig>,
+    device: B::Device,
+) -> Result<DimensionUncertainty, InferError> {
+    let physics = match physics {
+        Some(physics) => physics.clone(),
+        None => physics_config_for(artifact_dir)?,
+    };
+    let model = load_model::<B>(artifact_dir, &device)?;
+    predict_with_uncertainty(&model, freq, samples, &physics, &device)
+}
+
+/// 与えられたモデルでモンテカルロ・ドロップアウトを実行します ([`run_with_uncertainty`]を参照)。
+///
+/// 同じ周波数を`samples`行並べた入力で一度に推論します。ドロップアウトのマスクは要素ごとに
+/// 独立に生成されるため、各行が独立したサンプルになります。
+///
+/// 材質や温度を入力とするモデルでは、`physics`の材質・温度として推論します。
+///
+/// # Errors
+///
+/// 周波数が不正、`samples`が0、モデルの出力が円形断面の寸法数と異なる、またはモデルが
+/// バッチ正規化を含む場合に`InferError`を返します。自動微分が有効なバックエンドでは
+/// バッチ正規化がミニバッチの統計量を使うため、同じ周波数を並べた入力では正しく推論できません。
+pub fn predict_with_uncertainty<B: AutodiffBackend>(
+    model: &TuningForkPINN<B>,
+    freq: f32,
+    samples: usize,
+    physics: &PhysicsConfig,
+    device: &B::Device,
+) -> Result<DimensionUncertainty, InferError> {
+    validate_frequencies(&[freq])?;
+    if samples == 0 {
+        return Err(InferError::InvalidInput("samples must be at least 1".to_string()));
+    }
+    if !model.batch_norms().is_empty() {
+        return Err(InferError::InvalidInput(
+            "uncertainty estimation is not supported for models with batch normalization"
+                .to_string(),
+        ));
+    }
+
+    let input = Tensor::<B, 2>::full([samples, 1], freq, device);
+    let dims = model
+        .forward(model.with_conditions(input, physics))
+        .detach();
+    ensure_circular_output(dims.dims()[1])?;
+
+    let mean = dims.clone().mean_dim(0);
+    let variance = (dims - mean.clone()).powf_scalar(2.0).mean_dim(0);
+    let to_dimensions = |tensor: Tensor<B, 2>| {
+        let values: Vec<f32> = tensor.into_data().convert::<f32>().into_vec().unwrap();
+        ForkDimensions::from_slice(&values)
+    };
+
+    Ok(DimensionUncertainty {
+        mean: to_dimensions(mean),
+        std_dev: to_dimensions(variance.sqrt()),
+    })
+}
+
+/// 予測寸法の入力周波数に対する感度 `∂寸法/∂周波数` (m/Hz) を計算します。
+///
+/// 学習済みモデルを自動微分が有効なバックエンド`B`で読み込み、[`sensitivity`]を計算します。
+/// 勾配が確率的にならないよう、ドロップアウトは無効にして読み込みます。`physics`を省略した
+/// 場合は、学習時に保存された設定 ([`physics_config_for`]) の材質・温度として計算します。
+///
+/// # Errors
+///
+/// モデルや学習設定が読み込めない場合、または[`sensitivity`]がエラーを返した場合に
+/// `InferError`を返します。
+pub fn run_sensitivity<B: AutodiffBackend>(
+    artifact_dir: &str,
+    freq: f32,
+    physics: Option<&PhysicsConfig>,
+    device: B::Device,
+) -> Result<[f32; model_dims::NUM_DIMS], InferError> {
+    let physics = match physics {
+        Some(physics) => physics.clone(),
+        None => physics_config_for(artifact_dir)?,
+    };
+    let model = load_model_with::<B>(artifact_dir, &device, |config| config.with_dropout(0.0))?;
+    sensitivity(&model, freq, &physics, &device)
+}
+
+/// 各予測寸法の入力周波数に対する勾配 `∂寸法/∂周波数` (m/Hz) を自動微分で計算します。
+///
+/// 学習と同じ自動微分の仕組みを使い、寸法ごとにフォワードパスと逆伝播を行います。
+/// 戻り値は[`ForkDimensions::to_array`]と同じ順序です。ドロップアウトが有効なモデルでは
+/// 勾配も確率的になる点に注意してください ([`run_sensitivity`]はドロップアウトを無効にします)。
+/// 材質や温度を入力とするモデルでは、`physics`の材質・温度として計算します。
+///
+/// # Errors
+///
+/// 周波数が不正、モデルの出力が円形断面の寸法数と異なる、またはモデルがバッチ正規化を
+/// 含む場合に`InferError`を返します。自動微分が有効なバックエンドではバッチ正規化が
+/// ミニバッチの統計量を使うため、1つの周波数では正しく微分できません。
+pub fn sensitivity<B: AutodiffBackend>(
+    model: &TuningForkPINN<B>,
+    freq: f32,
+    physics: &PhysicsConfig,
+    device: &B::Device,
+) -> Result<[f32; model_dims::NUM_DIMS], InferError> {
+    validate_frequencies(&[freq])?;
+    ensure_circular_output(model.output_size())?;
+    if !model.batch_norms().is_empty() {
+        return Err(InferError::InvalidInput(
+            "sensitivity analysis is not supported for models with batch normalization"
+                .to_string(),
+        ));
+    }
+
+    let mut gradients = [0.0; model_dims::NUM_DIMS];
+    for (i, gradient) in gradients.iter_mut().enumerate() {
+        // 出力ごとに計算グラフを作り直し、その寸法だけを逆伝播する
+        let input = Tensor::<B, 2>::from_floats([[freq]], device).require_grad();
+        let model_input = model.with_conditions(input.clone(), physics);
+        let grads = model.forward(model_input).narrow(1, i, 1).sum().backward();
+        *gradient = input
+            .grad(&grads)
+            .map_or(0.0, |grad| grad.into_scalar().elem::<f32>());
+    }
+    Ok(gradients)
+}
+
+/// 成果物ディレクトリに保存された学習設定 ([`TRAINING_CONFIG_FILE`]) を読み込みます。
+///
+/// 設定ファイルが存在しない場合 (設定の保存に対応する前に学習したモデルなど) は
+/// `None`を返します。
+///
+/// # Errors
+///
+/// 設定ファイルが存在するが読み込めない場合に`InferError::ConfigLoad`を返します。
+pub fn load_training_config(artifact_dir: &str) -> Result<Option<TrainingConfig>, InferError> {
+    let path = format!("{artifact_dir}/{TRAINING_CONFIG_FILE}");
+    if !Path::new(&path).exists() {
+        return Ok(None);
+    }
+    TrainingConfig::load(&path)
+        .map(Some)
+        .map_err(|err| InferError::ConfigLoad(format!("{path}: {err}")))
+}
+
+/// 成果物ディレクトリに保存されたモデルのメタデータ ([`METADATA_FILE`]) を読み込みます。
+///
+/// メタデータが存在しない場合 (メタデータの保存に対応する前に学習したモデルなど) は
+/// `None`を返します。
+///
+/// # Errors
+///
+/// メタデータが存在するが読み込めない場合に`InferError::MetadataLoad`を返します。
+pub fn load_metadata(artifact_dir: &str) -> Result<Option<ModelMetadata>, InferError> {
+    let path = format!("{artifact_dir}/{METADATA_FILE}");
+    if !Path::new(&path).exists() {
+        return Ok(None);
+    }
+    ModelMetadata::load(&path)
+        .map(Some)
+        .map_err(|err| InferError::MetadataLoad(format!("{path}: {err}")))
+}
+
+/// 学習時と同じ物理モデル (材質・振動モード・断面形状など) の設定を返します。
+///
+/// 保存された学習設定がなければデフォルトの[`PhysicsConfig`]を返します。
+pub fn physics_config_for(artifact_dir: &str) -> Result<PhysicsConfig, InferError> {
+    Ok(load_training_config(artifact_dir)?
+        .map(|config| config.loss_config().physics)
+        .unwrap_or_else(PhysicsConfig::new))
+}
+
+/// 複数の周波数に対する推論を、`chunk_size`個ずつのフォワードパスで実行します。
+///
+/// 各チャンクの結果を連結して返すため、結果は`chunk_size`によらず同じです。
+/// 入力が空の場合は、モデルの読み込みやデバイスへのアクセスを行わずに空の`Vec`を返します。
+///
+/// # Arguments
+///
+/// * `artifact_dir` - 学習済みモデルが保存されているディレクトリ。
+/// * `freqs` - 推論したい周波数 (Hz) のスライス。
+/// * `physics` - 材質や温度を入力とするモデルに与える材質・温度。`None`の場合は学習時に
+///   保存された設定 ([`physics_config_for`]) を使用します。
+/// * `chunk_size` - 一度のフォワードパスに渡す周波数の数 (通常は[`DEFAULT_CHUNK_SIZE`])。
+///   メモリ使用量の上限を決めます。
+/// * `device` - 推論に使用するデバイス。
+/// * `progress` - 周波数を`chunk_size`個処理するごと (最後は残りを処理したとき) に
+///   呼び出されるコールバック。進捗バーなどの表示は呼び出し側で行います。
+///
+/// # Returns
+///
+/// 入力と同じ順序で並んだ、各周波数に対する予測寸法。
+///
+/// # Errors
+///
+/// `chunk_size`が0、周波数が不正、またはモデルや学習設定が読み込めない場合に`InferError`を
+/// 返します。
+pub fn run_batch<B: Backend>(
+    artifact_dir: &str,
+    freqs: &[f32],
+    physics: Option<&PhysicsConfig>,
+    chunk_size: usize,
+    device: B::Device,
+    mut progress: Option<ProgressCallback<'_>>,
+) -> Result<Vec<ForkDimensions>, InferError> {
+    if chunk_size == 0 {
+        return Err(InferError::InvalidInput("chunk size must be at least 1".to_string()));
+    }
+    if freqs.is_empty() {
+        return Ok(Vec::new());
+    }
+    validate_frequencies(freqs)?;
+    let physics = match physics {
+        Some(physics) => physics.clone(),
+        None => physics_config_for(artifact_dir)?,
+    };
 
-    // レコードからモデルを復元
-    let model: TuningForkPINN<B> = TuningForkPINN::new(&device).load_record(record);
+    let model = load_model::<B>(artifact_dir, &device)?;
+    ensure_circular_output(model.output_size())?;
+    let mut results = Vec::with_capacity(freqs.len());
+    for chunk in freqs.chunks(chunk_size) {
+        results.extend(model.predict_batch_with_physics(chunk, &physics, &device)?);
+        if let Some(progress) = progress.as_mut() {
+            progress(results.len(), freqs.len());
+        }
+    }
+    Ok(results)
+}
+
+/// 学習済みモデルの出力層の値を、`softplus`の適用前と適用後の両方で返します。
+///
+/// 寸法が範囲の端に張り付く (飽和する) 原因を調べるためのデバッグ用の関数で、円形断面以外の
+/// モデルやQ値を出力するモデルも含め、すべての出力の列を返します。
+///
+/// # Errors
+///
+/// モデルが読み込めない、または周波数が不正な場合に`InferError`を返します。
+pub fn run_raw<B: Backend>(
+    artifact_dir: &str,
+    freq: f32,
+    device: B::Device,
+) -> Result<RawOutputs, InferError> {
+    validate_frequencies(&[freq])?;
+    let model = load_model::<B>(artifact_dir, &device)?;
+    // 材質や温度を入力とするモデルには、学習時に保存された設定の値を与える
+    let physics = physics_config_for(artifact_dir)?;
 
-    // 入力テンソルを作成
     let input = Tensor::<B, 2>::from_floats([[freq]], &device);
+    let input = model.with_conditions(input, &physics);
+    let to_vec = |tensor: Tensor<B, 2>| -> Vec<f32> {
+        tensor.into_data().convert::<f32>().into_vec().unwrap()
+    };
+    let raw = to_vec(model.forward_raw(input.clone()));
+    let activated = to_vec(model.forward(input));
 
-    // 推論を実行
-    let dims = model.forward(input);
-    let dims_values: Vec<f32> = dims.into_data().convert::<f32>().into_vec().unwrap();
-
-    // 結果を表示
-    println!("\n--- Predicted Dimensions (in meters) ---");
-    println!("  - Handle Length:     {:.6}", dims_values[0]);
-    println!("  - Handle Diameter:   {:.6}", dims_values[1]);
-    println!("  - Prong Length:      {:.6}", dims_values[2]);
-    println!("  - Prong Diameter:    {:.6}", dims_values[3]);
-    println!("  - Prong Gap:         {:.6}", dims_values[4]);
-    println!("----------------------------------------");
+    let dimension_labels = [
+        "Handle Length",
+        "Handle Diameter",
+        "Prong Length",
+        "Prong Diameter",
+        "Prong Gap",
+        "Prong Height",
+    ];
+    let mut labels = dimension_labels[..model.output_size()].to_vec();
+    if model.output_size() == model_dims::NUM_DIMS_RECTANGULAR {
+        labels[model_dims::PRONG_WIDTH_IDX] = "Prong Width";
+    }
+    if model.has_q_factor() {
+        labels.push("log10(Q)");
+    }
+    Ok(RawOutputs {
+        frequency: freq,
+        labels,
+        raw,
+        activated,
+    })
+}
+
+/// 学習済みモデルを一度だけ読み込み、対話的に推論します (`repl`サブコマンド)。
+///
+/// 実現周波数の計算には学習時に保存された物理モデル ([`physics_config_for`]) を使用します。
+/// 入出力の扱いは[`repl`]を参照してください。
+///
+/// # Returns
+///
+/// 推論した周波数の数。
+///
+/// # Errors
+///
+/// モデルや学習設定の読み込みに失敗した場合、または入出力に失敗した場合に`InferError`を
+/// 返します。入力された周波数が不正な場合はエラーにせず、次の入力を待ちます。
+pub fn run_repl<B: Backend, R: BufRead, W: Write>(
+    artifact_dir: &str,
+    reader: R,
+    writer: W,
+    device: B::Device,
+) -> Result<usize, InferError> {
+    let physics = physics_config_for(artifact_dir)?;
+    physics.validate().map_err(InferError::InvalidInput)?;
+    let model = load_model::<B>(artifact_dir, &device)?;
+    ensure_circular_output(model.output_size())?;
+    repl(&model, &physics, reader, writer, &device)
+}
+
+/// `reader`から1行ずつ周波数 (Hz) を読み込み、予測寸法と実現周波数を`writer`に書き出します。
+///
+/// 入力を待つたびに[`REPL_PROMPT`]を表示し、`reader`がEOFに達するまで繰り返します。
+/// 空行は読み飛ばし、数値として解釈できない行や正の有限値でない周波数には
+/// エラーメッセージを書き出してから次の入力を待ちます。結果は周波数ごとに1行で、
+/// 寸法はメートル単位です。
+///
+/// # Returns
+///
+/// 推論した周波数の数。
+///
+/// # Errors
+///
+/// `reader`からの読み込み、または`writer`への書き出しに失敗した場合に`InferError::Io`を
+/// 返します。
+pub fn repl<B: Backend, R: BufRead, W: Write>(
+    model: &TuningForkPINN<B>,
+    physics: &PhysicsConfig,
+    reader: R,
+    mut writer: W,
+    device: &B::Device,
+) -> Result<usize, InferError> {
+    let io_err = |err: std::io::Error| InferError::Io(err.to_string());
+    let mut count = 0;
+    write!(writer, "{REPL_PROMPT}").map_err(io_err)?;
+    writer.flush().map_err(io_err)?;
+    for line in reader.lines() {
+        let line = line.map_err(io_err)?;
+        let input = line.trim();
+        if !input.is_empty() {
+            let freq = input
+                .parse::<f32>()
+                .map_err(|_| InferError::InvalidInput(format!("cannot parse frequency '{input}'")))
+                .and_then(|freq| validate_frequencies(&[freq]).map(|()| freq));
+            match freq {
+                Ok(freq) => {
+                    let dims = model
+                        .predict_batch_with_physics(&[freq], physics, device)?
+                        .remove(0);
+                    let realized_frequency = frequency_from_dims_with_config(&dims, physics);
+                    writeln!(
+                        writer,
+                        "🎵 {freq} Hz: handle {:.6} x {:.6} m, prong {:.6} x {:.6} m, \
+                         gap {:.6} m (realized {realized_frequency:.2} Hz)",
+                        dims.handle_length,
+                        dims.handle_diameter,
+                        dims.prong_length,
+                        dims.prong_diameter,
+                        dims.prong_gap
+                    )
+                    .map_err(io_err)?;
+                    count += 1;
+                }
+                Err(err) => writeln!(writer, "❌ {err}").map_err(io_err)?,
+            }
+        }
+        write!(writer, "{REPL_PROMPT}").map_err(io_err)?;
+        writer.flush().map_err(io_err)?;
+    }
+    // EOFで終了したときに、プロンプトの後で改行する
+    writeln!(writer).map_err(io_err)?;
+    Ok(count)
+}
+
+/// `start`から`end`まで (`end`を含む) `step`間隔の周波数のリストを生成します。
+///
+/// 浮動小数点の誤差が蓄積しないよう、各周波数は `start + i · step` として計算します。
+///
+/// # Errors
+///
+/// いずれかの値が正の有限値でない場合、`start >= end`の場合、または`step <= 0`の場合に
+/// `InferError::InvalidInput`を返します。
+pub fn sweep_frequencies(start: f32, end: f32, step: f32) -> Result<Vec<f32>, InferError> {
+    if !step.is_finite() || step <= 0.0 {
+        return Err(InferError::InvalidInput(format!(
+            "sweep step must be positive, got {step}"
+        )));
+    }
+    if start.is_nan() || end.is_nan() || start >= end {
+        return Err(InferError::InvalidInput(format!(
+            "sweep start must be less than end, got ({start}, {end})"
+        )));
+    }
+    validate_frequencies(&[start, end])?;
+
+    // 端点が丸め誤差で落ちないよう、わずかな余裕を持たせて個数を求める
+    let count = ((end - start) / step + 1e-4).floor() as usize + 1;
+    Ok((0..count).map(|i| start + i as f32 * step).collect())
+}
+
+/// 周波数ごとの予測寸法を表形式で標準出力に表示します。
+pub fn print_dimensions_table(freqs: &[f32], dims: &[ForkDimensions]) {
+    println!(
+        "{:>10} {:>14} {:>16} {:>13} {:>15} {:>10}",
+        "freq (Hz)",
+        "handle_length",
+        "handle_diameter",
+        "prong_length",
+        "prong_diameter",
+        "prong_gap"
+    );
+    for (freq, d) in freqs.iter().zip(dims) {
+        println!(
+            "{:>10.1} {:>14.6} {:>16.6} {:>13.6} {:>15.6} {:>10.6}",
+            freq, d.handle_length, d.handle_diameter, d.prong_length, d.prong_diameter, d.prong_gap
+        );
+    }
+}
+
+/// 学習済みモデルで目標周波数の一覧を推論し、周波数誤差の統計と物理的に妥当な予測の割合を
+/// 返します (`eval`サブコマンド)。
+///
+/// 推論には[`run_batch`]を使用し、誤差は保存された学習設定の物理モデル ([`physics_config_for`])
+/// で予測寸法から逆算した周波数と目標周波数の差です。材質や温度を入力とするモデルには、
+/// 同じ設定の材質・温度を与えて推論します。再学習は行いません。
+///
+/// # Errors
+///
+/// `freqs`が空の場合は`InferError::InvalidInput`を返します。周波数が不正な場合、または
+/// モデルや学習設定が読み込めない場合にも`InferError`を返します。
+pub fn run_eval<B: Backend>(
+    artifact_dir: &str,
+    freqs: &[f32],
+    chunk_size: usize,
+    device: B::Device,
+) -> Result<EvaluationSummary, InferError> {
+    if freqs.is_empty() {
+        return Err(InferError::InvalidInput("no frequencies to evaluate".to_string()));
+    }
+    let physics = physics_config_for(artifact_dir)?;
+    let dims = run_batch::<B>(artifact_dir, freqs, Some(&physics), chunk_size, device, None)?;
+    let errors: Vec<f32> = dims
+        .iter()
+        .zip(freqs)
+        .map(|(dims, freq)| frequency_from_dims_with_config(dims, &physics) - freq)
+        .collect();
+    let rows: Vec<_> = dims.iter().map(ForkDimensions::to_array).collect();
+    Ok(EvaluationSummary::new(&errors, &rows))
+}
+
+/// 2つのモデルの比較 (`compare`サブコマンド) で、どちらのモデルの誤差が小さいかを表します。
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Winner {
+    /// 1つ目のモデル (`--model-a`)。
+    A,
+    /// 2つ目のモデル (`--model-b`)。
+    B,
+    /// 誤差が等しい。
+    Tie,
+}
+
+impl Winner {
+    /// 2つの誤差 (Hz、絶対値) を比べ、小さい方のモデルを返します。
+    pub fn from_errors(error_a: f32, error_b: f32) -> Self {
+        match error_a.total_cmp(&error_b) {
+            Ordering::Less => Winner::A,
+            Ordering::Greater => Winner::B,
+            Ordering::Equal => Winner::Tie,
+        }
+    }
+}
+
+impl fmt::Display for Winner {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            Winner::A => "A",
+            Winner::B => "B",
+            Winner::Tie => "tie",
+        })
+    }
+}
+
+/// 2つのモデルの比較 ([`run_compare`]) で、1つの周波数に対する各モデルの周波数誤差。
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct ComparisonRow {
+    /// 目標周波数 (Hz)。
+    pub frequency: f32,
+    /// 1つ目のモデルの予測寸法から逆算した周波数の絶対誤差 (Hz)。
+    pub error_a: f32,
+    /// 2つ目のモデルの予測寸法から逆算した周波数の絶対誤差 (Hz)。
+    pub error_b: f32,
+}
+
+impl ComparisonRow {
+    /// この周波数で誤差が小さい方のモデルを返します。
+    pub fn winner(&self) -> Winner {
+        Winner::from_errors(self.error_a, self.error_b)
+    }
+}
+
+/// 比較結果全体で、周波数誤差の平均が小さい方のモデルを返します。
+///
+/// 周波数ごとの勝ち数ではなく平均誤差で決めるため、一部の周波数で大きく外すモデルは
+/// 勝ち数が多くても負けになります。
+pub fn overall_winner(rows: &[ComparisonRow]) -> Winner {
+    let (mean_a, mean_b) = mean_errors(rows);
+    Winner::from_errors(mean_a, mean_b)
+}
+
+/// 比較結果の各モデルの周波数誤差の平均 (Hz) を返します。行がない場合はどちらも0です。
+fn mean_errors(rows: &[ComparisonRow]) -> (f32, f32) {
+    let count = rows.len().max(1) as f32;
+    let sum_a: f32 = rows.iter().map(|row| row.error_a).sum();
+    let sum_b: f32 = rows.iter().map(|row| row.error_b).sum();
+    (sum_a / count, sum_b / count)
+}
+
+/// 2つの学習済みモデルで同じ周波数のリストを推論し、周波数ごとの周波数誤差を比較します
+/// (`compare`サブコマンド)。
+///
+/// 各モデルの推論には[`run_batch`]を使用し、誤差は各モデルのディレクトリに保存された学習設定の
+/// 物理モデル ([`physics_config_for`]) で予測寸法から逆算した周波数と目標周波数の差です。
+/// 推論にも同じ設定の材質・温度を使用します。
+///
+/// # Errors
+///
+/// 周波数が不正な場合、またはいずれかのモデルや学習設定が読み込めない場合に`InferError`を
+/// 返します。
+pub fn run_compare<B: Backend>(
+    artifact_dir_a: &str,
+    artifact_dir_b: &str,
+    freqs: &[f32],
+    chunk_size: usize,
+    device: B::Device,
+) -> Result<Vec<ComparisonRow>, InferError> {
+    let errors = |artifact_dir: &str| -> Result<Vec<f32>, InferError> {
+        let physics = physics_config_for(artifact_dir)?;
+        let dims =
+            run_batch::<B>(artifact_dir, freqs, Some(&physics), chunk_size, device.clone(), None)?;
+        Ok(dims
+            .iter()
+            .zip(freqs)
+            .map(|(dims, freq)| (frequency_from_dims_with_config(dims, &physics) - freq).abs())
+            .collect())
+    };
+    let (errors_a, errors_b) = (errors(artifact_dir_a)?, errors(artifact_dir_b)?);
+    Ok(freqs
+        .iter()
+        .zip(errors_a.into_iter().zip(errors_b))
+        .map(|(&frequency, (error_a, error_b))| ComparisonRow {
+            frequency,
+            error_a,
+            error_b,
+        })
+        .collect())
+}
+
+/// 2つのモデルの比較結果を表形式で標準出力に表示し、最後に勝ち数と平均誤差をまとめます。
+pub fn print_comparison(rows: &[ComparisonRow]) {
+    println!(
+        "{:>10} {:>14} {:>14} {:>7}",
+        "freq (Hz)", "error A (Hz)", "error B (Hz)", "winner"
+    );
+    for row in rows {
+        println!(
+            "{:>10.1} {:>14.3} {:>14.3} {:>7}",
+            row.frequency,
+            row.error_a,
+            row.error_b,
+            row.winner()
+        );
+    }
+    let wins = |winner: Winner| rows.iter().filter(|row| row.winner() == winner).count();
+    let (mean_a, mean_b) = mean_errors(rows);
+    println!(
+        "\nWins: A {}, B {}, tie {} / mean error: A {mean_a:.3} Hz, B {mean_b:.3} Hz",
+        wins(Winner::A),
+        wins(Winner::B),
+        wins(Winner::Tie)
+    );
+    println!("🏆 Overall winner: {}", overall_winner(rows));
+}
+
+/// カンマ・空白・改行区切りの文字列から周波数のリストを解析します。
+///
+/// # Errors
+///
+/// 数値として解釈できない要素が含まれる場合に`InferError::InvalidInput`を返します。
+pub fn parse_frequencies(text: &str) -> Result<Vec<f32>, InferError> {
+    text.split(|c: char| c == ',' || c.is_whitespace())
+        .filter(|token| !token.is_empty())
+        .map(|token| {
+            token
+                .parse::<f32>()
+                .map_err(|_| InferError::InvalidInput(format!("cannot parse frequency '{token}'")))
+        })
+        .collect()
+}
+
+/// CSVから周波数のリストを読み込みます。
+///
+/// 各行の先頭列を周波数 (Hz) として解釈します。先頭行が数値として解釈できない場合は
+/// ヘッダー行とみなして読み飛ばします。
+///
+/// # Errors
+///
+/// CSVの読み込みに失敗した場合、またはヘッダー以外の行が数値でない場合に
+/// `InferError::CsvParse`を返します。
+pub fn read_frequencies_csv<R: Read>(reader: R) -> Result<Vec<f32>, InferError> {
+    let mut csv_reader = csv::ReaderBuilder::new()
+        .has_headers(false)
+        .trim(csv::Trim::All)
+        .flexible(true)
+        .from_reader(reader);
+
+    let mut freqs = Vec::new();
+    for (row, record) in csv_reader.records().enumerate() {
+        let record = record.map_err(|err| InferError::CsvParse(err.to_string()))?;
+        let Some(field) = record.get(0).filter(|field| !field.is_empty()) else {
+            continue;
+        };
+        match field.parse::<f32>() {
+            Ok(freq) => freqs.push(freq),
+            // 先頭行に限り、数値でなければヘッダーとして扱う
+            Err(_) if row == 0 => continue,
+            Err(_) => {
+                return Err(InferError::CsvParse(format!(
+                    "row {}: cannot parse frequency '{field}'",
+                    row + 1
+                )));
+            }
+        }
+    }
+    Ok(freqs)
+}
+
+/// 周波数と予測寸法をCSVとして書き出します。
+///
+/// 列は `frequency, handle_length, handle_diameter, prong_length, prong_diameter, prong_gap` です。
+pub fn write_dimensions_csv<W: Write>(
+    writer: W,
+    freqs: &[f32],
+    dims: &[ForkDimensions],
+) -> Result<(), InferError> {
+    let to_csv_err = |err: csv::Error| InferError::CsvParse(err.to_string());
+    let mut csv_writer = csv::Writer::from_writer(writer);
+
+    csv_writer
+        .write_record([
+            "frequency",
+            "handle_length",
+            "handle_diameter",
+            "prong_length",
+            "prong_diameter",
+            "prong_gap",
+        ])
+        .map_err(to_csv_err)?;
+    for (freq, d) in freqs.iter().zip(dims) {
+        csv_writer
+            .write_record(
+                [
+                    *freq,
+                    d.handle_length,
+                    d.handle_diameter,
+                    d.prong_length,
+                    d.prong_diameter,
+                    d.prong_gap,
+                ]
+                .map(|value| value.to_string()),
+            )
+            .map_err(to_csv_err)?;
+    }
+    csv_writer
+        .flush()
+        .map_err(|err| InferError::CsvParse(err.to_string()))
+}
+
+/// すべての周波数が正の有限値であることを検証します。
+pub(crate) fn validate_frequencies(freqs: &[f32]) -> Result<(), InferError> {
+    match freqs.iter().find(|f| !f.is_finite() || **f <= 0.0) {
+        Some(freq) => Err(InferError::InvalidInput(format!(
+            "frequency must be a positive finite number, got {freq}"
+        ))),
+        None => Ok(()),
+    }
+}
+
+/// モデルの出力が円形断面の寸法数 (`model_dims::NUM_DIMS`) であることを検証します。
+pub(crate) fn ensure_circular_output(num_dims: usize) -> Result<(), InferError> {
+    if num_dims != model_dims::NUM_DIMS {
+        return Err(InferError::RecordLoad(format!(
+            "model outputs {num_dims} dimensions, but inference supports only circular prongs ({})",
+            model_dims::NUM_DIMS
+        )));
+    }
+    Ok(())
+}
+
+/// 成果物ディレクトリから学習済みモデルを読み込みます。
+///
+/// 検証損失が最小だった`model_best`が存在すればそれを優先し、なければ最終エポックの
+/// `model`を使用します。保存形式は学習時に保存された設定の`record_format`に従い、設定が
+/// なければ存在するファイルの拡張子から判定します ([`RecordFormat::ALL`]の順に探索)。
+/// 別の形式で学習し直したディレクトリに残った古い形式のファイルは読み込みません。モデルの構成 (ドロップアウトなど) は学習時に保存された
+/// 設定から復元し、設定がなければデフォルトの構成を使用します。
+pub(crate) fn load_model<B: Backend>(
+    artifact_dir: &str,
+    device: &B::Device,
+) -> Result<TuningForkPINN<B>, InferError> {
+    load_model_with::<B>(artifact_dir, device, |config| config)
+}
+
+/// [`load_model`]と同じですが、学習時の構成を`configure`で変更してからモデルを初期化します。
+fn load_model_with<B: Backend>(
+    artifact_dir: &str,
+    device: &B::Device,
+    configure: impl FnOnce(ModelConfig) -> ModelConfig,
+) -> Result<TuningForkPINN<B>, InferError> {
+    let training_config = load_training_config(artifact_dir)?;
+    let formats = match &training_config {
+        Some(config) => vec![config.record_format],
+        None => RecordFormat::ALL.to_vec(),
+    };
+    let (model_path, format) = ["model_best", "model"]
+        .into_iter()
+        .flat_map(|stem| {
+            let path = format!("{artifact_dir}/{stem}");
+            formats.iter().map(move |&format| (path.clone(), format))
+        })
+        .find(|(path, format)| Path::new(&format!("{path}.{}", format.extension())).exists())
+        .ok_or_else(|| {
+            InferError::ModelNotFound(format!("{artifact_dir}/model.{}", formats[0].extension()))
+        })?;
+
+    // 保存されたモデルのレコードを読み込む
+    let record = record::load_record::<B, _>(format, model_path.into(), device)
+        .map_err(|err| InferError::RecordLoad(err.to_string()))?;
+
+    // 学習時の構成でモデルを初期化し、レコードから重みを復元
+    let model_config = training_config
+        .map(|config| config.model_config())
+        .unwrap_or_else(ModelConfig::new);
+    Ok(configure(model_config).init(device).load_record(record))
+}
+
+/// メモリ上のバイト列から学習済みモデルを読み込みます。
+///
+/// バイト列は[`save_model_to_bytes`]の出力、または`--record-format bincode`で保存した
+/// モデルファイル (`model.bin`) の内容です ([`record::load_record_from_bytes`]を参照)。
+/// モデルの構成は`config_json` (学習時に保存された[`TRAINING_CONFIG_FILE`]の内容) から
+/// 復元し、`None`の場合はデフォルトの構成を使用します ([`load_model`]と同じ)。
+///
+/// # Errors
+///
+/// 学習設定を解釈できない場合に`InferError::ConfigLoad`、バイト列をレコードとして
+/// 解釈できない場合に`InferError::RecordLoad`を返します。
+pub fn load_model_from_bytes<B: Backend>(
+    bytes: &[u8],
+    config_json: Option<&str>,
+    device: &B::Device,
+) -> Result<TuningForkPINN<B>, InferError> {
+    let model_config = match config_json {
+        Some(json) => training_config_from_json(json)?.model_config(),
+        None => ModelConfig::new(),
+    };
+    let record = record::load_record_from_bytes::<B, _>(bytes, device)
+        .map_err(|err| InferError::RecordLoad(err.to_string()))?;
+    Ok(model_config.init(device).load_record(record))
+}
+
+/// 学習設定 ([`TRAINING_CONFIG_FILE`]) の内容を解釈します。
+///
+/// # Errors
+///
+/// 学習設定として解釈できない場合に`InferError::ConfigLoad`を返します。
+pub fn training_config_from_json(json: &str) -> Result<TrainingConfig, InferError> {
+    TrainingConfig::load_binary(json.as_bytes())
+        .map_err(|err| InferError::ConfigLoad(format!("{TRAINING_CONFIG_FILE}: {err}")))
+}
+
+/// モデルの重みをメモリ上のバイト列として保存します ([`load_model_from_bytes`]で読み込めます)。
+///
+/// バイト列は`--record-format bincode`で保存したモデルファイル (`model.bin`) と同じ形式です。
+///
+/// # Errors
+///
+/// レコードのシリアライズに失敗した場合に`InferError::RecordSave`を返します。
+pub fn save_model_to_bytes<B: Backend>(
+    model: &TuningForkPINN<B>,
+) -> Result<Vec<u8>, InferError> {
+    record::save_record_to_bytes::<B, _>(model.clone().into_record())
+        .map_err(|err| InferError::RecordSave(err.to_string()))
 }
@@ -5,8 +5,27 @@
 //! (以下、ドキュメントコメントは省略)
 
 // 各モジュールをライブラリの公開APIとして定義
+#[cfg(feature = "native")]
+pub mod backend;
+#[cfg(feature = "capi")]
+pub mod capi;
+pub mod cli;
 pub mod constants;
+#[cfg(feature = "native")]
+pub mod device;
+pub mod export;
+pub mod geometry;
 pub mod infer;
+pub mod logging;
+pub mod metadata;
+pub mod metrics;
 pub mod model;
 pub mod physics;
+#[cfg(feature = "python")]
+pub mod python;
+pub mod record;
+#[cfg(feature = "server")]
+pub mod server;
 pub mod train;
+#[cfg(feature = "wasm")]
+pub mod wasm;
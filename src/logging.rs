@@ -0,0 +1,58 @@
+//! # ログ出力
+//!
+//! コマンドラインの`-v`/`--verbose`・`-q`/`--quiet`フラグと、ログ出力の初期化を定義します。
+//! ログは標準エラー出力に書き出されるため、推論結果などの標準出力とは混ざりません。
+
+use clap::Args;
+use log::LevelFilter;
+
+/// このクレートのログに適用するモジュール名。
+const CRATE_MODULE: &str = "burn_tuningfork_pinn";
+
+/// ログの詳細度を指定するコマンドライン引数。
+///
+/// `#[command(flatten)]`で任意のclapコマンドに組み込めます。
+#[derive(Args, Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct Verbosity {
+    /// ログの詳細度を上げます (`-v`: info、`-vv`: debug、`-vvv`: trace)。
+    ///
+    /// 省略時は警告とエラーのみを表示します。`-vv`ではエポックごとの損失の内訳も表示されます。
+    #[arg(short, long, global = true, action = clap::ArgAction::Count)]
+    pub verbose: u8,
+
+    /// エラー以外のログを抑制します。`--verbose`より優先されます。
+    #[arg(short, long, global = true)]
+    pub quiet: bool,
+}
+
+impl Verbosity {
+    /// フラグに対応するログレベル。
+    ///
+    /// `--verbose`の回数に応じて warn → info → debug → trace の順に詳細になります。
+    pub fn level_filter(&self) -> LevelFilter {
+        if self.quiet {
+            return LevelFilter::Error;
+        }
+        match self.verbose {
+            0 => LevelFilter::Warn,
+            1 => LevelFilter::Info,
+            2 => LevelFilter::Debug,
+            _ => LevelFilter::Trace,
+        }
+    }
+
+    /// ログ出力を初期化します。
+    ///
+    /// フラグはこのクレートのログレベルを決めます。依存クレートのログは警告以上のみ表示されます。
+    /// フラグを指定しない場合は、環境変数`RUST_LOG`でレベルを細かく指定できます。
+    #[cfg(feature = "native")]
+    pub fn init_logger(&self) {
+        let default_filter = format!("warn,{CRATE_MODULE}={}", self.level_filter());
+        let env = env_logger::Env::default().default_filter_or(default_filter);
+        let mut builder = env_logger::Builder::from_env(env);
+        if self.quiet || self.verbose > 0 {
+            builder.filter_module(CRATE_MODULE, self.level_filter());
+        }
+        builder.format_timestamp(None).format_target(false).init();
+    }
+}
@@ -1,15 +1,28 @@
 //! # メインエントリーポイント
 //!
 //! コマンドライン引数を解析し、選択されたバックエンド（`wgpu`、`ndarray`、`cuda`）で
-//! 学習または推論プロセスを開始します。
+//! 学習または推論プロセスを開始します。`tch`・`candle`フィーチャーを有効にすると、
+//! `tch` (LibTorch)・`candle` (Candle) も選択できます。バックエンドの選択は
+//! [`backend::dispatch`]が行います。
 
 #![recursion_limit = "256"]
 
-use burn::backend::{Autodiff, NdArray, wgpu::Wgpu, Cuda};
-use burn_tuningfork_pinn::{infer, train};
+use burn::tensor::backend::{AutodiffBackend, Backend};
+use burn_tuningfork_pinn::{
+    backend::{self, BackendAction, BackendOptions, Dtype},
+    cli::TrainArgs,
+    constants::physics::Material,
+    device, export, geometry, infer, logging, train,
+};
+#[cfg(feature = "server")]
+use burn_tuningfork_pinn::server;
 use clap::{Parser, Subcommand};
-
-// デフォルトのバックエンド定義は不要になります
+#[cfg(feature = "progress")]
+use indicatif::{ProgressBar, ProgressStyle};
+use log::{error, info, warn};
+#[cfg(feature = "progress")]
+use std::io::IsTerminal;
+use std::{fmt::Display, fs::File, path::{Path, PathBuf}};
 
 /// コマンドラインインターフェースの定義
 #[derive(Parser, Debug)]
@@ -20,41 +33,641 @@ struct Cli {
 
     /// 使用するバックエンドを指定します。
     ///
-    /// `ndarray`, `wgpu`, `cuda`を選択できます。
+    /// `ndarray`, `wgpu`, `cuda`を選択できます。`tch`・`candle`フィーチャーを有効にして
+    /// ビルドした場合は、それぞれ`tch` (LibTorch、CPU)・`candle` (Candle、CPU) も選択できます。
     #[arg(long, default_value = "cuda")]
     backend: String,
+
+    /// 学習済みモデルを保存・読み込みするディレクトリ。
+    ///
+    /// 学習と推論で同じディレクトリを指定してください。
+    #[arg(long, global = true, default_value = infer::DEFAULT_ARTIFACT_DIR)]
+    artifact_dir: String,
+
+    /// 使用するGPUのインデックス (`wgpu`のみ)。
+    ///
+    /// 省略時はデフォルトのデバイスを使用します。その他のバックエンドでは無視されます。
+    #[arg(long, global = true)]
+    device_index: Option<usize>,
+
+    #[command(flatten)]
+    verbosity: logging::Verbosity,
+
+    /// 計算に使用する浮動小数点の精度 (`f32`、`f64`)。
+    ///
+    /// `f64`は`ndarray`バックエンドでのみ使用できます。細いプロングでは直径の4乗を含む
+    /// 断面二次モーメントの計算で単精度の誤差が大きくなるため、精度が必要な場合に使用します。
+    #[arg(long, global = true, default_value = "f32")]
+    dtype: Dtype,
 }
 
-/// サブコマンド (`train` または `infer`)
+/// サブコマンド (`list-backends`と、バックエンドを使用する[`BackendCommand`]のサブコマンド)
 #[derive(Subcommand, Debug)]
 enum Commands {
+    /// このビルドで使用できるバックエンド (`--backend`に指定できる値) を表示します。
+    ListBackends,
+    #[command(flatten)]
+    Backend(BackendCommand),
+}
+
+/// バックエンドを選択して実行するサブコマンド (`train`、`infer`、`forward`、`infer-batch`、
+/// `infer-csv`、`sweep`、`compare`、`eval`、`export-onnx`、`export-safetensors`など)
+#[derive(Subcommand, Debug)]
+enum BackendCommand {
     /// モデルを学習させます。
-    Train,
+    Train(TrainArgs),
     /// 学習済みモデルを使って推論します。
     Infer {
         /// 推論したい音叉の周波数 (Hz)
         #[arg(short, long)]
         freq: f32,
+        /// 実現周波数の誤差がこの値 (Hz) を超えた場合に警告を表示します。
+        #[arg(long, default_value_t = infer::DEFAULT_FREQ_ERROR_WARN_HZ)]
+        warn_threshold: f32,
+        /// 実現周波数の計算に使用する材質 (`steel304`、`aluminum`、`brass`、`titanium`)。
+        /// 省略時は学習時に保存された設定を使用します
+        #[arg(long)]
+        material: Option<Material>,
+        /// 実現周波数の計算に使用する曲げ振動モードの次数 (1〜3)。
+        /// 省略時は学習時に保存された設定を使用します
+        #[arg(long)]
+        mode: Option<u8>,
+        /// 音叉の温度 (℃)。温度を入力とするモデルの入力と、実現周波数の計算に使用します。
+        /// 省略時は学習時に保存された設定を使用します
+        #[arg(long)]
+        temperature: Option<f32>,
+        /// 実現周波数の計算にTimoshenko補正 (回転慣性・せん断変形) を適用します
+        #[arg(long)]
+        timoshenko: bool,
+        /// 寸法を表示する単位 (`m`、`mm`)
+        #[arg(long, default_value = "m")]
+        unit: infer::Unit,
+        /// 指定した回数だけモンテカルロ・ドロップアウトで推論し、寸法の平均と標準偏差を
+        /// 表示します (ドロップアウトを有効にして学習したモデルが必要です)
+        #[arg(long)]
+        uncertainty_samples: Option<usize>,
+        /// 出力形式 (`text`、`json`)。`json`では寸法を常にメートルで出力します
+        #[arg(long, default_value = "text")]
+        format: infer::OutputFormat,
+        /// 学習時に保存されたモデルのメタデータ (バージョン・学習日時・材質など) を表示します
+        #[arg(long)]
+        show_metadata: bool,
+        /// 各寸法の周波数に対する感度 (∂寸法/∂周波数) を自動微分で計算して表示します
+        /// (`json`形式では`sensitivity`フィールドにm/Hzで出力します)
+        #[arg(long)]
+        sensitivity: bool,
+        /// 予測した寸法を製造可能な範囲に収めてから表示し、変更した寸法を報告します
+        #[arg(long)]
+        clamp: bool,
+    },
+    /// 学習済みモデルを一度だけ読み込み、標準入力から1行ずつ読んだ周波数の寸法を表示します。
+    ///
+    /// 入力の終わり (Ctrl-D) で終了します。
+    Repl,
+    /// ネットワークの出力を列ごとに表示します (デバッグ用)。
+    Debug {
+        /// 入力する周波数 (Hz)
+        #[arg(short, long)]
+        freq: f32,
+        /// 出力層の`softplus`を適用する前の値も表示します
+        #[arg(long)]
+        raw: bool,
+    },
+    /// 指定された寸法から、物理モデルだけで音叉の周波数を計算します (寸法の単位はメートル)。
+    Forward {
+        /// 柄の長さ (m)
+        #[arg(long, required_unless_present = "dims_json")]
+        handle_length: Option<f32>,
+        /// 柄の直径 (m)
+        #[arg(long, required_unless_present = "dims_json")]
+        handle_diameter: Option<f32>,
+        /// プロングの長さ (m)
+        #[arg(long, required_unless_present = "dims_json")]
+        prong_length: Option<f32>,
+        /// プロングの直径 (m)
+        #[arg(long, required_unless_present = "dims_json")]
+        prong_diameter: Option<f32>,
+        /// プロングの間隔 (m)
+        #[arg(long, required_unless_present = "dims_json")]
+        prong_gap: Option<f32>,
+        /// 寸法を個別に指定する代わりに、寸法のJSONファイル (`ForkDimensions::to_json`の形式) から
+        /// 読み込みます
+        #[arg(
+            long,
+            conflicts_with_all = [
+                "handle_length", "handle_diameter", "prong_length", "prong_diameter", "prong_gap"
+            ]
+        )]
+        dims_json: Option<PathBuf>,
+        /// 音叉の材質 (`steel304`、`aluminum`、`brass`、`titanium`)
+        #[arg(long, default_value = "steel304")]
+        material: Material,
+        /// 曲げ振動モードの次数 (1〜3)
+        #[arg(long, default_value_t = 1)]
+        mode: u8,
+    },
+    /// 複数の周波数に対してまとめて推論します。
+    InferBatch {
+        /// カンマ区切りの周波数リスト (例: `440,880`)、または周波数を列挙したファイルのパス
+        input: String,
+        /// 推論の進捗バーを表示します (標準エラー出力が端末でない場合は表示しません)
+        #[arg(long)]
+        progress: bool,
+        /// 一度のフォワードパスに渡す周波数の数。メモリ使用量の上限を決めます
+        #[arg(long, default_value_t = infer::DEFAULT_CHUNK_SIZE)]
+        chunk_size: usize,
+    },
+    /// CSVファイルの周波数に対して推論し、結果をCSVに書き出します。
+    InferCsv {
+        /// 周波数を1行に1つ記載した入力CSV (ヘッダー行は任意)
+        #[arg(long)]
+        input: PathBuf,
+        /// 推論結果を書き出すCSV
+        #[arg(long)]
+        output: PathBuf,
+        /// 推論の進捗バーを表示します (標準エラー出力が端末でない場合は表示しません)
+        #[arg(long)]
+        progress: bool,
+        /// 一度のフォワードパスに渡す周波数の数。メモリ使用量の上限を決めます
+        #[arg(long, default_value_t = infer::DEFAULT_CHUNK_SIZE)]
+        chunk_size: usize,
+    },
+    /// 周波数範囲を一定間隔で掃引し、周波数ごとの予測寸法を表にします。
+    Sweep {
+        /// 掃引を開始する周波数 (Hz)
+        #[arg(long, default_value_t = 200.0)]
+        start: f32,
+        /// 掃引を終了する周波数 (Hz)。この値も含みます
+        #[arg(long, default_value_t = 2000.0)]
+        end: f32,
+        /// 周波数の間隔 (Hz)
+        #[arg(long, default_value_t = 20.0)]
+        step: f32,
+        /// 表を表示する代わりに結果を書き出すCSV
+        #[arg(long)]
+        output: Option<PathBuf>,
+        /// 推論の進捗バーを表示します (標準エラー出力が端末でない場合は表示しません)
+        #[arg(long)]
+        progress: bool,
+        /// 一度のフォワードパスに渡す周波数の数。メモリ使用量の上限を決めます
+        #[arg(long, default_value_t = infer::DEFAULT_CHUNK_SIZE)]
+        chunk_size: usize,
+    },
+    /// 目標周波数のCSVに対する学習済みモデルの周波数誤差の統計を表示します (再学習しません)。
+    Eval {
+        /// 評価するモデルの成果物ディレクトリ。省略した場合は`--artifact-dir`を使用します
+        #[arg(long)]
+        model: Option<String>,
+        /// 目標周波数を1行に1つ記載した入力CSV (ヘッダー行は任意)
+        #[arg(long)]
+        input: PathBuf,
+        /// 一度のフォワードパスに渡す周波数の数。メモリ使用量の上限を決めます
+        #[arg(long, default_value_t = infer::DEFAULT_CHUNK_SIZE)]
+        chunk_size: usize,
+    },
+    /// 2つの学習済みモデルを同じ周波数のグリッドで推論し、周波数誤差を比較します。
+    Compare {
+        /// 1つ目のモデルの成果物ディレクトリ
+        #[arg(long)]
+        model_a: String,
+        /// 2つ目のモデルの成果物ディレクトリ
+        #[arg(long)]
+        model_b: String,
+        /// グリッドを開始する周波数 (Hz)
+        #[arg(long, default_value_t = 200.0)]
+        start: f32,
+        /// グリッドを終了する周波数 (Hz)。この値も含みます
+        #[arg(long, default_value_t = 2000.0)]
+        end: f32,
+        /// 周波数の間隔 (Hz)
+        #[arg(long, default_value_t = 50.0)]
+        step: f32,
+        /// 一度のフォワードパスに渡す周波数の数。メモリ使用量の上限を決めます
+        #[arg(long, default_value_t = infer::DEFAULT_CHUNK_SIZE)]
+        chunk_size: usize,
+    },
+    /// 学習済みモデルをONNX形式で書き出します。
+    ExportOnnx {
+        /// 書き出すONNXファイルのパス
+        #[arg(long, default_value = "model.onnx")]
+        output: PathBuf,
+    },
+    /// 学習済みモデルの重みをSafeTensors形式で書き出します。
+    ExportSafetensors {
+        /// 書き出すSafeTensorsファイルのパス
+        #[arg(long, default_value = "weights.safetensors")]
+        output: PathBuf,
+    },
+    /// 推論した音叉の形状をOpenSCADのスクリプトとして書き出します。
+    ExportScad {
+        /// 形状を生成する音叉の周波数 (Hz)
+        #[arg(short, long)]
+        freq: f32,
+        /// 書き出すOpenSCADファイルのパス
+        #[arg(long, default_value = "fork.scad")]
+        output: PathBuf,
+    },
+    /// 推論した音叉の形状をASCII形式のSTLとして書き出します。
+    ExportStl {
+        /// 形状を生成する音叉の周波数 (Hz)
+        #[arg(short, long)]
+        freq: f32,
+        /// 円柱の円周方向の分割数 (3以上)
+        #[arg(long, default_value_t = geometry::DEFAULT_SEGMENTS)]
+        segments: usize,
+        /// 書き出すSTLファイルのパス
+        #[arg(long, default_value = "fork.stl")]
+        output: PathBuf,
+    },
+    /// 学習済みモデルを一度だけ読み込み、推論用のHTTPサーバーを起動します。
+    ///
+    /// `GET /predict?freq=440`で寸法をJSONとして返します。`server`フィーチャーが必要です。
+    #[cfg(feature = "server")]
+    Serve {
+        /// 待ち受けるアドレス
+        #[arg(long, default_value = "127.0.0.1")]
+        host: std::net::IpAddr,
+        /// 待ち受けるポート番号
+        #[arg(long, default_value_t = 8080)]
+        port: u16,
     },
 }
 
-/// 指定されたバックエンドでアクション（学習または推論）を実行するためのマクロ
-macro_rules! run_action {
-    ($backend:ty, $device:expr, $command:expr) => {
-        match $command {
-            Commands::Train => {
-                println!("🚀 Starting training on {:?}...", $device);
-                train::run::<Autodiff<$backend>>($device);
+/// 選択されたバックエンドでサブコマンドを実行するアクション。
+///
+/// 学習と自動微分を使う推論には`B`を、それ以外の推論には`B::InnerBackend`を使用します。
+struct CommandAction<'a> {
+    command: BackendCommand,
+    artifact_dir: &'a str,
+}
+
+impl BackendAction for CommandAction<'_> {
+    type Output = ();
+
+    fn run<B: AutodiffBackend>(self, device: B::Device) {
+        let artifact_dir = self.artifact_dir;
+        match self.command {
+            BackendCommand::Train(args) => {
+                let config = args
+                    .to_config()
+                    .unwrap_or_else(exit_with_error)
+                    .with_artifact_dir(artifact_dir.to_string());
+                let action = train::TrainAction {
+                    config,
+                    resume_path: args.resume,
+                    dry_run: args.dry_run,
+                };
+                action.run::<B>(device).unwrap_or_else(exit_with_error);
             }
-            Commands::Infer { freq } => {
-                println!(
+            BackendCommand::Infer {
+                freq,
+                warn_threshold,
+                material,
+                mode,
+                temperature,
+                timoshenko,
+                unit,
+                uncertainty_samples,
+                format,
+                show_metadata,
+                sensitivity,
+                clamp,
+            } => {
+                info!(
                     "🔍 Inferring for frequency: {} Hz on {:?}...",
-                    freq, $device
+                    freq, device
                 );
-                infer::run::<$backend>(freq, $device);
+                // 学習時の設定を基に、指定されたオプションだけを上書きする
+                let mut physics =
+                    infer::physics_config_for(artifact_dir).unwrap_or_else(exit_with_error);
+                if let Some(material) = material {
+                    physics.material = material;
+                }
+                if let Some(mode) = mode {
+                    physics.mode = mode;
+                }
+                if let Some(temperature) = temperature {
+                    physics.temperature = temperature;
+                }
+                if timoshenko {
+                    physics.timoshenko = true;
+                }
+                let report = infer::run::<B::InnerBackend>(
+                    artifact_dir,
+                    freq,
+                    Some(&physics),
+                    device.clone(),
+                )
+                .unwrap_or_else(exit_with_error);
+                let report = if clamp {
+                    report.with_clamped_dimensions(&physics)
+                } else {
+                    report
+                };
+                let gradients = sensitivity.then(|| {
+                    let device = device.clone();
+                    infer::run_sensitivity::<B>(artifact_dir, freq, Some(&physics), device)
+                        .unwrap_or_else(exit_with_error)
+                });
+                let uncertainty = uncertainty_samples.map(|samples| {
+                    let physics = Some(&physics);
+                    infer::run_with_uncertainty::<B>(artifact_dir, freq, samples, physics, device)
+                        .unwrap_or_else(exit_with_error)
+                });
+                let metadata = show_metadata
+                    .then(|| infer::load_metadata(artifact_dir).unwrap_or_else(exit_with_error))
+                    .flatten();
+                if show_metadata && metadata.is_none() {
+                    warn!("⚠️  No model metadata found in '{artifact_dir}'");
+                }
+                match format {
+                    infer::OutputFormat::Text => {
+                        if let Some(metadata) = &metadata {
+                            println!("\n{metadata}");
+                        }
+                        infer::print_report(&report, warn_threshold, unit);
+                        if let Some(uncertainty) = &uncertainty {
+                            infer::print_uncertainty(uncertainty, unit);
+                        }
+                        if let Some(gradients) = &gradients {
+                            infer::print_sensitivity(gradients, unit);
+                        }
+                    }
+                    infer::OutputFormat::Json => {
+                        let json = infer::to_json(
+                            &report,
+                            uncertainty.as_ref(),
+                            gradients.as_ref(),
+                            metadata.as_ref(),
+                        );
+                        println!("{json}");
+                    }
+                }
+            }
+            BackendCommand::Repl => {
+                info!("🔁 Enter frequencies in Hz, one per line (Ctrl-D to exit)...");
+                let stdin = std::io::stdin();
+                infer::run_repl::<B::InnerBackend, _, _>(
+                    artifact_dir,
+                    stdin.lock(),
+                    std::io::stdout(),
+                    device,
+                )
+                .unwrap_or_else(exit_with_error);
+            }
+            BackendCommand::Debug { freq, raw } => {
+                let outputs = infer::run_raw::<B::InnerBackend>(artifact_dir, freq, device)
+                    .unwrap_or_else(exit_with_error);
+                infer::print_raw_outputs(&outputs, raw);
+            }
+            BackendCommand::Forward {
+                handle_length,
+                handle_diameter,
+                prong_length,
+                prong_diameter,
+                prong_gap,
+                dims_json,
+                material,
+                mode,
+            } => {
+                let dims = match dims_json {
+                    Some(path) => std::fs::read_to_string(&path)
+                        .map_err(|err| infer::InferError::Io(format!("{}: {err}", path.display())))
+                        .and_then(|json| infer::ForkDimensions::from_json(&json))
+                        .unwrap_or_else(exit_with_error),
+                    // dims_jsonがない場合、clapが各寸法の指定を必須にしている
+                    None => infer::ForkDimensions {
+                        handle_length: handle_length.unwrap(),
+                        handle_diameter: handle_diameter.unwrap(),
+                        prong_length: prong_length.unwrap(),
+                        prong_diameter: prong_diameter.unwrap(),
+                        prong_gap: prong_gap.unwrap(),
+                    },
+                };
+                let frequency =
+                    infer::forward(&dims, material, mode).unwrap_or_else(exit_with_error);
+                infer::print_forward(&dims, frequency);
+            }
+            BackendCommand::InferBatch {
+                input,
+                progress,
+                chunk_size,
+            } => {
+                let freqs = read_frequencies(&input).unwrap_or_else(exit_with_error);
+                info!(
+                    "🔍 Inferring for {} frequencies on {:?}...",
+                    freqs.len(),
+                    device
+                );
+                let results = run_batch_with_progress::<B::InnerBackend>(
+                    artifact_dir,
+                    &freqs,
+                    chunk_size,
+                    device,
+                    progress,
+                );
+                for (freq, dims) in freqs.iter().zip(&results) {
+                    println!("\n🎵 {} Hz", freq);
+                    infer::print_dimensions(dims, infer::Unit::Meters);
+                }
+            }
+            BackendCommand::InferCsv {
+                input,
+                output,
+                progress,
+                chunk_size,
+            } => {
+                let reader = File::open(&input).unwrap_or_else(exit_with_error);
+                let freqs = infer::read_frequencies_csv(reader).unwrap_or_else(exit_with_error);
+                info!(
+                    "🔍 Inferring for {} frequencies from {:?} on {:?}...",
+                    freqs.len(),
+                    input,
+                    device
+                );
+                let results = run_batch_with_progress::<B::InnerBackend>(
+                    artifact_dir,
+                    &freqs,
+                    chunk_size,
+                    device,
+                    progress,
+                );
+                let writer = File::create(&output).unwrap_or_else(exit_with_error);
+                infer::write_dimensions_csv(writer, &freqs, &results)
+                    .unwrap_or_else(exit_with_error);
+                info!("✅ Results written to {:?}", output);
+            }
+            BackendCommand::Sweep {
+                start,
+                end,
+                step,
+                output,
+                progress,
+                chunk_size,
+            } => {
+                let freqs =
+                    infer::sweep_frequencies(start, end, step).unwrap_or_else(exit_with_error);
+                info!(
+                    "🔍 Sweeping {} frequencies from {} Hz to {} Hz on {:?}...",
+                    freqs.len(),
+                    start,
+                    end,
+                    device
+                );
+                let results = run_batch_with_progress::<B::InnerBackend>(
+                    artifact_dir,
+                    &freqs,
+                    chunk_size,
+                    device,
+                    progress,
+                );
+                match output {
+                    Some(output) => {
+                        let writer = File::create(&output).unwrap_or_else(exit_with_error);
+                        infer::write_dimensions_csv(writer, &freqs, &results)
+                            .unwrap_or_else(exit_with_error);
+                        info!("✅ Results written to {:?}", output);
+                    }
+                    None => infer::print_dimensions_table(&freqs, &results),
+                }
+            }
+            BackendCommand::Eval {
+                model,
+                input,
+                chunk_size,
+            } => {
+                let model_dir = model.as_deref().unwrap_or(artifact_dir);
+                let reader = File::open(&input).unwrap_or_else(exit_with_error);
+                let freqs = infer::read_frequencies_csv(reader).unwrap_or_else(exit_with_error);
+                info!(
+                    "📏 Evaluating '{}' on {} target frequencies from {:?}...",
+                    model_dir,
+                    freqs.len(),
+                    input
+                );
+                let summary =
+                    infer::run_eval::<B::InnerBackend>(model_dir, &freqs, chunk_size, device)
+                        .unwrap_or_else(exit_with_error);
+                infer::print_evaluation(&summary);
+            }
+            BackendCommand::Compare {
+                model_a,
+                model_b,
+                start,
+                end,
+                step,
+                chunk_size,
+            } => {
+                let freqs =
+                    infer::sweep_frequencies(start, end, step).unwrap_or_else(exit_with_error);
+                info!(
+                    "⚖️ Comparing '{}' (A) and '{}' (B) on {} frequencies...",
+                    model_a,
+                    model_b,
+                    freqs.len()
+                );
+                let rows = infer::run_compare::<B::InnerBackend>(
+                    &model_a, &model_b, &freqs, chunk_size, device,
+                )
+                .unwrap_or_else(exit_with_error);
+                infer::print_comparison(&rows);
+            }
+            BackendCommand::ExportOnnx { output } => {
+                export::run_onnx::<B::InnerBackend>(artifact_dir, &output, device)
+                    .unwrap_or_else(exit_with_error);
+                info!("✅ ONNX model written to {:?}", output);
+            }
+            BackendCommand::ExportSafetensors { output } => {
+                export::run_safetensors::<B::InnerBackend>(artifact_dir, &output, device)
+                    .unwrap_or_else(exit_with_error);
+                info!("✅ Weights written to {:?}", output);
+            }
+            BackendCommand::ExportScad { freq, output } => {
+                export::run_scad::<B::InnerBackend>(artifact_dir, freq, &output, device)
+                    .unwrap_or_else(exit_with_error);
+                info!("✅ OpenSCAD script for {} Hz written to {:?}", freq, output);
+            }
+            BackendCommand::ExportStl {
+                freq,
+                segments,
+                output,
+            } => {
+                export::run_stl::<B::InnerBackend>(artifact_dir, freq, segments, &output, device)
+                    .unwrap_or_else(exit_with_error);
+                info!("✅ STL mesh for {} Hz written to {:?}", freq, output);
+            }
+            #[cfg(feature = "server")]
+            BackendCommand::Serve { host, port } => {
+                info!("🚀 Starting inference server on {:?}...", device);
+                let addr = std::net::SocketAddr::new(host, port);
+                server::run::<B::InnerBackend>(artifact_dir, addr, device)
+                    .unwrap_or_else(exit_with_error);
             }
         }
+    }
+}
+
+/// エラーメッセージを表示し、終了コード1でプロセスを終了します。
+fn exit_with_error(err: impl Display) -> ! {
+    error!("❌ {err}");
+    std::process::exit(1);
+}
+
+/// 複数の周波数に対する推論を実行し、`progress`が指定された場合は進捗バーを表示します。
+///
+/// 進捗バーは標準エラー出力が端末の場合にのみ表示し、推論が終わると消去します。
+/// `progress`フィーチャーを無効にしたビルドでは、警告を出力して進捗バーを表示せずに推論します。
+/// 推論に失敗した場合はプロセスを終了します。
+fn run_batch_with_progress<B: Backend>(
+    artifact_dir: &str,
+    freqs: &[f32],
+    chunk_size: usize,
+    device: B::Device,
+    progress: bool,
+) -> Vec<infer::ForkDimensions> {
+    #[cfg(feature = "progress")]
+    let bar = (progress && std::io::stderr().is_terminal()).then(|| {
+        let style = ProgressStyle::with_template("{bar:40} {pos}/{len} frequencies ({eta})")
+            .expect("progress bar template is valid");
+        ProgressBar::new(freqs.len() as u64).with_style(style)
+    });
+    #[cfg(feature = "progress")]
+    let mut on_progress = |done: usize, _total: usize| {
+        if let Some(bar) = &bar {
+            bar.set_position(done as u64);
+        }
+    };
+    #[cfg(feature = "progress")]
+    let callback: Option<infer::ProgressCallback<'_>> = Some(&mut on_progress);
+    #[cfg(not(feature = "progress"))]
+    let callback = {
+        if progress {
+            warn!("⚠️  --progress requires the `progress` feature; ignoring it.");
+        }
+        None
     };
+    let results = infer::run_batch::<B>(artifact_dir, freqs, None, chunk_size, device, callback)
+        .unwrap_or_else(exit_with_error);
+    #[cfg(feature = "progress")]
+    if let Some(bar) = bar {
+        bar.finish_and_clear();
+    }
+    results
+}
+
+/// 周波数リストを読み込みます。
+///
+/// `input`が既存のファイルを指す場合はその内容を、そうでなければ`input`自体を
+/// カンマ区切りのリストとして解析します。
+fn read_frequencies(input: &str) -> Result<Vec<f32>, infer::InferError> {
+    if Path::new(input).is_file() {
+        let text = std::fs::read_to_string(input).map_err(|err| {
+            infer::InferError::InvalidInput(format!("cannot read '{input}': {err}"))
+        })?;
+        infer::parse_frequencies(&text)
+    } else {
+        infer::parse_frequencies(input)
+    }
 }
 
 /// アプリケーションのエントリーポイント
@@ -63,22 +676,30 @@ macro_rules! run_action {
 /// 適切なアクション（学習または推論）を実行します。
 fn main() {
     let cli = Cli::parse();
-
-    match cli.backend.as_str() {
-        "wgpu" => {
-            let device = burn::backend::wgpu::WgpuDevice::default();
-            run_action!(Wgpu, device, cli.command);
-        }
-        "ndarray" => {
-            let device = burn::backend::ndarray::NdArrayDevice::default();
-            run_action!(NdArray, device, cli.command);
-        }
-        "cuda" => {
-            let device = burn::backend::cuda::CudaDevice::default();
-            run_action!(Cuda, device, cli.command);
-        }
-        _ => {
-            panic!("❌ Invalid backend specified. Use 'wgpu', 'ndarray', or 'cuda'.");
+    cli.verbosity.init_logger();
+    let command = match cli.command {
+        Commands::ListBackends => {
+            for name in device::available_backends() {
+                println!("{name}");
+            }
+            return;
         }
+        Commands::Backend(command) => command,
+    };
+    if cli.device_index.is_some() && cli.backend != "wgpu" {
+        warn!(
+            "⚠️  --device-index is only supported by the wgpu backend; ignoring it for '{}'.",
+            cli.backend
+        );
     }
+
+    let options = BackendOptions {
+        device_index: cli.device_index,
+        dtype: cli.dtype,
+    };
+    let action = CommandAction {
+        command,
+        artifact_dir: &cli.artifact_dir,
+    };
+    backend::dispatch(&cli.backend, &options, action).unwrap_or_else(exit_with_error);
 }
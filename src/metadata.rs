@@ -0,0 +1,75 @@
+//! # モデルのメタデータ
+//!
+//! 学習済みモデルの来歴 (クレートのバージョン・学習日時・材質・周波数範囲・最終損失) を
+//! 成果物ディレクトリの[`METADATA_FILE`]にJSONとして保存します。
+
+use crate::constants::physics::Material;
+use crate::train::TrainingConfig;
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use std::{fmt, fs, io, path::Path};
+
+/// メタデータを保存するファイル名 (成果物ディレクトリからの相対パス)。
+pub const METADATA_FILE: &str = "metadata.json";
+
+/// 学習済みモデルの来歴。
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ModelMetadata {
+    /// 学習に使用したクレートのバージョン。
+    pub crate_version: String,
+    /// 学習が完了した日時 (UTC)。
+    pub trained_at: DateTime<Utc>,
+    /// 学習時の音叉の材質。
+    pub material: Material,
+    /// 学習データの周波数範囲の下限 (Hz)。
+    pub train_freq_min: f32,
+    /// 学習データの周波数範囲の上限 (Hz)。
+    pub train_freq_max: f32,
+    /// 最終エポックの検証損失。検証が行われなかった場合は`None`です。
+    pub final_loss: Option<f64>,
+}
+
+impl ModelMetadata {
+    /// 学習設定と最終損失から、現在の日時で学習したモデルのメタデータを作成します。
+    pub fn new(config: &TrainingConfig, final_loss: Option<f64>) -> Self {
+        Self {
+            crate_version: env!("CARGO_PKG_VERSION").to_string(),
+            trained_at: Utc::now(),
+            material: config.material,
+            train_freq_min: config.train_freq_min,
+            train_freq_max: config.train_freq_max,
+            final_loss,
+        }
+    }
+
+    /// メタデータを整形されたJSONとして`path`に保存します。
+    pub fn save(&self, path: impl AsRef<Path>) -> io::Result<()> {
+        let json = serde_json::to_string_pretty(self)?;
+        fs::write(path, json)
+    }
+
+    /// `path`からメタデータを読み込みます。
+    pub fn load(path: impl AsRef<Path>) -> io::Result<Self> {
+        let json = fs::read_to_string(path)?;
+        Ok(serde_json::from_str(&json)?)
+    }
+}
+
+impl fmt::Display for ModelMetadata {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(f, "--- Model Metadata ---")?;
+        writeln!(f, "  - Crate Version:   {}", self.crate_version)?;
+        writeln!(f, "  - Trained At:      {}", self.trained_at.to_rfc3339())?;
+        writeln!(f, "  - Material:        {:?}", self.material)?;
+        writeln!(
+            f,
+            "  - Frequency Range: {} - {} Hz",
+            self.train_freq_min, self.train_freq_max
+        )?;
+        match self.final_loss {
+            Some(loss) => writeln!(f, "  - Final Loss:      {loss:.6}")?,
+            None => writeln!(f, "  - Final Loss:      n/a")?,
+        }
+        write!(f, "----------------------")
+    }
+}
@@ -0,0 +1,473 @@
+//! # 学習メトリクス
+//!
+//! 学習・検証中にダッシュボードへ表示するカスタムメトリクスを定義します。
+
+use crate::constants::{bounds, model_dims};
+use crate::physics::{LossComponents, PhysicsConfig, predicted_frequency_with_config};
+use burn::{
+    prelude::*,
+    train::RegressionOutput,
+    train::metric::{
+        Metric, MetricEntry, MetricMetadata, Numeric,
+        state::{FormatOptions, NumericMetricState},
+    },
+};
+use std::{
+    fmt,
+    fs::{self, OpenOptions},
+    io::{self, Write},
+    marker::PhantomData,
+    path::PathBuf,
+};
+
+/// 損失関数のどの項を表示するかを表します。
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum LossComponentKind {
+    /// 周波数損失。
+    Frequency,
+    /// プロング長と柄の長さの比率ペナルティ。
+    Ratio,
+    /// プロングの範囲ペナルティ。
+    Range,
+    /// その他の寸法の範囲ペナルティ。
+    Other,
+    /// 近傍の周波数に対する自己一貫性ペナルティ。
+    Consistency,
+    /// 倍音の周波数損失。
+    Overtone,
+    /// Q値の損失。
+    QFactor,
+}
+
+impl LossComponentKind {
+    /// すべての項。
+    pub const ALL: [LossComponentKind; 7] = [
+        LossComponentKind::Frequency,
+        LossComponentKind::Ratio,
+        LossComponentKind::Range,
+        LossComponentKind::Other,
+        LossComponentKind::Consistency,
+        LossComponentKind::Overtone,
+        LossComponentKind::QFactor,
+    ];
+
+    /// ダッシュボードに表示するメトリクス名。
+    pub fn name(&self) -> &'static str {
+        match self {
+            LossComponentKind::Frequency => "Frequency Loss",
+            LossComponentKind::Ratio => "Ratio Penalty",
+            LossComponentKind::Range => "Range Penalty",
+            LossComponentKind::Other => "Other Penalty",
+            LossComponentKind::Consistency => "Consistency Penalty",
+            LossComponentKind::Overtone => "Overtone Loss",
+            LossComponentKind::QFactor => "Q Factor Loss",
+        }
+    }
+}
+
+/// 損失関数の一つの項 (重み適用前) の平均値を報告するメトリクス。
+pub struct LossComponentMetric<B: Backend> {
+    kind: LossComponentKind,
+    state: NumericMetricState,
+    _backend: PhantomData<B>,
+}
+
+impl<B: Backend> LossComponentMetric<B> {
+    /// 指定された項を報告するメトリクスを作成します。
+    pub fn new(kind: LossComponentKind) -> Self {
+        Self {
+            kind,
+            state: NumericMetricState::default(),
+            _backend: PhantomData,
+        }
+    }
+}
+
+impl<B: Backend> Metric for LossComponentMetric<B> {
+    type Input = LossComponents<B>;
+
+    fn update(&mut self, item: &Self::Input, _metadata: &MetricMetadata) -> MetricEntry {
+        let component = match self.kind {
+            LossComponentKind::Frequency => &item.frequency_loss,
+            LossComponentKind::Ratio => &item.ratio_penalty,
+            LossComponentKind::Range => &item.range_penalty,
+            LossComponentKind::Other => &item.other_penalty,
+            LossComponentKind::Consistency => &item.consistency_penalty,
+            LossComponentKind::Overtone => &item.overtone_loss,
+            LossComponentKind::QFactor => &item.q_factor_loss,
+        };
+        let value = component.clone().into_scalar().elem::<f64>();
+
+        // 各項はバッチ平均済みのため、バッチごとに等しい重みで集計する
+        self.state
+            .update(value, 1, FormatOptions::new(self.name()).precision(6))
+    }
+
+    fn clear(&mut self) {
+        self.state.reset()
+    }
+
+    fn name(&self) -> String {
+        self.kind.name().to_string()
+    }
+}
+
+impl<B: Backend> Numeric for LossComponentMetric<B> {
+    fn value(&self) -> f64 {
+        self.state.value()
+    }
+}
+
+/// 予測寸法から逆算した周波数と目標周波数の平均絶対誤差 (Hz) を報告するメトリクス。
+///
+/// 損失は周波数損失とペナルティを重み付けして合計した値のため、そのままでは精度を
+/// 解釈しづらくなります。このメトリクスは`RegressionOutput`の予測寸法から物理モデルで
+/// 周波数を再計算し、`mean |Δf|` を報告します。
+pub struct FrequencyErrorMetric<B: Backend> {
+    physics: PhysicsConfig,
+    state: NumericMetricState,
+    _backend: PhantomData<B>,
+}
+
+impl<B: Backend> FrequencyErrorMetric<B> {
+    /// 指定された物理モデルで周波数を再計算するメトリクスを作成します。
+    pub fn new(physics: PhysicsConfig) -> Self {
+        Self {
+            physics,
+            state: NumericMetricState::default(),
+            _backend: PhantomData,
+        }
+    }
+}
+
+impl<B: Backend> Metric for FrequencyErrorMetric<B> {
+    type Input = RegressionOutput<B>;
+
+    fn update(&mut self, item: &Self::Input, _metadata: &MetricMetadata) -> MetricEntry {
+        let predicted = predicted_frequency_with_config(item.output.clone(), &self.physics);
+        let [batch_size] = predicted.dims();
+        let targets = item.targets.clone().reshape([batch_size]);
+        let value = (predicted - targets)
+            .abs()
+            .mean()
+            .into_scalar()
+            .elem::<f64>();
+
+        // バッチごとの平均を、サンプル数で重み付けして集計する
+        self.state.update(
+            value,
+            batch_size,
+            FormatOptions::new(self.name()).unit("Hz").precision(3),
+        )
+    }
+
+    fn clear(&mut self) {
+        self.state.reset()
+    }
+
+    fn name(&self) -> String {
+        "Frequency Error".to_string()
+    }
+}
+
+impl<B: Backend> Numeric for FrequencyErrorMetric<B> {
+    fn value(&self) -> f64 {
+        self.state.value()
+    }
+}
+
+/// 製造可能とみなす各寸法の範囲 (列のインデックス, 下限, 上限) [m]。
+const DIMENSION_BOUNDS: [(usize, f32, f32); 5] = [
+    (
+        model_dims::HANDLE_LENGTH_IDX,
+        bounds::HANDLE_LENGTH_MIN,
+        bounds::HANDLE_LENGTH_MAX,
+    ),
+    (
+        model_dims::HANDLE_DIAMETER_IDX,
+        bounds::HANDLE_DIAMETER_MIN,
+        bounds::HANDLE_DIAMETER_MAX,
+    ),
+    (
+        model_dims::PRONG_LENGTH_IDX,
+        bounds::PRONG_LENGTH_MIN,
+        bounds::PRONG_LENGTH_MAX,
+    ),
+    (
+        model_dims::PRONG_DIAMETER_IDX,
+        bounds::PRONG_DIAMETER_MIN,
+        bounds::PRONG_DIAMETER_MAX,
+    ),
+    (
+        model_dims::PRONG_GAP_IDX,
+        bounds::PRONG_GAP_MIN,
+        bounds::PRONG_GAP_MAX,
+    ),
+];
+
+/// 予測寸法の1行がすべての範囲の制約を満たすかを判定します。
+///
+/// 矩形断面 (6列) の場合は、厚さにも直径と同じ範囲を適用します。
+fn is_physically_valid(row: &[f32]) -> bool {
+    let in_range = |value: f32, lower: f32, upper: f32| (lower..=upper).contains(&value);
+    let within_bounds = DIMENSION_BOUNDS
+        .iter()
+        .all(|&(idx, lower, upper)| in_range(row[idx], lower, upper));
+    let height_within_bounds = match row.get(model_dims::PRONG_HEIGHT_IDX) {
+        Some(&height) => in_range(height, bounds::PRONG_HEIGHT_MIN, bounds::PRONG_HEIGHT_MAX),
+        None => true,
+    };
+    within_bounds && height_within_bounds
+}
+
+/// 予測寸法のうち、すべての範囲の制約を満たす行の割合 (%) を報告するメトリクス。
+///
+/// ペナルティの合計値では「どれだけの予測がそのまま製造できるか」が分からないため、
+/// 行ごとに制約を判定して割合を集計します。
+pub struct PhysicalValidityMetric<B: Backend> {
+    state: NumericMetricState,
+    _backend: PhantomData<B>,
+}
+
+impl<B: Backend> PhysicalValidityMetric<B> {
+    /// 新しいメトリクスを作成します。
+    pub fn new() -> Self {
+        Self {
+            state: NumericMetricState::default(),
+            _backend: PhantomData,
+        }
+    }
+}
+
+impl<B: Backend> Default for PhysicalValidityMetric<B> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<B: Backend> Metric for PhysicalValidityMetric<B> {
+    type Input = RegressionOutput<B>;
+
+    fn update(&mut self, item: &Self::Input, _metadata: &MetricMetadata) -> MetricEntry {
+        let [batch_size, num_dims] = item.output.dims();
+        let values: Vec<f32> = item
+            .output
+            .clone()
+            .into_data()
+            .convert::<f32>()
+            .into_vec()
+            .unwrap();
+        let num_valid = values
+            .chunks_exact(num_dims)
+            .filter(|row| is_physically_valid(row))
+            .count();
+        let percentage = 100.0 * num_valid as f64 / batch_size as f64;
+
+        self.state.update(
+            percentage,
+            batch_size,
+            FormatOptions::new(self.name()).unit("%").precision(1),
+        )
+    }
+
+    fn clear(&mut self) {
+        self.state.reset()
+    }
+
+    fn name(&self) -> String {
+        "Physical Validity".to_string()
+    }
+}
+
+impl<B: Backend> Numeric for PhysicalValidityMetric<B> {
+    fn value(&self) -> f64 {
+        self.state.value()
+    }
+}
+
+/// エポックごとの学習損失と検証損失をCSVに追記し、学習曲線を記録するロガー。
+///
+/// 学習中のダッシュボードは端末に表示されるだけなので、プロット用に
+/// `epoch,train_loss,valid_loss` の行を書き出します。値が得られなかった列は空になります。
+#[derive(Clone, Debug)]
+pub struct MetricLogger {
+    path: PathBuf,
+}
+
+impl MetricLogger {
+    /// CSVのヘッダー行。
+    pub const HEADER: &str = "epoch,train_loss,valid_loss";
+
+    /// ヘッダー行だけを書き込んだCSVを作成します。既存のファイルは上書きされます。
+    ///
+    /// # Errors
+    ///
+    /// ファイルを作成できない場合にI/Oエラーを返します。
+    pub fn create(path: impl Into<PathBuf>) -> io::Result<Self> {
+        let path = path.into();
+        fs::write(&path, format!("{}\n", Self::HEADER))?;
+        Ok(Self { path })
+    }
+
+    /// 1エポック分の行を追記します。
+    ///
+    /// # Errors
+    ///
+    /// ファイルに書き込めない場合にI/Oエラーを返します。
+    pub fn log_epoch(
+        &self,
+        epoch: usize,
+        train_loss: Option<f64>,
+        valid_loss: Option<f64>,
+    ) -> io::Result<()> {
+        let cell = |value: Option<f64>| value.map(|v| v.to_string()).unwrap_or_default();
+        let mut file = OpenOptions::new().append(true).open(&self.path)?;
+        writeln!(file, "{epoch},{},{}", cell(train_loss), cell(valid_loss))
+    }
+}
+
+/// 予測寸法から逆算した周波数の誤差が許容値に収まる割合と、誤差の平均・中央値をまとめた結果。
+///
+/// 学習後の評価 ([`crate::train::evaluate_tolerance`]) で、「どれだけの予測がそのまま目標の
+/// 周波数に使えるか」を報告するために使用します。
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct ToleranceReport {
+    /// 評価した予測の数。
+    pub num_predictions: usize,
+    /// 誤差が許容値以下だった予測の数。
+    pub num_within: usize,
+    /// 許容する誤差 (Hz)。
+    pub tolerance_hz: f32,
+    /// 誤差の絶対値の平均 (Hz)。
+    pub mean_error_hz: f32,
+    /// 誤差の絶対値の中央値 (Hz)。
+    pub median_error_hz: f32,
+}
+
+impl ToleranceReport {
+    /// 各予測の周波数の誤差 (逆算した周波数 − 目標周波数、Hz) から結果をまとめます。
+    ///
+    /// 誤差の絶対値が`tolerance_hz`以下の予測を許容範囲内とみなします (`NaN`は範囲外)。
+    /// `errors`が空の場合、平均と中央値は`NaN`になります。
+    pub fn from_errors(errors: &[f32], tolerance_hz: f32) -> Self {
+        let mut abs_errors: Vec<f32> = errors.iter().map(|error| error.abs()).collect();
+        abs_errors.sort_by(f32::total_cmp);
+        let num_predictions = abs_errors.len();
+        let num_within = abs_errors
+            .iter()
+            .filter(|&&error| error <= tolerance_hz)
+            .count();
+        let mean_error_hz = abs_errors.iter().sum::<f32>() / num_predictions as f32;
+        let median_error_hz = sorted_percentile(&abs_errors, 50.0);
+        Self {
+            num_predictions,
+            num_within,
+            tolerance_hz,
+            mean_error_hz,
+            median_error_hz,
+        }
+    }
+
+    /// 誤差が許容値以下だった予測の割合 (%) を返します。予測がない場合は0です。
+    pub fn percent_within(&self) -> f32 {
+        if self.num_predictions == 0 {
+            return 0.0;
+        }
+        100.0 * self.num_within as f32 / self.num_predictions as f32
+    }
+}
+
+impl fmt::Display for ToleranceReport {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{:.1}% of {} predictions within ±{} Hz (mean error {:.3} Hz, median {:.3} Hz)",
+            self.percent_within(),
+            self.num_predictions,
+            self.tolerance_hz,
+            self.mean_error_hz,
+            self.median_error_hz
+        )
+    }
+}
+
+/// 昇順に並んだ値の`percentile`パーセンタイル (0〜100) を、隣り合う値の線形補間で返します。
+/// 値がない場合は`NaN`です。
+fn sorted_percentile(sorted: &[f32], percentile: f32) -> f32 {
+    if sorted.is_empty() {
+        return f32::NAN;
+    }
+    let rank = percentile.clamp(0.0, 100.0) / 100.0 * (sorted.len() - 1) as f32;
+    let (lower, upper) = (rank.floor() as usize, rank.ceil() as usize);
+    let fraction = rank - lower as f32;
+    sorted[lower] + (sorted[upper] - sorted[lower]) * fraction
+}
+
+/// 周波数の誤差の絶対値の`percentile`パーセンタイル (0〜100、Hz) を返します。
+///
+/// 隣り合う値の線形補間で計算するため、50パーセンタイルは中央値と一致します。
+/// `errors`が空の場合は`NaN`です。
+pub fn error_percentile(errors: &[f32], percentile: f32) -> f32 {
+    let mut abs_errors: Vec<f32> = errors.iter().map(|error| error.abs()).collect();
+    abs_errors.sort_by(f32::total_cmp);
+    sorted_percentile(&abs_errors, percentile)
+}
+
+/// 目標周波数の一覧に対する予測の周波数誤差と、物理的に妥当な予測の割合をまとめた結果
+/// (`eval`サブコマンド)。
+///
+/// 誤差は予測寸法から物理モデルで逆算した周波数と目標周波数の差の絶対値で、妥当性は
+/// [`PhysicalValidityMetric`]と同じ範囲の制約で判定します。
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct EvaluationSummary {
+    /// 評価した予測の数。
+    pub num_predictions: usize,
+    /// 誤差の平均 (Hz)。
+    pub mean_error_hz: f32,
+    /// 誤差の最大値 (Hz)。
+    pub max_error_hz: f32,
+    /// 誤差の50パーセンタイル (中央値、Hz)。
+    pub p50_error_hz: f32,
+    /// 誤差の90パーセンタイル (Hz)。
+    pub p90_error_hz: f32,
+    /// 誤差の99パーセンタイル (Hz)。
+    pub p99_error_hz: f32,
+    /// すべての寸法が製造可能な範囲に収まる予測の割合 (%)。
+    pub validity_percent: f32,
+}
+
+impl EvaluationSummary {
+    /// 各予測の周波数の誤差 (Hz) と、予測寸法の行 (モデル出力と同じ列の順序) から結果を
+    /// まとめます。
+    ///
+    /// `errors`が空の場合、誤差の統計は`NaN`、妥当性の割合は0になります。
+    ///
+    /// # Panics
+    ///
+    /// `errors`と`dims`の長さが異なる場合にパニックします。
+    pub fn new(errors: &[f32], dims: &[[f32; model_dims::NUM_DIMS]]) -> Self {
+        assert_eq!(
+            errors.len(),
+            dims.len(),
+            "got {} errors for {} rows of dimensions",
+            errors.len(),
+            dims.len()
+        );
+        let num_predictions = errors.len();
+        let num_valid = dims.iter().filter(|row| is_physically_valid(&row[..])).count();
+        Self {
+            num_predictions,
+            mean_error_hz: errors.iter().map(|error| error.abs()).sum::<f32>()
+                / num_predictions as f32,
+            max_error_hz: error_percentile(errors, 100.0),
+            p50_error_hz: error_percentile(errors, 50.0),
+            p90_error_hz: error_percentile(errors, 90.0),
+            p99_error_hz: error_percentile(errors, 99.0),
+            validity_percent: if dims.is_empty() {
+                0.0
+            } else {
+                100.0 * num_valid as f32 / dims.len() as f32
+            },
+        }
+    }
+}
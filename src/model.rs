@@ -2,67 +2,828 @@
 //!
 //! 周波数から音叉の寸法を予測するための、シンプルな多層パーセプトロン (MLP) モデルを定義します。
 
-use crate::constants::model_dims;
+use crate::constants::{
+    model_dims,
+    physics::{Material, REFERENCE_TEMPERATURE},
+};
+use crate::infer::{self, ForkDimensions, InferError};
+use crate::physics::PhysicsConfig;
 use burn::prelude::*;
 use burn::{
-    nn::{Linear, LinearConfig, Relu},
-    tensor::activation::softplus,
+    module::{Ignored, Param},
+    nn::{
+        BatchNorm, BatchNormConfig, Dropout, DropoutConfig, Initializer, Linear, LinearConfig,
+    },
+    tensor::activation::{gelu, relu, silu, softplus, tanh},
 };
+use std::str::FromStr;
+
+/// 隠れ層で使用する活性化関数。
+#[derive(Config, Debug, PartialEq)]
+pub enum Activation {
+    /// ReLU (デフォルト)。
+    Relu,
+    /// 双曲線正接。滑らかな回帰対象に向いています。
+    Tanh,
+    /// GELU。
+    Gelu,
+    /// SiLU (Swish)。
+    SiLU,
+}
+
+impl FromStr for Activation {
+    type Err = String;
+
+    /// `relu`、`tanh`、`gelu`、`silu` を解析します。
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_ascii_lowercase().as_str() {
+            "relu" => Ok(Activation::Relu),
+            "tanh" => Ok(Activation::Tanh),
+            "gelu" => Ok(Activation::Gelu),
+            "silu" => Ok(Activation::SiLU),
+            _ => Err(format!(
+                "unknown activation '{s}' (expected relu, tanh, gelu or silu)"
+            )),
+        }
+    }
+}
+
+/// 全結合層の重みの初期化方法。
+///
+/// 活性化関数との組み合わせの目安:
+/// - `ReLU`・`GELU`・`SiLU`: 負の入力の半分を捨てる分を補う`KaimingNormal`。
+/// - `Tanh`: 原点付近で線形に近く、入出力の分散を揃える`XavierUniform`。
+#[derive(Config, Debug, PartialEq)]
+pub enum InitKind {
+    /// Burnの`LinearConfig`のデフォルト (ゲイン`1/√3`のKaiming一様分布)。
+    Default,
+    /// He (Kaiming) の正規分布。ゲイン`√2`で、重みの分散は`2 / fan_in`になります。
+    KaimingNormal,
+    /// Xavier (Glorot) の一様分布。重みの分散は`2 / (fan_in + fan_out)`になります。
+    XavierUniform,
+}
+
+impl FromStr for InitKind {
+    type Err = String;
+
+    /// `default`、`kaiming-normal`、`xavier-uniform` を解析します。
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_ascii_lowercase().as_str() {
+            "default" => Ok(InitKind::Default),
+            "kaiming-normal" => Ok(InitKind::KaimingNormal),
+            "xavier-uniform" => Ok(InitKind::XavierUniform),
+            _ => Err(format!(
+                "unknown initializer '{s}' (expected default, kaiming-normal or xavier-uniform)"
+            )),
+        }
+    }
+}
+
+impl InitKind {
+    /// 対応するBurnの初期化方法を返します。`Default`の場合は`None`です。
+    fn initializer(&self) -> Option<Initializer> {
+        match self {
+            InitKind::Default => None,
+            InitKind::KaimingNormal => Some(Initializer::KaimingNormal {
+                gain: 2f64.sqrt(),
+                fan_out_only: false,
+            }),
+            InitKind::XavierUniform => Some(Initializer::XavierUniform { gain: 1.0 }),
+        }
+    }
+
+    /// 指定された層の設定に初期化方法を適用します。
+    fn apply(&self, config: LinearConfig) -> LinearConfig {
+        match self.initializer() {
+            Some(initializer) => config.with_initializer(initializer),
+            None => config,
+        }
+    }
+}
+
+/// 指定された活性化関数をテンソルに適用します。
+fn apply_activation<B: Backend>(activation: &Activation, x: Tensor<B, 2>) -> Tensor<B, 2> {
+    match activation {
+        Activation::Relu => relu(x),
+        Activation::Tanh => tanh(x),
+        Activation::Gelu => gelu(x),
+        Activation::SiLU => silu(x),
+    }
+}
+
+/// モデルのアーキテクチャに関する設定。
+#[derive(Config, Debug)]
+pub struct ModelConfig {
+    /// 入力周波数の正規化に使う平均 (Hz)。
+    ///
+    /// デフォルトは学習範囲 (200〜1800Hz) の一様分布の平均です。
+    #[config(default = 1000.0)]
+    pub freq_mean: f32,
+    /// 入力周波数の正規化に使う標準偏差 (Hz)。
+    ///
+    /// デフォルトは学習範囲 (200〜1800Hz) の一様分布の標準偏差 (`1600 / √12`) です。
+    #[config(default = 461.880_2)]
+    pub freq_std: f32,
+    /// 隠れ層のユニット数。
+    #[config(default = 128)]
+    pub hidden_size: usize,
+    /// 隠れ層の数。
+    #[config(default = 3)]
+    pub num_hidden_layers: usize,
+    /// 隠れ層の活性化関数。
+    #[config(default = "Activation::Relu")]
+    pub activation: Activation,
+    /// 全結合層の重みの初期化方法 ([`InitKind`]を参照)。
+    #[config(default = "InitKind::Default")]
+    pub init_kind: InitKind,
+    /// 出力する寸法の数。円形断面では`model_dims::NUM_DIMS`、矩形断面では
+    /// `model_dims::NUM_DIMS_RECTANGULAR`です。
+    #[config(default = "model_dims::NUM_DIMS")]
+    pub output_size: usize,
+    /// 各隠れ層の活性化関数の後に適用するドロップアウトの確率 (0.0以上1.0未満)。
+    ///
+    /// ドロップアウトは自動微分が有効なバックエンドでのみ適用されます。0.0の場合は何もしません。
+    /// [`crate::infer::run_with_uncertainty`]で不確かさを推定するには、0より大きい値で
+    /// 学習する必要があります。
+    #[config(default = 0.0)]
+    pub dropout: f64,
+    /// 隠れ層に残差接続 (スキップ接続) を加えるかどうか。
+    ///
+    /// 有効にすると、入出力の次元が等しい隠れ層 (2層目以降) で、層の入力を出力に加えてから
+    /// 活性化関数を適用します。最初の隠れ層と出力層は通常の射影のままです。
+    #[config(default = false)]
+    pub residual: bool,
+    /// 各隠れ層の全結合層と活性化関数の間にバッチ正規化を挿入するかどうか。
+    ///
+    /// 学習時 (自動微分が有効なバックエンド) はミニバッチの統計量で正規化し、推論時は学習中に
+    /// 蓄積した移動平均を使用します。
+    #[config(default = false)]
+    pub batch_norm: bool,
+    /// 寸法の後に、音叉のQ値の常用対数 (`log10(Q)`) を出力するかどうか。
+    ///
+    /// 有効にすると出力層の次元が`output_size + model_dims::NUM_Q_FACTOR_OUTPUTS`になります。
+    /// 無効のまま学習したモデルは従来どおり`output_size`次元の出力で読み込めます。
+    #[config(default = false)]
+    pub q_factor: bool,
+    /// 周波数に加えて材質を入力とするかどうか。
+    ///
+    /// 有効にすると入力は`[batch_size, 2]`になり、2列目に材質の特徴量 ([`material_feature`]) を
+    /// 与えます。1つのモデルで複数の材質の寸法を予測できます。
+    #[config(default = false)]
+    pub material_input: bool,
+    /// 周波数に加えて温度を入力とするかどうか。
+    ///
+    /// 有効にすると、材質の特徴量の後 (材質を入力としない場合は2列目) に温度の特徴量
+    /// ([`temperature_feature`]) の列が加わります。
+    #[config(default = false)]
+    pub temperature_input: bool,
+    /// 出力層の`softplus`の鋭さ (β)。
+    ///
+    /// 出力は `ln(1 + exp(β·x)) / β` です。大きくするほど0付近の形がReLUに近づき、
+    /// 小さな寸法を出力しやすくなります。正の有限値である必要があり、学習時は
+    /// [`crate::train::TrainingConfig::validate`]で検証されます。
+    #[config(default = 1.0)]
+    pub softplus_beta: f64,
+}
+
+/// 材質を入力とするモデル ([`ModelConfig::material_input`]) の2列目に与える材質の特徴量。
+///
+/// ステンレス鋼 (SUS304) を1とした縦波の速さ ([`Material::wave_speed`]) の比です。
+/// 同じ寸法の周波数はこの値に比例するため、材質ごとの寸法の違いを1つの値で表せます。
+pub fn material_feature(material: Material) -> f32 {
+    material.wave_speed() / Material::Steel304.wave_speed()
+}
+
+/// 温度を入力とするモデル ([`ModelConfig::temperature_input`]) に与える温度の特徴量の尺度 (℃)。
+pub const TEMPERATURE_FEATURE_SCALE: f32 = 100.0;
+
+/// 温度を入力とするモデルに与える温度の特徴量。
+///
+/// 基準温度 ([`REFERENCE_TEMPERATURE`]) からの差を[`TEMPERATURE_FEATURE_SCALE`]で割った値で、
+/// 実用的な温度範囲で周波数の特徴量と同程度の大きさになります。
+pub fn temperature_feature(temperature: f32) -> f32 {
+    (temperature - REFERENCE_TEMPERATURE) / TEMPERATURE_FEATURE_SCALE
+}
+
+impl ModelConfig {
+    /// モデルの入力の次元数 (周波数・材質・温度のうち入力とする数) を返します。
+    pub fn input_size(&self) -> usize {
+        1 + usize::from(self.material_input) + usize::from(self.temperature_input)
+    }
+
+    /// 設定に基づいて `TuningForkPINN` モデルを初期化します。
+    ///
+    /// # Panics
+    ///
+    /// `num_hidden_layers` が0の場合にパニックします。
+    pub fn init<B: Backend>(&self, device: &B::Device) -> TuningForkPINN<B> {
+        assert!(
+            self.num_hidden_layers > 0,
+            "num_hidden_layers must be at least 1"
+        );
+
+        let hidden_layers = (0..self.num_hidden_layers)
+            .map(|i| {
+                let d_input = if i == 0 { self.input_size() } else { self.hidden_size };
+                self.init_kind
+                    .apply(LinearConfig::new(d_input, self.hidden_size))
+                    .init(device)
+            })
+            .collect();
+        let batch_norms = if self.batch_norm {
+            (0..self.num_hidden_layers)
+                .map(|_| BatchNormConfig::new(self.hidden_size).init(device))
+                .collect()
+        } else {
+            Vec::new()
+        };
+
+        let num_outputs = if self.q_factor {
+            self.output_size + model_dims::NUM_Q_FACTOR_OUTPUTS
+        } else {
+            self.output_size
+        };
+
+        // 正規化の定数と`softplus`のβはレコードに保存されるようパラメータとして持つが、
+        // 学習はしない
+        let constant = |value: f32| {
+            Param::from_tensor(Tensor::from_floats([value], device)).set_require_grad(false)
+        };
+
+        TuningForkPINN {
+            freq_mean: constant(self.freq_mean),
+            freq_std: constant(self.freq_std),
+            hidden_layers,
+            batch_norms,
+            activation: Ignored(self.activation.clone()),
+            residual: Ignored(self.residual),
+            q_factor: Ignored(self.q_factor),
+            material_input: Ignored(self.material_input),
+            temperature_input: Ignored(self.temperature_input),
+            softplus_beta: constant(self.softplus_beta as f32),
+            dropout: DropoutConfig::new(self.dropout).init(),
+            output_layer: self
+                .init_kind
+                .apply(LinearConfig::new(self.hidden_size, num_outputs))
+                .init(device),
+        }
+    }
+}
 
 /// 音叉の寸法を予測するPINNモデル。
 ///
 /// ## アーキテクチャ
-/// - 入力: 周波数 (1次元)
-/// - 隠れ層: 3層の全結合層 (活性化関数: ReLU)
-/// - 出力: 音叉の寸法 (5次元)
+/// - 入力: 周波数 (1次元)。`freq_mean`と`freq_std`で平均0・分散1程度に正規化してから
+///   最初の層に入力します。`ModelConfig::material_input`・`ModelConfig::temperature_input`が
+///   有効な場合は、周波数の後に材質 ([`material_feature`])・温度 ([`temperature_feature`]) の
+///   特徴量の列が続き、これらの列は正規化しません。
+/// - 隠れ層: `num_hidden_layers`層の全結合層。`ModelConfig::residual`が有効な場合、
+///   2層目以降は`activation(layer(x) + x)`の残差ブロックになります。
+/// - バッチ正規化: `ModelConfig::batch_norm`が有効な場合、各隠れ層の全結合層の直後
+///   (残差を加える前) に挿入します。
+/// - 出力: 音叉の寸法 (デフォルトは5次元)
 ///   - [柄の長さ, 柄の直径, プロングの長さ, プロングの直径, プロングの間隔]
+///   - 矩形断面の場合は、プロングの直径の位置に幅が入り、末尾に厚さが追加されます (6次元)。
+///   - `ModelConfig::q_factor`が有効な場合は、寸法の後に`log10(Q)`が追加されます。
 ///
 /// ## 活性化関数
-/// - 隠れ層には`ReLU`を使用。
+/// - 隠れ層には`ModelConfig::activation`で選択した関数を使用 (デフォルト: `ReLU`)。
 /// - 出力層には`softplus`を使用し、寸法が必ず正の値になるように制約をかけます。
+///
+/// ## ドロップアウト
+/// - 各隠れ層の活性化関数の後に`ModelConfig::dropout`の確率でドロップアウトを適用します。
+/// - Burnのドロップアウトは自動微分が有効なバックエンドでのみ動作するため、通常の推論では
+///   決定的な出力になります。
+///
+/// ## 学習モードと推論モード
+/// - バッチ正規化はBurnの規約に従い、自動微分が有効なバックエンドではミニバッチの統計量、
+///   それ以外では移動平均を使用します。推論には`AutodiffModule::valid`で得られるモデル、
+///   または自動微分のないバックエンドで読み込んだモデルを使用してください。
 #[derive(Module, Debug)]
 pub struct TuningForkPINN<B: Backend> {
-    layer_1: Linear<B>,
-    activation_1: Relu,
-    layer_2: Linear<B>,
-    activation_2: Relu,
-    layer_3: Linear<B>,
-    activation_3: Relu,
+    freq_mean: Param<Tensor<B, 1>>,
+    freq_std: Param<Tensor<B, 1>>,
+    hidden_layers: Vec<Linear<B>>,
+    batch_norms: Vec<BatchNorm<B, 0>>,
+    activation: Ignored<Activation>,
+    residual: Ignored<bool>,
+    q_factor: Ignored<bool>,
+    material_input: Ignored<bool>,
+    temperature_input: Ignored<bool>,
+    softplus_beta: Param<Tensor<B, 1>>,
+    dropout: Dropout,
     output_layer: Linear<B>,
 }
 
 impl<B: Backend> TuningForkPINN<B> {
-    /// 新しい `TuningForkPINN` モデルを初期化します。
+    /// デフォルト設定で新しい `TuningForkPINN` モデルを初期化します。
     pub fn new(device: &B::Device) -> Self {
-        let hidden_size = 128;
-        Self {
-            layer_1: LinearConfig::new(1, hidden_size).init(device),
-            activation_1: Relu::new(),
-            layer_2: LinearConfig::new(hidden_size, hidden_size).init(device),
-            activation_2: Relu::new(),
-            layer_3: LinearConfig::new(hidden_size, hidden_size).init(device),
-            activation_3: Relu::new(),
-            output_layer: LinearConfig::new(hidden_size, model_dims::NUM_DIMS).init(device),
-        }
+        ModelConfig::new().init(device)
     }
 
     /// モデルのフォワードパス。
     ///
     /// # Arguments
-    /// * `input` - 周波数のテンソル。形状は `[batch_size, 1]`。
+    /// * `input` - 周波数のテンソル。形状は `[batch_size, 1]`。材質を入力とするモデルでは
+    ///   `[batch_size, input_size]` ([`Self::with_conditions`]を参照)。
     ///
     /// # Returns
-    /// 予測された寸法のテンソル。形状は `[batch_size, 5]`。
+    /// 予測された寸法のテンソル。形状は `[batch_size, output_size]`。
     pub fn forward(&self, input: Tensor<B, 2>) -> Tensor<B, 2> {
-        let x = self.layer_1.forward(input);
-        let x = self.activation_1.forward(x);
-        let x = self.layer_2.forward(x);
-        let x = self.activation_2.forward(x);
-        let x = self.layer_3.forward(x);
-        let x = self.activation_3.forward(x);
-        let x = self.output_layer.forward(x);
-
         // softplusを適用して出力が必ず正の値になるようにする
-        softplus(x, 1.0)
+        softplus(self.forward_raw(input), self.softplus_beta())
+    }
+
+    /// 出力層の`softplus`を適用する前の値を返すフォワードパス。
+    ///
+    /// 形状は[`Self::forward`]と同じです。寸法が飽和する原因の調査など、デバッグに使用します。
+    pub fn forward_raw(&self, input: Tensor<B, 2>) -> Tensor<B, 2> {
+        self.output_layer.forward(self.hidden_features(input))
+    }
+
+    /// 出力層に入力される特徴量 (最後の隠れ層の活性化とドロップアウトの後の値) を返します。
+    ///
+    /// 形状は`[batch_size, hidden_size]`です。
+    pub fn hidden_features(&self, input: Tensor<B, 2>) -> Tensor<B, 2> {
+        self.hidden_features_with_dropout(input, true)
+    }
+
+    /// [`Self::hidden_features`]の本体。`dropout`が`false`の場合はドロップアウトを適用しません。
+    fn hidden_features_with_dropout(&self, input: Tensor<B, 2>, dropout: bool) -> Tensor<B, 2> {
+        let mut x = self.normalize_input(input);
+        for (i, layer) in self.hidden_layers.iter().enumerate() {
+            // 最初の隠れ層は入力 (1次元) からの射影なので、残差を加えられない
+            let skip = (*self.residual && i > 0).then(|| x.clone());
+            x = layer.forward(x);
+            if let Some(batch_norm) = self.batch_norms.get(i) {
+                x = batch_norm.forward(x);
+            }
+            if let Some(skip) = skip {
+                x = x + skip;
+            }
+            x = apply_activation(&self.activation, x);
+            if dropout {
+                x = self.dropout.forward(x);
+            }
+        }
+        x
+    }
+
+    /// 1つの周波数 (Hz) に対する音叉の寸法を予測します。
+    ///
+    /// `[1, 1]`形状の入力テンソルの作成と出力の取り出しをまとめた、[`Self::forward`]の
+    /// スカラー版です。
+    ///
+    /// # Errors
+    ///
+    /// [`Self::predict_batch`]と同じ条件で`InferError`を返します。
+    pub fn predict(&self, freq: f32, device: &B::Device) -> Result<ForkDimensions, InferError> {
+        Ok(self.predict_batch(&[freq], device)?.remove(0))
+    }
+
+    /// 複数の周波数 (Hz) に対する音叉の寸法を一度に予測します。
+    ///
+    /// `[N, 1]`形状の入力テンソルで一括して推論し、出力を行ごとに分割します。
+    /// `freqs`が空の場合は空のベクタを返します。材質や温度を入力とするモデルには
+    /// [`Self::predict_batch_with_physics`]を使用してください。
+    ///
+    /// # Errors
+    ///
+    /// モデルの出力が円形断面の寸法数 (`model_dims::NUM_DIMS`) でない場合に
+    /// `InferError::RecordLoad`を、材質や温度を入力とするモデルの場合に
+    /// `InferError::InvalidInput`を返します。材質・温度を暗黙に仮定して誤った寸法を
+    /// 返さないためです。
+    pub fn predict_batch(
+        &self,
+        freqs: &[f32],
+        device: &B::Device,
+    ) -> Result<Vec<ForkDimensions>, InferError> {
+        if self.has_material_input() || self.has_temperature_input() {
+            return Err(InferError::InvalidInput(
+                "models with material or temperature input need a material and temperature"
+                    .to_string(),
+            ));
+        }
+        self.predict_batch_with_physics(freqs, &PhysicsConfig::new(), device)
+    }
+
+    /// `physics`の材質・温度の音叉として、複数の周波数 (Hz) に対する寸法を一度に予測します。
+    ///
+    /// 材質や温度を入力としないモデルではそれらを無視し、[`Self::predict_batch`]と同じ
+    /// 結果になります。
+    ///
+    /// # Errors
+    ///
+    /// モデルの出力が円形断面の寸法数 (`model_dims::NUM_DIMS`) でない場合に
+    /// `InferError::RecordLoad`を返します。
+    pub fn predict_batch_with_physics(
+        &self,
+        freqs: &[f32],
+        physics: &PhysicsConfig,
+        device: &B::Device,
+    ) -> Result<Vec<ForkDimensions>, InferError> {
+        infer::ensure_circular_output(self.output_size())?;
+        if freqs.is_empty() {
+            return Ok(Vec::new());
+        }
+        let input = Tensor::<B, 1>::from_floats(freqs, device).reshape([-1, 1]);
+        let values: Vec<f32> = self
+            .forward(self.with_conditions(input, physics))
+            .into_data()
+            .convert::<f32>()
+            .into_vec()
+            .unwrap();
+        let [_, num_outputs] = self.output_layer.weight.val().dims();
+        Ok(values
+            .chunks_exact(num_outputs)
+            .map(ForkDimensions::from_slice)
+            .collect())
+    }
+
+    /// 複数の周波数 (Hz) に対する音叉のQ値を予測します。
+    ///
+    /// モデルが出力する`log10(Q)`を`Q`に変換して返します。Q値を出力しないモデル
+    /// (`ModelConfig::q_factor`が無効) の場合は`None`です。`physics`の材質・温度は、それらを
+    /// 入力とするモデルでのみ使用します。
+    pub fn predict_q_factor_batch(
+        &self,
+        freqs: &[f32],
+        physics: &PhysicsConfig,
+        device: &B::Device,
+    ) -> Option<Vec<f32>> {
+        if !*self.q_factor {
+            return None;
+        }
+        if freqs.is_empty() {
+            return Some(Vec::new());
+        }
+        let input = Tensor::<B, 1>::from_floats(freqs, device).reshape([-1, 1]);
+        let log_q: Vec<f32> = self
+            .forward(self.with_conditions(input, physics))
+            .narrow(1, self.output_size(), model_dims::NUM_Q_FACTOR_OUTPUTS)
+            .into_data()
+            .convert::<f32>()
+            .into_vec()
+            .unwrap();
+        Some(log_q.into_iter().map(|value| 10f32.powf(value)).collect())
+    }
+
+    /// 出力する寸法の数を返します。Q値の出力は含みません。
+    pub fn output_size(&self) -> usize {
+        let [_, d_output] = self.output_layer.weight.val().dims();
+        if *self.q_factor {
+            d_output - model_dims::NUM_Q_FACTOR_OUTPUTS
+        } else {
+            d_output
+        }
+    }
+
+    /// 寸法の後にQ値 (`log10(Q)`) を出力するかどうかを返します。
+    pub fn has_q_factor(&self) -> bool {
+        *self.q_factor
+    }
+
+    /// 周波数に加えて材質を入力とするかどうかを返します ([`ModelConfig::material_input`])。
+    pub fn has_material_input(&self) -> bool {
+        *self.material_input
+    }
+
+    /// 周波数に加えて温度を入力とするかどうかを返します ([`ModelConfig::temperature_input`])。
+    pub fn has_temperature_input(&self) -> bool {
+        *self.temperature_input
+    }
+
+    /// モデルの入力の次元数を返します。
+    pub fn input_size(&self) -> usize {
+        1 + usize::from(*self.material_input) + usize::from(*self.temperature_input)
+    }
+
+    /// `[batch_size, 1]`形状の周波数のテンソルを、このモデルの入力に変換します。
+    ///
+    /// 材質や温度を入力とするモデルでは、`physics`の材質 ([`material_feature`])・温度
+    /// ([`temperature_feature`]) の特徴量の列をこの順に加えます。それ以外のモデルでは
+    /// `frequencies`をそのまま返します。
+    pub fn with_conditions(
+        &self,
+        frequencies: Tensor<B, 2>,
+        physics: &PhysicsConfig,
+    ) -> Tensor<B, 2> {
+        let features: Vec<f32> = [
+            (*self.material_input).then(|| material_feature(physics.material)),
+            (*self.temperature_input).then(|| temperature_feature(physics.temperature)),
+        ]
+        .into_iter()
+        .flatten()
+        .collect();
+        if features.is_empty() {
+            return frequencies;
+        }
+        let [batch_size, _] = frequencies.dims();
+        let device = frequencies.device();
+        let columns = features
+            .into_iter()
+            .map(|feature| Tensor::full([batch_size, 1], feature, &device));
+        Tensor::cat(std::iter::once(frequencies).chain(columns).collect(), 1)
+    }
+
+    /// 入力周波数を学習時の平均と標準偏差で正規化します。
+    ///
+    /// 200〜2000Hzの生の周波数をそのまま全結合層に入力すると条件数が悪くなるため、
+    /// `(f - freq_mean) / freq_std` に変換します。材質や温度を入力とするモデルでは、
+    /// 1列目の周波数だけを正規化します。
+    pub fn normalize_input(&self, input: Tensor<B, 2>) -> Tensor<B, 2> {
+        let normalize = |frequencies: Tensor<B, 2>| {
+            (frequencies - self.freq_mean.val().unsqueeze()) / self.freq_std.val().unsqueeze()
+        };
+        let num_features = self.input_size() - 1;
+        if num_features == 0 {
+            return normalize(input);
+        }
+        let frequencies = input.clone().narrow(1, 0, 1);
+        let features = input.narrow(1, 1, num_features);
+        Tensor::cat(vec![normalize(frequencies), features], 1)
+    }
+
+    /// 隠れ層の活性化関数を返します。
+    pub fn activation(&self) -> &Activation {
+        &self.activation
+    }
+
+    /// 隠れ層に残差接続があるかどうかを返します。
+    pub fn residual(&self) -> bool {
+        *self.residual
+    }
+
+    /// 出力層の`softplus`の鋭さ (β) を返します ([`ModelConfig::softplus_beta`])。
+    ///
+    /// βは正規化の定数と同じく学習しないパラメータとしてレコードに保存されるため、
+    /// 学習設定がなくても重みと一緒に復元されます。
+    pub fn softplus_beta(&self) -> f64 {
+        self.softplus_beta.val().into_scalar().elem::<f64>()
+    }
+
+    /// 隠れ層のバッチ正規化を返します。無効な場合は空です。
+    pub fn batch_norms(&self) -> &[BatchNorm<B, 0>] {
+        &self.batch_norms
+    }
+
+    /// 入力周波数の正規化に使う平均と標準偏差 (Hz) を返します。
+    pub fn input_normalization(&self) -> (f32, f32) {
+        let scalar = |param: &Param<Tensor<B, 1>>| param.val().into_scalar().elem::<f32>();
+        (scalar(&self.freq_mean), scalar(&self.freq_std))
+    }
+
+    /// 入力側から順に、すべての全結合層 (隠れ層と出力層) を返します。
+    pub fn linear_layers(&self) -> impl Iterator<Item = &Linear<B>> {
+        self.hidden_layers
+            .iter()
+            .chain(std::iter::once(&self.output_layer))
+    }
+
+    /// 隠れ層 (全結合層とバッチ正規化) のパラメータを勾配の計算対象から外したモデルを返します。
+    ///
+    /// 出力層だけを学習するファインチューニング ([`crate::train::TrainingConfig::freeze_hidden`])
+    /// に使用します。バッチ正規化の移動平均は学習対象のパラメータではないため、学習時の
+    /// フォワードパスでは引き続き更新されます。
+    pub fn freeze_hidden_layers(mut self) -> Self {
+        self.hidden_layers = self.hidden_layers.into_iter().map(Module::no_grad).collect();
+        self.batch_norms = self.batch_norms.into_iter().map(Module::no_grad).collect();
+        self
+    }
+
+    /// 出力層の重みとバイアスを、`input`に対する`softplus`の適用後の出力が先頭の列から順に
+    /// `targets`に近づくよう、リッジ回帰で設定したモデルを返します。
+    ///
+    /// `targets`の各行は`input`の同じ行に対応します。隠れ層は変更せず、ドロップアウトを
+    /// 適用しない隠れ層の出力 ([`Self::hidden_features`]) を説明変数、`softplus`の逆関数を
+    /// 適用した`targets`を目的変数とします。正則化の強さ`ridge`は、特徴量の2乗和の平均に
+    /// 対する比で指定します。`targets`より後ろの列 (`log10(Q)`など) の重みとバイアスは
+    /// 変更しません。
+    ///
+    /// # Errors
+    ///
+    /// 正規方程式が特異 (`ridge`が0で標本数が特徴量の数より少ない場合や、すべての特徴量が
+    /// 0の場合など) で係数が定まらない場合に`InferError::InvalidInput`を返します。
+    ///
+    /// # Panics
+    ///
+    /// `targets`の行数が`input`の行数と異なる場合、各行の要素数が揃っていないか出力の数を
+    /// 超える場合、または正の有限値でない値を含む場合にパニックします。
+    pub fn fit_output_layer(
+        mut self,
+        input: Tensor<B, 2>,
+        targets: &[Vec<f32>],
+        ridge: f64,
+    ) -> Result<Self, InferError> {
+        let [num_samples, _] = input.dims();
+        let [num_features, num_outputs] = self.output_layer.weight.val().dims();
+        let num_targets = targets.first().map_or(0, Vec::len);
+        assert_eq!(
+            targets.len(),
+            num_samples,
+            "got {} target rows for {num_samples} input rows",
+            targets.len()
+        );
+        assert!(
+            num_targets <= num_outputs && targets.iter().all(|row| row.len() == num_targets),
+            "each target row must have the same length of at most {num_outputs}"
+        );
+        assert!(
+            targets.iter().flatten().all(|target| target.is_finite() && *target > 0.0),
+            "targets must be positive finite numbers"
+        );
+
+        // 当てはめた係数が入力ごとに変わらないよう、ドロップアウトは適用しない
+        let features: Vec<f64> = self
+            .hidden_features_with_dropout(input, false)
+            .into_data()
+            .convert::<f64>()
+            .into_vec()
+            .unwrap();
+        // softplusの逆関数 ln(e^(β·y) − 1) / β
+        let beta = self.softplus_beta();
+        let responses: Vec<f64> = targets
+            .iter()
+            .flatten()
+            .map(|&target| (beta * f64::from(target)).exp_m1().ln() / beta)
+            .collect();
+        let Some(coefficients) =
+            ridge_regression(&features, num_features, &responses, num_targets, ridge)
+        else {
+            return Err(InferError::InvalidInput(
+                "cannot fit the output layer: the normal equations are singular".to_string(),
+            ));
+        };
+
+        // 置き換えたテンソルも元のパラメータと同じく勾配の計算対象 (葉) にする
+        self.output_layer.weight = self.output_layer.weight.map(|weight| {
+            let require_grad = weight.is_require_grad();
+            let mut values: Vec<f32> = weight.to_data().convert::<f32>().into_vec().unwrap();
+            for feature in 0..num_features {
+                for target in 0..num_targets {
+                    values[feature * num_outputs + target] =
+                        coefficients[feature * num_targets + target] as f32;
+                }
+            }
+            Tensor::<B, 1>::from_floats(values.as_slice(), &weight.device())
+                .reshape([num_features, num_outputs])
+                .set_require_grad(require_grad)
+        });
+        self.output_layer.bias = self.output_layer.bias.map(|bias| {
+            bias.map(|bias| {
+                let require_grad = bias.is_require_grad();
+                let mut values: Vec<f32> = bias.to_data().convert::<f32>().into_vec().unwrap();
+                let intercepts = &coefficients[num_features * num_targets..];
+                for (value, intercept) in values.iter_mut().zip(intercepts) {
+                    *value = *intercept as f32;
+                }
+                Tensor::<B, 1>::from_floats(values.as_slice(), &bias.device())
+                    .set_require_grad(require_grad)
+            })
+        });
+        Ok(self)
+    }
+
+    /// パラメータ (重みとバイアス、および入力正規化の定数と`softplus`のβ) の総数を返します。
+    pub fn num_parameters(&self) -> usize {
+        self.num_params()
+    }
+
+    /// 各層の入出力サイズとパラメータ数をまとめた、人が読める形式の要約を返します。
+    pub fn summary(&self) -> String {
+        let layers = self
+            .hidden_layers
+            .iter()
+            .enumerate()
+            .map(|(i, layer)| (format!("hidden_layers.{i}"), layer))
+            .chain(std::iter::once((
+                "output_layer".to_string(),
+                &self.output_layer,
+            )));
+
+        let mut summary = format!(
+            "TuningForkPINN (activation: {:?}, residual: {}, batch_norm: {})\n",
+            *self.activation,
+            *self.residual,
+            !self.batch_norms.is_empty()
+        );
+        summary.push_str(&format!(
+            "  {:<18} {:>5} -> {:<5} params: {}\n",
+            "input_norm",
+            self.input_size(),
+            self.input_size(),
+            self.freq_mean.num_params() + self.freq_std.num_params()
+        ));
+        for (i, (name, layer)) in layers.enumerate() {
+            let [d_input, d_output] = layer.weight.val().dims();
+            summary.push_str(&format!(
+                "  {name:<18} {d_input:>5} -> {d_output:<5} params: {}\n",
+                layer.num_params()
+            ));
+            if let Some(batch_norm) = self.batch_norms.get(i) {
+                summary.push_str(&format!(
+                    "  {:<18} {d_output:>5} -> {d_output:<5} params: {}\n",
+                    format!("batch_norms.{i}"),
+                    batch_norm.num_params()
+                ));
+            }
+        }
+        summary.push_str(&format!("  total params: {}", self.num_parameters()));
+        summary
+    }
+}
+
+/// 切片付きのリッジ回帰を正規方程式 `(XᵀX + λI) β = Xᵀy` で解きます。
+///
+/// `features`は`n × num_features`、`responses`は`n × num_targets`の行優先の行列です。
+/// `(num_features + 1) × num_targets`の係数を行優先で返し、最後の行が切片です。切片には
+/// 正則化をかけず、`λ`は特徴量の2乗和の平均の`ridge`倍とします。
+///
+/// 消去の途中のピボットが丸め誤差の範囲 (係数行列の最大要素に対する相対値) に収まり、
+/// 正規方程式が特異とみなせる場合は`None`を返します。
+fn ridge_regression(
+    features: &[f64],
+    num_features: usize,
+    responses: &[f64],
+    num_targets: usize,
+    ridge: f64,
+) -> Option<Vec<f64>> {
+    if num_targets == 0 {
+        return Some(Vec::new());
+    }
+    let size = num_features + 1;
+    let num_samples = responses.len() / num_targets;
+    let column = |sample: usize, i: usize| {
+        if i < num_features {
+            features[sample * num_features + i]
+        } else {
+            1.0
+        }
+    };
+
+    let mut gram = vec![0.0; size * size];
+    let mut rhs = vec![0.0; size * num_targets];
+    for sample in 0..num_samples {
+        for i in 0..size {
+            let x_i = column(sample, i);
+            if x_i == 0.0 {
+                continue;
+            }
+            for j in 0..size {
+                gram[i * size + j] += x_i * column(sample, j);
+            }
+            for target in 0..num_targets {
+                rhs[i * num_targets + target] += x_i * responses[sample * num_targets + target];
+            }
+        }
+    }
+    let mean_square =
+        (0..num_features).map(|i| gram[i * size + i]).sum::<f64>() / num_features.max(1) as f64;
+    let penalty = ridge * mean_square.max(f64::EPSILON);
+    for i in 0..num_features {
+        gram[i * size + i] += penalty;
+    }
+
+    let tolerance = f64::EPSILON * size as f64 * gram.iter().fold(0.0, |max, x| x.abs().max(max));
+
+    // 部分ピボット選択付きのガウス・ジョルダン法
+    for pivot_column in 0..size {
+        let pivot_row = (pivot_column..size)
+            .max_by(|&a, &b| {
+                let a = gram[a * size + pivot_column].abs();
+                a.total_cmp(&gram[b * size + pivot_column].abs())
+            })
+            .unwrap();
+        for j in 0..size {
+            gram.swap(pivot_column * size + j, pivot_row * size + j);
+        }
+        for target in 0..num_targets {
+            rhs.swap(pivot_column * num_targets + target, pivot_row * num_targets + target);
+        }
+        let diagonal = gram[pivot_column * size + pivot_column];
+        if diagonal.abs() <= tolerance {
+            return None;
+        }
+        for row in 0..size {
+            let factor = gram[row * size + pivot_column] / diagonal;
+            if row == pivot_column || factor == 0.0 {
+                continue;
+            }
+            for j in pivot_column..size {
+                let value = factor * gram[pivot_column * size + j];
+                gram[row * size + j] -= value;
+            }
+            for target in 0..num_targets {
+                let value = factor * rhs[pivot_column * num_targets + target];
+                rhs[row * num_targets + target] -= value;
+            }
+        }
+    }
+    for i in 0..size {
+        let diagonal = gram[i * size + i];
+        for target in 0..num_targets {
+            rhs[i * num_targets + target] /= diagonal;
+        }
     }
+    Some(rhs)
 }
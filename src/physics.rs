@@ -2,80 +2,932 @@
 //!
 //! このモジュールは、PINNの核となるカスタム損失関数を定義します。
 
-use crate::constants::{model_dims, physics::*};
+use crate::constants::{bounds, model_dims, physics::*};
+use crate::infer::ForkDimensions;
 use burn::prelude::*;
-use burn::tensor::{activation::relu, Tensor};
+use burn::tensor::{activation::relu, Distribution, Tensor, TensorData};
+use std::str::FromStr;
 
-/// 音叉の物理法則と制約に基づいた損失を計算します。
+/// プロングの断面形状の種類。モデルが出力する寸法の数を決定します。
+#[derive(Config, Debug, Copy, PartialEq)]
+pub enum CrossSectionKind {
+    /// 円形断面 (デフォルト)。プロングの直径を1つの寸法として予測します。
+    Circular,
+    /// 矩形断面。プロングの幅と厚さを別々の寸法として予測します。
+    Rectangular,
+}
+
+impl CrossSectionKind {
+    /// この断面形状でモデルが出力する寸法の数を返します。
+    pub fn num_dims(&self) -> usize {
+        match self {
+            CrossSectionKind::Circular => model_dims::NUM_DIMS,
+            CrossSectionKind::Rectangular => model_dims::NUM_DIMS_RECTANGULAR,
+        }
+    }
+
+    /// ポアソン比 `nu` に対するせん断補正係数 `κ` (Cowperの式) を返します。
+    ///
+    /// 円形断面は `6(1 + ν) / (7 + 6ν)`、矩形断面は `10(1 + ν) / (12 + 11ν)` です。
+    pub fn shear_coefficient(&self, nu: f32) -> f32 {
+        match self {
+            CrossSectionKind::Circular => 6.0 * (1.0 + nu) / (7.0 + 6.0 * nu),
+            CrossSectionKind::Rectangular => 10.0 * (1.0 + nu) / (12.0 + 11.0 * nu),
+        }
+    }
+}
+
+impl FromStr for CrossSectionKind {
+    type Err = String;
+
+    /// `circular`、`rectangular` を解析します。
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_ascii_lowercase().as_str() {
+            "circular" => Ok(CrossSectionKind::Circular),
+            "rectangular" => Ok(CrossSectionKind::Rectangular),
+            _ => Err(format!(
+                "unknown cross section '{s}' (expected circular or rectangular)"
+            )),
+        }
+    }
+}
+
+/// プロングの断面。
 ///
-/// この関数は、ニューラルネットワークが予測した寸法から周波数を計算し、
-/// 目標周波数との誤差（損失）を算出します。
-/// さらに、物理的に不適切な寸法に対するペナルティを追加します。
+/// `T`には寸法の型 (`f32`または`[batch_size, 1]`形状のテンソル) を指定します。
+#[derive(Debug, Clone)]
+pub enum CrossSection<T> {
+    /// 円形断面。
+    Circular {
+        /// 直径 (m)。
+        diameter: T,
+    },
+    /// 矩形断面。
+    Rectangular {
+        /// 幅 (m)。振動方向に垂直な辺。
+        width: T,
+        /// 厚さ (m)。振動方向の辺。
+        height: T,
+    },
+}
+
+impl CrossSection<f32> {
+    /// 断面積 (m²)。円形は `π·D²/4`、矩形は `b·h`。
+    pub fn area(&self) -> f32 {
+        match self {
+            CrossSection::Circular { diameter } => diameter.powi(2) * std::f32::consts::PI / 4.0,
+            CrossSection::Rectangular { width, height } => width * height,
+        }
+    }
+
+    /// 振動方向の断面二次モーメント (m⁴)。円形は `π·D⁴/64`、矩形は `b·h³/12`。
+    pub fn moment_of_inertia(&self) -> f32 {
+        match self {
+            CrossSection::Circular { diameter } => {
+                diameter.powi(4) * std::f32::consts::PI / 64.0
+            }
+            CrossSection::Rectangular { width, height } => width * height.powi(3) / 12.0,
+        }
+    }
+}
+
+impl<B: Backend> CrossSection<Tensor<B, 2>> {
+    /// 寸法のテンソル (形状 `[batch_size, num_dims]`) から、指定された形状の断面を取り出します。
+    pub fn from_dims(dims: &Tensor<B, 2>, kind: CrossSectionKind) -> Self {
+        let column = |idx: usize| dims.clone().narrow(1, idx, 1);
+        match kind {
+            CrossSectionKind::Circular => CrossSection::Circular {
+                diameter: column(model_dims::PRONG_DIAMETER_IDX),
+            },
+            CrossSectionKind::Rectangular => CrossSection::Rectangular {
+                width: column(model_dims::PRONG_WIDTH_IDX),
+                height: column(model_dims::PRONG_HEIGHT_IDX),
+            },
+        }
+    }
+
+    /// 断面積 (m²) と振動方向の断面二次モーメント (m⁴) を計算します。
+    pub fn area_and_moment_of_inertia(self) -> (Tensor<B, 2>, Tensor<B, 2>) {
+        // バックエンドの要素型 (f32/f64) に合わせて変換されるよう、f64の定数を使う
+        let pi = std::f64::consts::PI;
+        match self {
+            CrossSection::Circular { diameter } => {
+                let d2 = diameter.powf_scalar(2.0);
+                let area = d2.clone() * (pi / 4.0);
+                let moment_of_inertia = d2.powf_scalar(2.0) * (pi / 64.0);
+                (area, moment_of_inertia)
+            }
+            CrossSection::Rectangular { width, height } => {
+                let area = width.clone() * height.clone();
+                let moment_of_inertia = width * height.powf_scalar(3.0) / 12.0;
+                (area, moment_of_inertia)
+            }
+        }
+    }
+}
+
+/// 音叉の物理モデルの設定。
+#[derive(Config, Debug)]
+pub struct PhysicsConfig {
+    /// プロング間隔と柄の質量による補正 (結合項) を適用するかどうか。
+    ///
+    /// `false`の場合は、プロングの長さと直径のみに依存する単純な片持ち梁モデルになります。
+    #[config(default = true)]
+    pub coupling: bool,
+    /// 音叉の材質。ヤング率と密度の値を決定します。
+    #[config(default = "Material::Steel304")]
+    pub material: Material,
+    /// プロングの断面形状。`Rectangular`の場合、寸法のテンソルは
+    /// `model_dims::NUM_DIMS_RECTANGULAR`列である必要があります。
+    #[config(default = "CrossSectionKind::Circular")]
+    pub cross_section: CrossSectionKind,
+    /// 曲げ振動モードの次数 (1〜3)。1が基本振動です。
+    #[config(default = 1)]
+    pub mode: u8,
+    /// 回転慣性とせん断変形によるTimoshenko補正を適用するかどうか。
+    ///
+    /// `false`の場合はEuler-Bernoulli梁として計算します。
+    #[config(default = false)]
+    pub timoshenko: bool,
+    /// プロングの先端と付け根の断面寸法の比 (線形テーパー)。
+    ///
+    /// 予測する直径 (矩形断面では幅と厚さ) は付け根の値とし、先端に向かって線形に
+    /// この比まで縮小するとして周波数を補正します ([`taper_factor`]を参照)。デフォルトの1.0は
+    /// 一様な梁です。
+    #[config(default = 1.0)]
+    pub taper_ratio: f32,
+    /// 音叉の温度 (℃)。ヤング率を[`Material::youngs_modulus_at`]で補正します。
+    ///
+    /// デフォルトは材料定数の基準温度 ([`REFERENCE_TEMPERATURE`]) で、補正はかかりません。
+    #[config(default = "REFERENCE_TEMPERATURE")]
+    pub temperature: f32,
+}
+
+impl PhysicsConfig {
+    /// 設定値の整合性を検証します。
+    ///
+    /// # Errors
+    ///
+    /// 振動モードの次数が対応範囲外、テーパー比が正の有限値でない、または温度が有限値でないか
+    /// ヤング率が正にならない温度の場合にエラーメッセージを返します。
+    pub fn validate(&self) -> Result<(), String> {
+        if mode_factor(self.mode).is_none() {
+            return Err(format!(
+                "mode must be between 1 and {}, got {}",
+                MODE_FACTORS.len(),
+                self.mode
+            ));
+        }
+        if !self.taper_ratio.is_finite() || self.taper_ratio <= 0.0 {
+            return Err(format!(
+                "taper_ratio must be a positive finite number, got {}",
+                self.taper_ratio
+            ));
+        }
+        if !self.temperature.is_finite() || self.youngs_modulus() <= 0.0 {
+            return Err(format!(
+                "temperature must be a finite number at which the Young's modulus stays positive, \
+                 got {}",
+                self.temperature
+            ));
+        }
+        Ok(())
+    }
+
+    /// 材質と温度から決まるヤング率 (Pa)。
+    pub fn youngs_modulus(&self) -> f32 {
+        self.material.youngs_modulus_at(self.temperature)
+    }
+}
+
+/// [`taper_factor`]の数値積分 (シンプソン則) の分割数 (偶数)。
+const TAPER_INTEGRATION_STEPS: usize = 64;
+
+/// 線形テーパーのプロングに対する、付け根の断面で計算した一様な梁の周波数からの補正係数を
+/// 返します。
+///
+/// 断面は付け根から先端に向かって相似に縮小し、寸法の比は `s(ξ) = 1 − (1 − taper_ratio)·ξ`
+/// (`ξ = x / L`、付け根が0) とします。断面二次モーメントは `s⁴`、断面積は `s²` に比例します。
+/// 一様な梁のモード形状 `φ` ([`MODE_SHAPE_SIGMAS`]を参照) を試行関数としたレイリー商
+///
+/// `ω² = ∫E·I(x)·φ''² dx / ∫ρ·A(x)·φ² dx`
+///
+/// を数値積分で近似し、一様な梁との比
+///
+/// `sqrt((∫s⁴·φ''² dξ / ∫φ''² dξ) / (∫s²·φ² dξ / ∫φ² dξ))`
+///
+/// を返します。曲げ剛性は付け根付近、質量は先端付近の寄与が大きいため、先端が細いほど
+/// 周波数は高くなります。`taper_ratio`が1の場合は1です。
+///
+/// # Panics
+///
+/// `mode`が対応範囲外の場合にパニックします ([`PhysicsConfig::validate`]を参照)。
+pub fn taper_factor(taper_ratio: f32, mode: u8) -> f32 {
+    let k_factor =
+        mode_factor(mode).unwrap_or_else(|| panic!("unsupported vibration mode: {mode}"));
+    if taper_ratio == 1.0 {
+        return 1.0;
+    }
+    let beta = f64::from(k_factor).sqrt();
+    let sigma = f64::from(MODE_SHAPE_SIGMAS[usize::from(mode - 1)]);
+    let taper = 1.0 - f64::from(taper_ratio);
+
+    // シンプソン則の刻み幅は比で打ち消し合うため、重みだけを掛けて合計する
+    let (mut stiffness, mut uniform_stiffness, mut mass, mut uniform_mass) = (0.0, 0.0, 0.0, 0.0);
+    for i in 0..=TAPER_INTEGRATION_STEPS {
+        let weight = match i {
+            0 | TAPER_INTEGRATION_STEPS => 1.0,
+            _ if i % 2 == 1 => 4.0,
+            _ => 2.0,
+        };
+        let xi = i as f64 / TAPER_INTEGRATION_STEPS as f64;
+        let bx = beta * xi;
+        let shape = bx.cosh() - bx.cos() - sigma * (bx.sinh() - bx.sin());
+        let curvature = bx.cosh() + bx.cos() - sigma * (bx.sinh() + bx.sin());
+        let scale_sq = (1.0 - taper * xi).powi(2);
+
+        uniform_stiffness += weight * curvature.powi(2);
+        stiffness += weight * scale_sq.powi(2) * curvature.powi(2);
+        uniform_mass += weight * shape.powi(2);
+        mass += weight * scale_sq * shape.powi(2);
+    }
+    ((stiffness / uniform_stiffness) / (mass / uniform_mass)).sqrt() as f32
+}
+
+/// 線形テーパーのプロングの平均断面積の、付け根の断面積に対する比 `(1 + r + r²) / 3`。
+fn taper_mass_ratio(taper_ratio: f32) -> f32 {
+    (1.0 + taper_ratio + taper_ratio.powi(2)) / 3.0
+}
+
+/// 予測された寸法から、指定された材質とデフォルトの物理モデルで各行の音叉の基本周波数を計算します。
+///
+/// # Arguments
+///
+/// * `dims` - 音叉の寸法のテンソル。形状は `[batch_size, 5]` (円形断面)。
+/// * `material` - 音叉の材質。
+///
+/// # Returns
+///
+/// 各行に対する周波数 (Hz) のテンソル。形状は `[batch_size]`。
+pub fn predicted_frequency<B: Backend>(dims: Tensor<B, 2>, material: Material) -> Tensor<B, 1> {
+    predicted_frequency_with_config(dims, &PhysicsConfig::new().with_material(material))
+}
+
+/// 予測された寸法から、指定された物理モデルで各行の音叉の基本周波数を計算します。
+///
+/// 基本となるのは片持ち梁の基本振動数の公式です。
+///
+/// `f₀ = K / (2π L²) · sqrt(E·I / (ρ·A))`
+///
+/// 係数 `K` は`PhysicsConfig::mode`で選択した振動モードの値 ([`MODE_FACTORS`]) です。
+///
+/// `coupling`が有効な場合は、さらに次の2つの補正を加えます。
+///
+/// 1. **プロング間隔による有効長の延長**: 2本のプロングはU字部でつながっており、
+///    付け根のたわみによって振動する長さが延びます。有効長を
+///    `L_eff = L + GAP_LENGTH_FACTOR · gap` とします。
+/// 2. **柄の質量負荷**: 柄は理想的な固定端ではなく、プロングの振動により揺すられます。
+///    プロング1本の質量を `m_p = ρ·A·L`、柄の質量を `m_h = ρ·π·D_h²/4·L_h` として、
+///    周波数に `sqrt(m_h / (m_h + MODAL_MASS_RATIO · 2·m_p))` を乗じます。
+///    柄が重いほど理想的な固定端に近づき、係数は1に近づきます。
+///
+/// `timoshenko`が有効な場合は、太く短いプロングで無視できなくなる回転慣性とせん断変形の
+/// 影響 (Timoshenko梁理論) を近似的に考慮し、周波数に次の係数を乗じます。
+///
+/// `1 / sqrt(1 + K · (r / L)² · (1 + E / (κ·G)))`
+///
+/// ここで `r² = I / A` は断面二次半径の二乗、`E / G = 2(1 + ν)` はポアソン比 `ν` から求め、
+/// `κ` は断面形状ごとのせん断補正係数 ([`CrossSectionKind::shear_coefficient`]) です。
+/// 細長いプロング (`r / L → 0`) では係数は1に近づき、Euler-Bernoulli梁と一致します。
+///
+/// `taper_ratio`が1以外の場合は、先端に向かって細くなるプロングとして[`taper_factor`]を乗じ、
+/// 柄の質量負荷のプロングの質量にも平均断面積の比 `(1 + r + r²) / 3` を乗じます。断面積と
+/// 断面二次モーメント (Timoshenko補正の断面二次半径を含む) は付け根の断面の値です。
+///
+/// ヤング率 `E` と密度 `ρ` は`PhysicsConfig::material`から (`E`は`PhysicsConfig::temperature`の
+/// 温度での値)、断面積 `A` と断面二次モーメント `I` は`PhysicsConfig::cross_section`に応じた
+/// [`CrossSection`]の公式から計算します。
+/// いずれも材料は一様で、柄とプロングは同じ材料の円柱であると仮定した簡略化モデルです。
+///
+/// # Panics
+///
+/// `config.mode`が対応範囲外の場合にパニックします ([`PhysicsConfig::validate`]を参照)。
+pub fn predicted_frequency_with_config<B: Backend>(
+    dims: Tensor<B, 2>,
+    config: &PhysicsConfig,
+) -> Tensor<B, 1> {
+    // スカラーはバックエンドの要素型 (f32/f64) に変換されるため、定数はf64で計算する
+    let pi = std::f64::consts::PI;
+    let epsilon = 1e-8;
+    let [batch_size, _] = dims.dims();
+    let k_factor = mode_factor(config.mode)
+        .unwrap_or_else(|| panic!("unsupported vibration mode: {}", config.mode));
+
+    let column = |idx: usize| dims.clone().narrow(1, idx, 1);
+    let prong_length = column(model_dims::PRONG_LENGTH_IDX);
+    let (area, moment_of_inertia) =
+        CrossSection::from_dims(&dims, config.cross_section).area_and_moment_of_inertia();
+
+    // 回転慣性・せん断補正で使用する断面二次半径の二乗 r² = I / A
+    let gyration_sq = config
+        .timoshenko
+        .then(|| moment_of_inertia.clone() / (area.clone() + epsilon));
+
+    let density = config.material.density();
+    let stiffness = moment_of_inertia * config.youngs_modulus();
+    let density_mass = area * density;
+
+    let sqrt_term = (stiffness / (density_mass.clone() + epsilon)).sqrt();
+
+    // 1. プロング間隔による有効長の延長
+    let length = if config.coupling {
+        let prong_gap = column(model_dims::PRONG_GAP_IDX);
+        prong_length.clone() + prong_gap * GAP_LENGTH_FACTOR
+    } else {
+        prong_length.clone()
+    };
+    let length_term = length.powf_scalar(2.0);
+    let mut freqs = sqrt_term.mul_scalar(f64::from(k_factor) / (2.0 * pi)) / length_term.clone();
+
+    // 2. Timoshenko補正
+    if let Some(gyration_sq) = gyration_sq {
+        let nu = config.material.poisson_ratio();
+        let shear_term = 1.0 + 2.0 * (1.0 + nu) / config.cross_section.shear_coefficient(nu);
+        let correction = (gyration_sq / length_term * (k_factor * shear_term) + 1.0)
+            .sqrt()
+            .recip();
+        freqs = freqs * correction;
+    }
+
+    // 3. テーパーによる補正
+    if config.taper_ratio != 1.0 {
+        freqs = freqs.mul_scalar(taper_factor(config.taper_ratio, config.mode));
+    }
+
+    if !config.coupling {
+        return freqs.reshape([batch_size]);
+    }
+
+    // 4. 柄の質量負荷
+    let handle_length = column(model_dims::HANDLE_LENGTH_IDX);
+    let handle_diameter = column(model_dims::HANDLE_DIAMETER_IDX);
+    let prong_mass = density_mass * prong_length * taper_mass_ratio(config.taper_ratio);
+    let handle_mass =
+        handle_diameter.powf_scalar(2.0) * handle_length * (f64::from(density) * pi / 4.0);
+    let loaded_mass = handle_mass.clone() + prong_mass * (2.0 * MODAL_MASS_RATIO) + epsilon;
+    let mass_factor = (handle_mass / loaded_mass).sqrt();
+
+    (freqs * mass_factor).reshape([batch_size])
+}
+
+/// 予測された寸法から、材料の内部摩擦とアンカー損失を合わせた音叉のQ値を計算します。
+///
+/// `1 / Q = η + 1 / Q_anchor`、`Q_anchor = ANCHOR_LOSS_COEFFICIENT · (L / t)³`
+///
+/// `η`は`PhysicsConfig::material`の損失係数 ([`Material::loss_factor`])、`L`はプロングの長さ、
+/// `t`は振動方向の厚さ (円形断面では直径、矩形断面では厚さ) です。太く短いプロングほど
+/// 付け根から支持部へ逃げるエネルギーが増えてQ値が下がり、細長いプロングでは材料の
+/// `1 / η`に近づきます。空気による減衰は考慮しません。
+///
+/// # Returns
+///
+/// 各行に対するQ値のテンソル。形状は `[batch_size]`。
+pub fn q_factor_from_dims<B: Backend>(dims: Tensor<B, 2>, config: &PhysicsConfig) -> Tensor<B, 1> {
+    let [batch_size, _] = dims.dims();
+    let thickness_idx = match config.cross_section {
+        CrossSectionKind::Circular => model_dims::PRONG_DIAMETER_IDX,
+        CrossSectionKind::Rectangular => model_dims::PRONG_HEIGHT_IDX,
+    };
+    let prong_length = dims.clone().narrow(1, model_dims::PRONG_LENGTH_IDX, 1);
+    let thickness = dims.narrow(1, thickness_idx, 1);
+
+    let anchor_q = (prong_length / thickness).powf_scalar(3.0) * ANCHOR_LOSS_COEFFICIENT;
+    let inverse_q = anchor_q.recip() + config.material.loss_factor();
+    inverse_q.recip().reshape([batch_size])
+}
+
+/// モデルが寸法の後に出力した`log10(Q)`と、予測寸法から[`q_factor_from_dims`]で計算した
+/// Q値の常用対数との二乗誤差を要素ごとに返します。
+///
+/// Q値の出力を寸法に合わせて学習するための項のため、寸法から計算した目標値は勾配を
+/// 止め (`detach`)、寸法の予測には影響させません。
+fn q_factor_loss<B: Backend>(
+    predicted_dims: Tensor<B, 2>,
+    physics: &PhysicsConfig,
+) -> Tensor<B, 1> {
+    let [batch_size, _] = predicted_dims.dims();
+    let predicted_log_q = predicted_dims
+        .clone()
+        .narrow(1, physics.cross_section.num_dims(), 1)
+        .reshape([batch_size]);
+    let target_log_q =
+        (q_factor_from_dims(predicted_dims, physics).log() / std::f64::consts::LN_10).detach();
+    (predicted_log_q - target_log_q).powf_scalar(2.0)
+}
+
+/// 5つの寸法から、指定された材質と振動モードで音叉の周波数 (Hz) を計算します。
+///
+/// テンソルやバックエンドを使わない`f32`のみの計算で、それ以外の設定はデフォルトの
+/// [`PhysicsConfig`]を使用します。結果は[`predicted_frequency_with_config`]と一致します。
+///
+/// # Panics
+///
+/// `mode`が対応範囲外の場合にパニックします。
+pub fn frequency_from_dims(dims: &ForkDimensions, material: Material, mode: u8) -> f32 {
+    let config = PhysicsConfig::new().with_material(material).with_mode(mode);
+    frequency_from_dims_with_config(dims, &config)
+}
+
+/// 5つの寸法から、指定された物理モデルで音叉の周波数 (Hz) を計算します。
+///
+/// 計算式は[`predicted_frequency_with_config`]と同じです。[`ForkDimensions`]は円形断面の
+/// 寸法のみを持つため、`config.cross_section`に関わらず円形断面として計算します。
+///
+/// # Panics
+///
+/// `config.mode`が対応範囲外の場合にパニックします ([`PhysicsConfig::validate`]を参照)。
+pub fn frequency_from_dims_with_config(dims: &ForkDimensions, config: &PhysicsConfig) -> f32 {
+    let pi = std::f32::consts::PI;
+    let epsilon = 1e-8;
+    let k_factor = mode_factor(config.mode)
+        .unwrap_or_else(|| panic!("unsupported vibration mode: {}", config.mode));
+
+    let section = CrossSection::Circular {
+        diameter: dims.prong_diameter,
+    };
+    let (area, moment_of_inertia) = (section.area(), section.moment_of_inertia());
+
+    let density = config.material.density();
+    let stiffness = moment_of_inertia * config.youngs_modulus();
+    let density_mass = area * density;
+    let sqrt_term = (stiffness / (density_mass + epsilon)).sqrt();
+
+    // 1. プロング間隔による有効長の延長
+    let length = if config.coupling {
+        dims.prong_length + dims.prong_gap * GAP_LENGTH_FACTOR
+    } else {
+        dims.prong_length
+    };
+    let length_term = length.powi(2);
+    let mut freq = sqrt_term * k_factor / (2.0 * pi) / length_term;
+
+    // 2. Timoshenko補正
+    if config.timoshenko {
+        let nu = config.material.poisson_ratio();
+        let gyration_sq = moment_of_inertia / (area + epsilon);
+        let kappa = CrossSectionKind::Circular.shear_coefficient(nu);
+        let shear_term = 1.0 + 2.0 * (1.0 + nu) / kappa;
+        freq /= (1.0 + gyration_sq / length_term * k_factor * shear_term).sqrt();
+    }
+
+    // 3. テーパーによる補正
+    freq *= taper_factor(config.taper_ratio, config.mode);
+
+    if !config.coupling {
+        return freq;
+    }
+
+    // 4. 柄の質量負荷
+    let prong_mass = density_mass * dims.prong_length * taper_mass_ratio(config.taper_ratio);
+    let handle_mass = dims.handle_diameter.powi(2) * dims.handle_length * (density * pi / 4.0);
+    let loaded_mass = handle_mass + prong_mass * (2.0 * MODAL_MASS_RATIO) + epsilon;
+    freq * (handle_mass / loaded_mass).sqrt()
+}
+
+/// [`analytic_dimensions`]で有効長を補正する回数。
+const ANALYTIC_REFINEMENT_STEPS: usize = 4;
+
+/// 目標周波数 `freq` (Hz) に対する寸法の大まかな解析解を返します。
+///
+/// プロングの長さ以外の寸法を製造可能な範囲の中央に固定し、一様な円形断面の片持ち梁の式
+/// `f = k / (2π L²) · (D / 4) · √(E / ρ)` をプロングの有効長 `L` について解きます。
+/// その後、柄の質量負荷などを含む[`frequency_from_dims_with_config`]の周波数が`freq`に
+/// 近づくよう、`f ∝ 1 / L²`の関係で有効長を数回補正します。プロングの長さは製造可能な範囲に
+/// 収めるため、範囲の端では`freq`に一致しません。
+///
+/// モデルの初期値 ([`crate::train::TrainingConfig::warm_start`]) など、目安として使用します。
+///
+/// # Panics
+///
+/// `config.mode`が対応範囲外の場合にパニックします ([`PhysicsConfig::validate`]を参照)。
+pub fn analytic_dimensions(freq: f32, config: &PhysicsConfig) -> ForkDimensions {
+    let pi = std::f32::consts::PI;
+    let k_factor = mode_factor(config.mode)
+        .unwrap_or_else(|| panic!("unsupported vibration mode: {}", config.mode));
+    let midpoint = |min: f32, max: f32| (min + max) / 2.0;
+    let prong_diameter = midpoint(bounds::PRONG_DIAMETER_MIN, bounds::PRONG_DIAMETER_MAX);
+    let prong_gap = midpoint(bounds::PRONG_GAP_MIN, bounds::PRONG_GAP_MAX);
+
+    let wave_speed = (config.youngs_modulus() / config.material.density()).sqrt();
+    let gap_extension = if config.coupling {
+        prong_gap * GAP_LENGTH_FACTOR
+    } else {
+        0.0
+    };
+    let mut effective_length = (k_factor * prong_diameter * wave_speed / (8.0 * pi * freq)).sqrt();
+
+    let mut dims = ForkDimensions {
+        handle_length: midpoint(bounds::HANDLE_LENGTH_MIN, bounds::HANDLE_LENGTH_MAX),
+        handle_diameter: midpoint(bounds::HANDLE_DIAMETER_MIN, bounds::HANDLE_DIAMETER_MAX),
+        prong_length: 0.0,
+        prong_diameter,
+        prong_gap,
+    };
+    for _ in 0..ANALYTIC_REFINEMENT_STEPS {
+        dims.prong_length = (effective_length - gap_extension)
+            .clamp(bounds::PRONG_LENGTH_MIN, bounds::PRONG_LENGTH_MAX);
+        let predicted = frequency_from_dims_with_config(&dims, config);
+        effective_length = (dims.prong_length + gap_extension) * (predicted / freq).sqrt();
+    }
+    dims.prong_length = (effective_length - gap_extension)
+        .clamp(bounds::PRONG_LENGTH_MIN, bounds::PRONG_LENGTH_MAX);
+    dims
+}
+
+/// 周波数の残差 `r` に適用する損失関数の種類。
+#[derive(Config, Debug, PartialEq)]
+pub enum FrequencyLossKind {
+    /// 二乗誤差 `r²` (デフォルト)。
+    Squared,
+    /// Huber損失。`|r| <= delta` では `r² / 2`、それを超えると `delta · (|r| - delta / 2)`。
+    ///
+    /// 大きな残差の寄与が線形になるため、周波数範囲の端で生じる勾配のスパイクを抑えられます。
+    Huber {
+        /// 二乗と線形を切り替える閾値 (残差と同じ単位)。
+        delta: f32,
+    },
+    /// 相対誤差の絶対値 `|f_predicted - f_target| / f_target`。
+    ///
+    /// `LossConfig::normalize`に関わらず、常に目標周波数で正規化した残差を使用します。
+    AbsRelative,
+}
+
+impl FrequencyLossKind {
+    /// 残差のテンソルに損失関数を要素ごとに適用します。
+    pub fn apply<B: Backend>(&self, residual: Tensor<B, 2>) -> Tensor<B, 2> {
+        match self {
+            FrequencyLossKind::Squared => residual.powf_scalar(2.0),
+            FrequencyLossKind::Huber { delta } => {
+                // |r|を閾値以下の部分と超過部分に分け、前者は二乗、後者は線形に扱う
+                let abs = residual.abs();
+                let quadratic = abs.clone().clamp_max(*delta);
+                let linear = abs - quadratic.clone();
+                quadratic.powf_scalar(2.0) * 0.5 + linear * *delta
+            }
+            FrequencyLossKind::AbsRelative => residual.abs(),
+        }
+    }
+}
+
+impl FromStr for FrequencyLossKind {
+    type Err = String;
+
+    /// `squared`、`huber:<delta>`、`abs-relative` の形式を解析します。
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.split(':').collect::<Vec<_>>().as_slice() {
+            ["squared"] => Ok(FrequencyLossKind::Squared),
+            ["huber", delta] => Ok(FrequencyLossKind::Huber {
+                delta: delta
+                    .parse()
+                    .map_err(|_| format!("invalid delta '{delta}' in frequency loss '{s}'"))?,
+            }),
+            ["abs-relative"] => Ok(FrequencyLossKind::AbsRelative),
+            _ => Err(format!(
+                "unknown frequency loss '{s}'. Use squared, huber:<delta> or abs-relative"
+            )),
+        }
+    }
+}
+
+/// 損失関数の設定 (ペナルティの重みと周波数損失の正規化)。
+#[derive(Config, Debug)]
+pub struct LossConfig {
+    /// 周波数損失を目標周波数で正規化するかどうか。
+    ///
+    /// `true`の場合は相対誤差 `((f_predicted - f_target) / f_target)^2` を、
+    /// `false`の場合は従来の `(f_predicted - f_target)^2` (Hz²) を使用します。
+    /// 正規化しないと100Hzの誤差が10,000になり、ペナルティ項が実質的に無視されるため、
+    /// デフォルトでは正規化します。
+    #[config(default = true)]
+    pub normalize: bool,
+    /// 周波数の残差に適用する損失関数。
+    #[config(default = "FrequencyLossKind::Squared")]
+    pub frequency_loss: FrequencyLossKind,
+    /// 周波数の計算に使用する物理モデル。
+    #[config(default = "PhysicsConfig::new()")]
+    pub physics: PhysicsConfig,
+    /// `ratio_penalty`（プロング長 > 柄長）に対する重み。
+    #[config(default = "PENALTY_WEIGHT_RATIO")]
+    pub penalty_weight_ratio: f32,
+    /// `range_penalty`（プロングの直径・長さの範囲）に対する重み。
+    #[config(default = "PENALTY_WEIGHT_RANGE")]
+    pub penalty_weight_range: f32,
+    /// その他の寸法の範囲ペナルティに対する重み。
+    #[config(default = "PENALTY_WEIGHT_OTHER")]
+    pub penalty_weight_other: f32,
+    /// 寸法ごとの範囲ペナルティに対する重み (`model_dims`のインデックス順)。
+    ///
+    /// `range_penalty`・`other_penalty`に集計する前に、各寸法のペナルティに掛けます。
+    /// 例えば柄の長さと直径を0にすると、それらの範囲ペナルティは損失に寄与しません。
+    /// 矩形断面のプロングの厚さには、プロングの直径と同じ重みを使用します。
+    #[config(default = "[1.0; model_dims::NUM_DIMS]")]
+    pub dimension_weights: [f32; model_dims::NUM_DIMS],
+    /// 自己一貫性項 ([`consistency_penalty`]) に対する重み。0.0の場合は計算しません。
+    #[config(default = 0.0)]
+    pub consistency_weight: f32,
+    /// 自己一貫性項で目標周波数をずらす最大の割合 (例: 0.01で±1%)。
+    #[config(default = 0.01)]
+    pub consistency_perturbation: f32,
+    /// 倍音 (1つ上の振動モード) の周波数損失に対する重み。0.0の場合は計算しません。
+    ///
+    /// 同じ寸法から`physics.mode + 1`次のモードの周波数を計算し、目標周波数の
+    /// `overtone_ratio`倍との誤差を周波数損失と同じ方法で評価します。
+    #[config(default = 0.0)]
+    pub overtone_weight: f32,
+    /// 基本周波数に対する倍音の周波数の目標比。
+    ///
+    /// Euler-Bernoulli梁ではモード間の周波数比は寸法によらず一定 ([`OVERTONE_RATIO`]) のため、
+    /// 寸法で比を調整できるのは`physics.timoshenko`を有効にした場合のみです。
+    #[config(default = "OVERTONE_RATIO")]
+    pub overtone_ratio: f32,
+    /// Q値の損失に対する重み。0.0の場合は計算しません。
+    ///
+    /// 正の場合、予測寸法のテンソルは断面形状の寸法の後に`log10(Q)`の列
+    /// ([`model_dims::NUM_Q_FACTOR_OUTPUTS`]) を持つ必要があり、その列と寸法から計算した
+    /// Q値 ([`q_factor_from_dims`]) の常用対数との二乗誤差を損失に加えます。
+    #[config(default = 0.0)]
+    pub q_factor_weight: f32,
+}
+
+/// 損失関数を構成する各項。
+///
+/// いずれの項もバッチ平均済みのスカラー (形状 `[1]`) で、ペナルティの重みは適用前です。
+#[derive(Debug, Clone)]
+pub struct LossComponents<B: Backend> {
+    /// 周波数損失。`LossConfig::normalize`と`LossConfig::frequency_loss`に応じて計算されます。
+    pub frequency_loss: Tensor<B, 1>,
+    /// プロング長が柄の長さを超えることに対するペナルティ。
+    pub ratio_penalty: Tensor<B, 1>,
+    /// プロングの直径・長さの範囲に対するペナルティ。
+    pub range_penalty: Tensor<B, 1>,
+    /// その他の寸法 (柄の長さ・直径、プロング間隔) の範囲に対するペナルティ。
+    pub other_penalty: Tensor<B, 1>,
+    /// 近傍の周波数に対する予測寸法の不連続さに対するペナルティ ([`consistency_penalty`])。
+    /// 計算しない場合は0です。
+    pub consistency_penalty: Tensor<B, 1>,
+    /// 倍音の周波数損失 ([`LossConfig::overtone_weight`]を参照)。計算しない場合は0です。
+    pub overtone_loss: Tensor<B, 1>,
+    /// Q値の損失 ([`LossConfig::q_factor_weight`]を参照)。計算しない場合は0です。
+    pub q_factor_loss: Tensor<B, 1>,
+}
+
+impl<B: Backend> LossComponents<B> {
+    /// ペナルティの重みを適用して各項を合計し、最終的な損失を返します。
+    pub fn total(self, config: &LossConfig) -> Tensor<B, 1> {
+        self.frequency_loss
+            + self.ratio_penalty * config.penalty_weight_ratio
+            + self.range_penalty * config.penalty_weight_range
+            + self.other_penalty * config.penalty_weight_other
+            + self.consistency_penalty * config.consistency_weight
+            + self.overtone_loss * config.overtone_weight
+            + self.q_factor_loss * config.q_factor_weight
+    }
+}
+
 /// 音叉の物理法則と制約に基づいた損失を計算します。
 ///
 /// この関数は、ニューラルネットワークが予測した寸法から周波数を計算し、
 /// 目標周波数との誤差（損失）を算出します。
 /// さらに、物理的に不適切な寸法に対するペナルティを追加します。
+/// 材質以外の設定にはデフォルトの[`LossConfig`]を使用します。
+/// 各項の内訳は[`tuning_fork_loss_components`]で取得できます。
+pub fn tuning_fork_loss<B: Backend>(
+    predicted_dims: Tensor<B, 2>,
+    target_freqs: Tensor<B, 2>,
+    material: Material,
+) -> Tensor<B, 1> {
+    let config = LossConfig::new().with_physics(PhysicsConfig::new().with_material(material));
+    tuning_fork_loss_with_config(predicted_dims, target_freqs, &config)
+}
+
+/// 指定された[`LossConfig`]の重みで損失を計算します。
+pub fn tuning_fork_loss_with_config<B: Backend>(
+    predicted_dims: Tensor<B, 2>,
+    target_freqs: Tensor<B, 2>,
+    config: &LossConfig,
+) -> Tensor<B, 1> {
+    tuning_fork_loss_components(predicted_dims, target_freqs, config).total(config)
+}
+
+/// 損失関数を構成する各項を個別に計算します。
 ///
 /// # Note
 ///
 /// 計算効率を向上させるため、中間テンソルの生成と`.clone()`の呼び出しを
-/// 最小限に抑えるように最適化されています。
-pub fn tuning_fork_loss<B: Backend>(
+/// 最小限に抑えるように最適化されています。範囲のペナルティは1項あたり3回の演算
+/// (クランプ・減算・二乗) で計算します ([`out_of_range_penalty`]を参照)。
+pub fn tuning_fork_loss_components<B: Backend>(
     predicted_dims: Tensor<B, 2>,
     target_freqs: Tensor<B, 2>,
-) -> Tensor<B, 1> {
-    let pi = std::f32::consts::PI;
-    let epsilon = 1e-8;
+    config: &LossConfig,
+) -> LossComponents<B> {
+    let device = predicted_dims.device();
 
-    // --- 各次元のテンソルへの参照を取得 ---
-    let dim_tensors = predicted_dims.split(1, 1);
-    let handle_length = &dim_tensors[model_dims::HANDLE_LENGTH_IDX];
-    let handle_diameter = &dim_tensors[model_dims::HANDLE_DIAMETER_IDX];
-    let prong_length = &dim_tensors[model_dims::PRONG_LENGTH_IDX];
-    let prong_diameter = &dim_tensors[model_dims::PRONG_DIAMETER_IDX];
-    let prong_gap = &dim_tensors[model_dims::PRONG_GAP_IDX];
+    // --- 1. 周波数損失の計算 ---
+    // 倍音は同じ寸法から1つ上のモードで計算し、目標周波数の`overtone_ratio`倍と比較する
+    let overtone_loss = if config.overtone_weight > 0.0 {
+        let physics = config.physics.clone().with_mode(config.physics.mode + 1);
+        frequency_loss_for_mode(
+            predicted_dims.clone(),
+            target_freqs.clone() * config.overtone_ratio,
+            &physics,
+            config,
+        )
+        .mean()
+    } else {
+        Tensor::zeros([1], &device)
+    };
+    let frequency_loss =
+        frequency_loss_for_mode(predicted_dims.clone(), target_freqs, &config.physics, config);
+    let q_factor_loss = if config.q_factor_weight > 0.0 {
+        q_factor_loss(predicted_dims.clone(), &config.physics).mean()
+    } else {
+        Tensor::zeros([1], &device)
+    };
 
-    // --- 1. 周波数損失の計算 (中間テンソルの削減) ---
-    let prong_d2 = prong_diameter.clone().powf_scalar(2.0);
-    let area = prong_d2.clone() * (pi / 4.0);
-    let moment_of_inertia = prong_d2.powf_scalar(2.0) * (pi / 64.0);
+    // --- 各次元のテンソルを所有権ごと取り出す ---
+    // 参照から`.clone()`すると元の列が共有されたままになり、後続の演算がバッファを
+    // 再利用できないため、各列は一度だけ取り出して消費する
+    let mut columns: Vec<Option<Tensor<B, 2>>> =
+        predicted_dims.split(1, 1).into_iter().map(Some).collect();
+    let mut take = |idx: usize| columns[idx].take().expect("each column is taken once");
+    let handle_length = take(model_dims::HANDLE_LENGTH_IDX);
+    let handle_diameter = take(model_dims::HANDLE_DIAMETER_IDX);
+    let prong_length = take(model_dims::PRONG_LENGTH_IDX);
+    let prong_diameter = take(model_dims::PRONG_DIAMETER_IDX);
+    let prong_gap = take(model_dims::PRONG_GAP_IDX);
 
-    let stiffness = moment_of_inertia * YOUNGS_MODULUS;
-    let density_mass = area * DENSITY;
+    // --- 2. 物理的制約に対するペナルティの計算 ---
+    // 柄とプロングの長さは比のペナルティと範囲のペナルティの両方で使うため、ここでのみ複製する
+    let ratio_penalty = relu(prong_length.clone() - handle_length.clone()).powf_scalar(2.0);
 
-    let sqrt_term = (stiffness / (density_mass + epsilon)).sqrt();
-    let length_term = prong_length.clone().powf_scalar(2.0);
+    let prong_diameter_penalty = out_of_range_penalty(
+        prong_diameter,
+        bounds::PRONG_DIAMETER_MIN,
+        bounds::PRONG_DIAMETER_MAX,
+    );
+    let prong_length_penalty = out_of_range_penalty(
+        prong_length,
+        bounds::PRONG_LENGTH_MIN,
+        bounds::PRONG_LENGTH_MAX,
+    );
+    let handle_length_penalty = out_of_range_penalty(
+        handle_length,
+        bounds::HANDLE_LENGTH_MIN,
+        bounds::HANDLE_LENGTH_MAX,
+    );
+    let handle_diameter_penalty = out_of_range_penalty(
+        handle_diameter,
+        bounds::HANDLE_DIAMETER_MIN,
+        bounds::HANDLE_DIAMETER_MAX,
+    );
+    let prong_gap_penalty = out_of_range_penalty(
+        prong_gap,
+        bounds::PRONG_GAP_MIN,
+        bounds::PRONG_GAP_MAX,
+    );
 
-    let predicted_freqs = sqrt_term.mul_scalar(K_FACTOR / (2.0 * pi)) / length_term;
-    let frequency_loss = (predicted_freqs - target_freqs).powf_scalar(2.0);
+    // 矩形断面では、厚さにも直径と同じ範囲を適用する
+    let prong_diameter_penalty = match config.physics.cross_section {
+        CrossSectionKind::Circular => prong_diameter_penalty,
+        CrossSectionKind::Rectangular => {
+            let prong_height = take(model_dims::PRONG_HEIGHT_IDX);
+            prong_diameter_penalty
+                + out_of_range_penalty(
+                    prong_height,
+                    bounds::PRONG_HEIGHT_MIN,
+                    bounds::PRONG_HEIGHT_MAX,
+                )
+        }
+    };
 
-    // --- 2. 物理的制約に対するペナルティの計算 ---
-    let ratio_penalty = relu(prong_length.clone() - handle_length.clone()).powf_scalar(2.0);
+    // 寸法ごとの重み (既定の1.0では演算を追加しない)
+    let weighted = |penalty: Tensor<B, 2>, idx: usize| {
+        let weight = config.dimension_weights[idx];
+        if weight == 1.0 { penalty } else { penalty * weight }
+    };
+    let handle_length_penalty = weighted(handle_length_penalty, model_dims::HANDLE_LENGTH_IDX);
+    let handle_diameter_penalty =
+        weighted(handle_diameter_penalty, model_dims::HANDLE_DIAMETER_IDX);
+    let prong_length_penalty = weighted(prong_length_penalty, model_dims::PRONG_LENGTH_IDX);
+    let prong_diameter_penalty = weighted(prong_diameter_penalty, model_dims::PRONG_DIAMETER_IDX);
+    let prong_gap_penalty = weighted(prong_gap_penalty, model_dims::PRONG_GAP_IDX);
 
-    let prong_diameter_penalty = relu(0.002 - prong_diameter.clone()).powf_scalar(2.0)
-        + relu(prong_diameter.clone() - 0.02).powf_scalar(2.0);
+    // --- 3. バッチ平均 ---
+    LossComponents {
+        frequency_loss: frequency_loss.mean(),
+        ratio_penalty: ratio_penalty.mean(),
+        range_penalty: (prong_diameter_penalty + prong_length_penalty).mean(),
+        other_penalty: (handle_length_penalty + handle_diameter_penalty + prong_gap_penalty)
+            .mean(),
+        // 自己一貫性項は別の周波数での予測が必要なため、呼び出し側で設定する
+        consistency_penalty: Tensor::zeros([1], &device),
+        overtone_loss,
+        q_factor_loss,
+    }
+}
 
-    let prong_length_penalty = relu(0.01 - prong_length.clone()).powf_scalar(2.0)
-        + relu(prong_length.clone() - 0.2).powf_scalar(2.0);
+/// 指定された物理モデル (振動モード) で寸法から計算した周波数と、目標周波数との誤差に
+/// [`LossConfig::frequency_loss`]を適用した損失を要素ごとに返します。
+fn frequency_loss_for_mode<B: Backend>(
+    predicted_dims: Tensor<B, 2>,
+    target_freqs: Tensor<B, 2>,
+    physics: &PhysicsConfig,
+    config: &LossConfig,
+) -> Tensor<B, 2> {
+    let predicted_freqs =
+        predicted_frequency_with_config(predicted_dims, physics).unsqueeze_dim::<2>(1);
+    let relative = config.normalize || config.frequency_loss == FrequencyLossKind::AbsRelative;
+    let residual = if relative {
+        // 目標周波数で割り、スケールに依存しない相対誤差にする
+        (predicted_freqs - target_freqs.clone()) / target_freqs
+    } else {
+        predicted_freqs - target_freqs
+    };
+    config.frequency_loss.apply(residual)
+}
 
-    let handle_length_penalty = relu(0.03 - handle_length.clone()).powf_scalar(2.0)
-        + relu(handle_length.clone() - 0.15).powf_scalar(2.0);
+/// 目標周波数を`[-perturbation, perturbation]`の割合で一様ランダムにずらした周波数を返します。
+///
+/// 自己一貫性項 ([`consistency_penalty`]) で使う近傍の周波数の生成に使用します。
+pub fn perturb_frequencies<B: Backend>(
+    frequencies: Tensor<B, 2>,
+    perturbation: f32,
+) -> Tensor<B, 2> {
+    let noise = Tensor::random(
+        frequencies.shape(),
+        Distribution::Uniform(-1.0, 1.0),
+        &frequencies.device(),
+    );
+    frequencies.clone() + frequencies * noise * perturbation
+}
 
-    let handle_diameter_penalty =
-        relu(0.005 - handle_diameter.clone()).powf_scalar(2.0)
-            + relu(handle_diameter.clone() - 0.02).powf_scalar(2.0);
+/// 近傍の周波数に対する予測寸法の不連続さに対するペナルティを計算します。
+///
+/// 周波数 `f` に対する予測寸法 `d` と、わずかにずらした周波数 `f'` に対する予測寸法 `d'` の
+/// 相対差 `((d' - d) / d)²` を寸法について合計し、バッチ平均を返します。周波数損失は
+/// 各周波数を個別に合わせるだけなので、この項を加えると周波数に対して寸法が滑らかに
+/// 変化する解が優先されます。寸法は周波数に応じて本来変化するため、重み
+/// ([`LossConfig::consistency_weight`]) は他の項より十分小さくしてください。
+///
+/// `d`と`d'`が同一であれば0になります。
+pub fn consistency_penalty<B: Backend>(
+    predicted_dims: Tensor<B, 2>,
+    perturbed_dims: Tensor<B, 2>,
+) -> Tensor<B, 1> {
+    let epsilon = 1e-8;
+    let relative_change = (perturbed_dims - predicted_dims.clone()) / (predicted_dims + epsilon);
+    relative_change.powf_scalar(2.0).sum_dim(1).mean()
+}
 
-    let prong_gap_penalty = relu(0.002 - prong_gap.clone()).powf_scalar(2.0)
-        + relu(prong_gap.clone() - 0.02).powf_scalar(2.0);
+/// 値が`[lower, upper]`の範囲からはみ出した量の二乗を要素ごとに計算します。
+///
+/// `relu(lower - x)² + relu(x - upper)²` と同じ値になりますが、範囲の外側ではどちらか一方の
+/// 項しか非零にならないことを利用し、クランプ1回 (`(x - clamp(x, lower, upper))²`) で
+/// 計算して中間テンソルを減らしています。
+fn out_of_range_penalty<B: Backend>(x: Tensor<B, 2>, lower: f32, upper: f32) -> Tensor<B, 2> {
+    (x.clone() - x.clamp(lower, upper)).powf_scalar(2.0)
+}
 
-    // --- 3. 合計損失の計算 ---
-    let total_loss = (frequency_loss
-        + ratio_penalty * PENALTY_WEIGHT_RATIO
-        + (prong_diameter_penalty + prong_length_penalty) * PENALTY_WEIGHT_RANGE
-        + (handle_length_penalty + handle_diameter_penalty + prong_gap_penalty)
-            * PENALTY_WEIGHT_OTHER)
-        .mean();
+/// 損失関数のベンチマークやテストで共有する、決定的な入力を生成します。
+///
+/// 各寸法と目標周波数 (200〜1800Hz) を行ごとに線形に変化させた、物理的にありうる範囲の
+/// 値を返します。乱数を使わないため、実行ごとに同じ入力になります。
+///
+/// # Returns
+///
+/// `([batch_size, 5]`形状の寸法, `[batch_size, 1]`形状の目標周波数`)`。
+pub fn sample_loss_inputs<B: Backend>(
+    batch_size: usize,
+    device: &B::Device,
+) -> (Tensor<B, 2>, Tensor<B, 2>) {
+    let denom = batch_size.saturating_sub(1).max(1) as f32;
+    let mut dims = Vec::with_capacity(batch_size * model_dims::NUM_DIMS);
+    let mut targets = Vec::with_capacity(batch_size);
+    for i in 0..batch_size {
+        let t = i as f32 / denom;
+        dims.extend_from_slice(&[
+            0.05 + 0.10 * t,
+            0.005 + 0.010 * t,
+            0.03 + 0.10 * t,
+            0.002 + 0.008 * t,
+            0.002 + 0.015 * t,
+        ]);
+        targets.push(200.0 + 1600.0 * t);
+    }
 
-    total_loss
+    (
+        Tensor::from_data(TensorData::new(dims, [batch_size, model_dims::NUM_DIMS]), device),
+        Tensor::from_data(TensorData::new(targets, [batch_size, 1]), device),
+    )
 }
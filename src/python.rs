@@ -0,0 +1,72 @@
+//! # Pythonバインディング
+//!
+//! 学習済みモデルによる推論を、PyO3でPythonの拡張モジュール`tuningfork_pinn`として
+//! 提供します (`python`フィーチャー)。推論には`ndarray`バックエンド (CPU) を使用します。
+//!
+//! 拡張モジュールのビルドには、`extension-module`フィーチャーを有効にして
+//! [maturin](https://www.maturin.rs/)を使用します (`maturin develop --release`)。
+//!
+//! ```python
+//! import tuningfork_pinn
+//!
+//! dims = tuningfork_pinn.predict(440.0, "./artifacts")
+//! ```
+//!
+//! 寸法 (m) は`[handle_length, handle_diameter, prong_length, prong_diameter, prong_gap]`の
+//! 順のリストとして返します。
+
+use crate::infer::{self, InferError};
+use burn::backend::ndarray::{NdArray, NdArrayDevice};
+use pyo3::exceptions::{PyFileNotFoundError, PyRuntimeError, PyValueError};
+use pyo3::prelude::*;
+
+type PythonBackend = NdArray<f32>;
+
+impl From<InferError> for PyErr {
+    fn from(err: InferError) -> Self {
+        match err {
+            InferError::ModelNotFound(_) => PyFileNotFoundError::new_err(err.to_string()),
+            InferError::InvalidInput(_) => PyValueError::new_err(err.to_string()),
+            _ => PyRuntimeError::new_err(err.to_string()),
+        }
+    }
+}
+
+/// 周波数 (Hz) に対する音叉の寸法 (m) を推論します。
+///
+/// # Errors
+///
+/// モデルが見つからない場合は`FileNotFoundError`、周波数が不正な場合は`ValueError`、
+/// その他の読み込みの失敗は`RuntimeError`をPython側に送出します。
+#[pyfunction]
+#[pyo3(signature = (freq, artifact_dir = infer::DEFAULT_ARTIFACT_DIR))]
+pub fn predict(freq: f32, artifact_dir: &str) -> PyResult<Vec<f32>> {
+    Ok(predict_batch(vec![freq], artifact_dir)?.remove(0))
+}
+
+/// 複数の周波数 (Hz) に対する寸法 (m) を一度のフォワードパスで推論します。
+///
+/// # Errors
+///
+/// [`predict`]と同じ例外を送出します。
+#[pyfunction]
+#[pyo3(signature = (freqs, artifact_dir = infer::DEFAULT_ARTIFACT_DIR))]
+pub fn predict_batch(freqs: Vec<f32>, artifact_dir: &str) -> PyResult<Vec<Vec<f32>>> {
+    let dims = infer::run_batch::<PythonBackend>(
+        artifact_dir,
+        &freqs,
+        None,
+        infer::DEFAULT_CHUNK_SIZE,
+        NdArrayDevice::Cpu,
+        None,
+    )?;
+    Ok(dims.iter().map(|d| d.to_array().to_vec()).collect())
+}
+
+/// Pythonの拡張モジュール`tuningfork_pinn`を初期化します。
+#[pymodule]
+fn tuningfork_pinn(m: &Bound<'_, PyModule>) -> PyResult<()> {
+    m.add_function(wrap_pyfunction!(predict, m)?)?;
+    m.add_function(wrap_pyfunction!(predict_batch, m)?)?;
+    Ok(())
+}
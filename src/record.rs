@@ -0,0 +1,126 @@
+//! # モデルレコードの保存形式
+//!
+//! 学習済みモデルやチェックポイントを保存・読み込みする際のファイル形式を定義します。
+
+use burn::{
+    prelude::*,
+    record::{
+        BinBytesRecorder, BinFileRecorder, CompactRecorder, FullPrecisionSettings,
+        PrettyJsonFileRecorder, Record, Recorder, RecorderError,
+    },
+};
+use std::{path::PathBuf, str::FromStr};
+
+/// モデルレコードのファイル形式。
+#[derive(Config, Debug, Copy, PartialEq)]
+pub enum RecordFormat {
+    /// 名前付きMessagePack (`.mpk`、半精度)。サイズが小さい (デフォルト)。
+    NamedMpk,
+    /// Bincode (`.bin`、単精度)。
+    Bincode,
+    /// 整形されたJSON (`.json`、単精度)。人が読んで重みを確認できる。
+    PrettyJson,
+}
+
+impl RecordFormat {
+    /// すべての形式。推論時にモデルファイルを探索する順序でもあります。
+    pub const ALL: [RecordFormat; 3] = [
+        RecordFormat::NamedMpk,
+        RecordFormat::Bincode,
+        RecordFormat::PrettyJson,
+    ];
+
+    /// この形式のファイル拡張子 (`.`を含まない)。
+    pub fn extension(&self) -> &'static str {
+        match self {
+            RecordFormat::NamedMpk => "mpk",
+            RecordFormat::Bincode => "bin",
+            RecordFormat::PrettyJson => "json",
+        }
+    }
+
+    /// ファイルパスの拡張子から形式を判定します。該当しない場合は`None`を返します。
+    pub fn from_path(path: &str) -> Option<Self> {
+        let extension = std::path::Path::new(path).extension()?.to_str()?;
+        Self::ALL
+            .into_iter()
+            .find(|format| format.extension() == extension)
+    }
+}
+
+impl FromStr for RecordFormat {
+    type Err = String;
+
+    /// `mpk`、`bincode`、`json` を解析します。
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_ascii_lowercase().as_str() {
+            "mpk" | "named-mpk" => Ok(RecordFormat::NamedMpk),
+            "bin" | "bincode" => Ok(RecordFormat::Bincode),
+            "json" | "pretty-json" => Ok(RecordFormat::PrettyJson),
+            _ => Err(format!(
+                "unknown record format '{s}' (expected mpk, bincode or json)"
+            )),
+        }
+    }
+}
+
+/// 指定された形式でレコードを保存します。
+///
+/// `path`の拡張子は形式に応じて置き換えられます。
+pub fn save_record<B: Backend, R: Record<B>>(
+    format: RecordFormat,
+    record: R,
+    path: PathBuf,
+) -> Result<(), RecorderError> {
+    match format {
+        RecordFormat::NamedMpk => Recorder::<B>::record(&CompactRecorder::new(), record, path),
+        RecordFormat::Bincode => Recorder::<B>::record(
+            &BinFileRecorder::<FullPrecisionSettings>::new(),
+            record,
+            path,
+        ),
+        RecordFormat::PrettyJson => Recorder::<B>::record(
+            &PrettyJsonFileRecorder::<FullPrecisionSettings>::new(),
+            record,
+            path,
+        ),
+    }
+}
+
+/// 指定された形式でレコードを読み込みます。
+///
+/// `path`の拡張子は形式に応じて置き換えられます。
+pub fn load_record<B: Backend, R: Record<B>>(
+    format: RecordFormat,
+    path: PathBuf,
+    device: &B::Device,
+) -> Result<R, RecorderError> {
+    match format {
+        RecordFormat::NamedMpk => CompactRecorder::new().load(path, device),
+        RecordFormat::Bincode => BinFileRecorder::<FullPrecisionSettings>::new().load(path, device),
+        RecordFormat::PrettyJson => {
+            PrettyJsonFileRecorder::<FullPrecisionSettings>::new().load(path, device)
+        }
+    }
+}
+
+/// レコードをメモリ上のバイト列として保存します。
+///
+/// バイト列は[`RecordFormat::Bincode`]で保存したファイル (`.bin`) の内容と同じ形式です。
+pub fn save_record_to_bytes<B: Backend, R: Record<B>>(
+    record: R,
+) -> Result<Vec<u8>, RecorderError> {
+    Recorder::<B>::record(&BinBytesRecorder::<FullPrecisionSettings>::default(), record, ())
+}
+
+/// メモリ上のバイト列からレコードを読み込みます。
+///
+/// バイト列は[`save_record_to_bytes`]の出力、または[`RecordFormat::Bincode`]で保存した
+/// ファイル (`.bin`) の内容です。ファイルシステムのない環境 (WASMなど) でモデルを読み込む
+/// 場合に使用します。
+pub fn load_record_from_bytes<B: Backend, R: Record<B>>(
+    bytes: &[u8],
+    device: &B::Device,
+) -> Result<R, RecorderError> {
+    BinBytesRecorder::<FullPrecisionSettings>::default().load(bytes.to_vec(), device)
+}
@@ -0,0 +1,189 @@
+//! # 推論サーバー
+//!
+//! 学習済みモデルによる推論を、HTTPのREST APIとして提供します (`server`フィーチャー)。
+//!
+//! モデルと学習設定の材質・温度は起動時に一度だけ読み込み、リクエストごとには読み込みません。
+//! 推論は同期的な計算の
+//! ため、非同期ランタイムのワーカーを塞がないよう`spawn_blocking`で実行します。
+//!
+//! - `GET /predict?freq=440`: 周波数 (Hz) に対する寸法 ([`ForkDimensions`]) をJSONで返します。
+//!   周波数が不正な場合は`400 Bad Request`と`{"error": "..."}`を返します。
+
+use crate::infer::{self, ForkDimensions, InferError};
+use crate::model::TuningForkPINN;
+use crate::physics::PhysicsConfig;
+use axum::{
+    Json, Router,
+    extract::{Query, State},
+    http::StatusCode,
+    routing::get,
+};
+use burn::prelude::*;
+use log::info;
+use serde::{Deserialize, Serialize};
+use std::{
+    fmt,
+    net::SocketAddr,
+    sync::{Arc, Mutex},
+};
+
+/// サーバーで発生するエラー。
+#[derive(Debug)]
+pub enum ServerError {
+    /// 学習済みモデルの読み込みに失敗した。
+    Load(InferError),
+    /// ソケットの待ち受けまたは非同期ランタイムの起動に失敗した。
+    Io(std::io::Error),
+}
+
+impl fmt::Display for ServerError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ServerError::Load(err) => write!(f, "{err}"),
+            ServerError::Io(err) => write!(f, "Server error: {err}"),
+        }
+    }
+}
+
+impl std::error::Error for ServerError {}
+
+impl From<InferError> for ServerError {
+    fn from(err: InferError) -> Self {
+        ServerError::Load(err)
+    }
+}
+
+impl From<std::io::Error> for ServerError {
+    fn from(err: std::io::Error) -> Self {
+        ServerError::Io(err)
+    }
+}
+
+/// リクエスト間で共有する、読み込み済みのモデル・推論に使う材質と温度・デバイス。
+pub struct AppState<B: Backend> {
+    model: Arc<Mutex<TuningForkPINN<B>>>,
+    physics: Arc<PhysicsConfig>,
+    device: B::Device,
+}
+
+// `derive(Clone)`では`B: Clone`が要求されるため手動で実装する
+impl<B: Backend> Clone for AppState<B> {
+    fn clone(&self) -> Self {
+        Self {
+            model: Arc::clone(&self.model),
+            physics: Arc::clone(&self.physics),
+            device: self.device.clone(),
+        }
+    }
+}
+
+impl<B: Backend> AppState<B> {
+    /// 成果物ディレクトリから学習済みモデルと学習時の物理モデルの設定を読み込みます。
+    ///
+    /// # Errors
+    ///
+    /// モデルや学習設定が読み込めない、またはモデルの出力が円形断面の寸法数と異なる場合に
+    /// `InferError`を返します。
+    pub fn load(artifact_dir: &str, device: B::Device) -> Result<Self, InferError> {
+        let model = infer::load_model::<B>(artifact_dir, &device)?;
+        let physics = infer::physics_config_for(artifact_dir)?;
+        Self::from_model(model, physics, device)
+    }
+
+    /// 読み込み済みのモデルから状態を作成します。材質や温度を入力とするモデルには、
+    /// `physics`の材質・温度を与えて推論します。
+    ///
+    /// # Errors
+    ///
+    /// モデルの出力が円形断面の寸法数と異なる場合に`InferError`を返します。
+    pub fn from_model(
+        model: TuningForkPINN<B>,
+        physics: PhysicsConfig,
+        device: B::Device,
+    ) -> Result<Self, InferError> {
+        infer::ensure_circular_output(model.output_size())?;
+        Ok(Self {
+            model: Arc::new(Mutex::new(model)),
+            physics: Arc::new(physics),
+            device,
+        })
+    }
+}
+
+/// `GET /predict`のクエリパラメータ。
+#[derive(Debug, Clone, Copy, Deserialize)]
+pub struct PredictQuery {
+    /// 推論したい音叉の周波数 (Hz)。
+    pub freq: f32,
+}
+
+/// エラー時のレスポンスの本文。
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ErrorResponse {
+    /// エラーの内容。
+    pub error: String,
+}
+
+/// `GET /predict`のハンドラー。
+///
+/// # Errors
+///
+/// 周波数が正の有限値でない場合に`400 Bad Request`、推論に失敗またはパニックした場合に
+/// `500 Internal Server Error`を返します。
+pub async fn predict<B: Backend>(
+    State(state): State<AppState<B>>,
+    Query(query): Query<PredictQuery>,
+) -> Result<Json<ForkDimensions>, (StatusCode, Json<ErrorResponse>)> {
+    infer::validate_frequencies(&[query.freq]).map_err(|err| {
+        (
+            StatusCode::BAD_REQUEST,
+            Json(ErrorResponse {
+                error: err.to_string(),
+            }),
+        )
+    })?;
+    let predicted = tokio::task::spawn_blocking(move || {
+        // 推論中にパニックしてもモデルの重みは変化しないため、ロックの汚染は無視する
+        let model = state.model.lock().unwrap_or_else(|err| err.into_inner());
+        model
+            .predict_batch_with_physics(&[query.freq], &state.physics, &state.device)
+            .map(|mut dims| dims.remove(0))
+    })
+    .await;
+    let internal_error = |error| (StatusCode::INTERNAL_SERVER_ERROR, Json(ErrorResponse { error }));
+    match predicted {
+        Ok(Ok(dims)) => Ok(Json(dims)),
+        Ok(Err(err)) => Err(internal_error(err.to_string())),
+        Err(err) => Err(internal_error(format!("inference failed: {err}"))),
+    }
+}
+
+/// 推論APIのルーターを作成します。
+pub fn router<B: Backend>(state: AppState<B>) -> Router {
+    Router::new()
+        .route("/predict", get(predict::<B>))
+        .with_state(state)
+}
+
+/// 学習済みモデルを読み込み、`addr`でHTTPリクエストの待ち受けを開始します。
+///
+/// プロセスが終了するまで戻りません。
+///
+/// # Errors
+///
+/// モデルが読み込めない、非同期ランタイムを起動できない、またはアドレスで待ち受けられない
+/// 場合に`ServerError`を返します。
+pub fn run<B: Backend>(
+    artifact_dir: &str,
+    addr: SocketAddr,
+    device: B::Device,
+) -> Result<(), ServerError> {
+    let state = AppState::<B>::load(artifact_dir, device)?;
+    let runtime = tokio::runtime::Runtime::new()?;
+    runtime.block_on(async {
+        let listener = tokio::net::TcpListener::bind(addr).await?;
+        info!("🌐 Listening on http://{}", listener.local_addr()?);
+        axum::serve(listener, router(state)).await?;
+        Ok::<_, ServerError>(())
+    })
+}
@@ -3,41 +3,247 @@
 //! `burn`の`Learner` APIを使用して、物理情報ニューラルネットワーク（PINN）の学習プロセスを管理します。
 //! このモジュールは、任意のバックエンドで動作するようにジェネリックになっています。
 
-use crate::model::TuningForkPINN;
-use crate::physics::tuning_fork_loss;
+#[cfg(feature = "native")]
+use crate::backend::BackendAction;
+use crate::constants::model_dims;
+use crate::constants::physics::{
+    Material, OVERTONE_RATIO, PENALTY_WEIGHT_OTHER, PENALTY_WEIGHT_RANGE, PENALTY_WEIGHT_RATIO,
+    REFERENCE_TEMPERATURE,
+};
+use crate::infer::{DEFAULT_ARTIFACT_DIR, TRAINING_CONFIG_FILE};
+use crate::metadata::{METADATA_FILE, ModelMetadata};
+use crate::metrics::{
+    FrequencyErrorMetric, LossComponentKind, LossComponentMetric, MetricLogger,
+    PhysicalValidityMetric, ToleranceReport,
+};
+use crate::model::{Activation, InitKind, ModelConfig, TuningForkPINN};
+use crate::physics::{
+    CrossSectionKind, FrequencyLossKind, LossComponents, LossConfig, PhysicsConfig,
+    analytic_dimensions, consistency_penalty, perturb_frequencies, predicted_frequency_with_config,
+    tuning_fork_loss_components,
+};
+use crate::record::{self, RecordFormat};
 use burn::{
+    backend::{NdArray, ndarray::NdArrayDevice},
     config::Config,
-    data::{dataloader::DataLoaderBuilder, dataloader::batcher::Batcher, dataset::Dataset},
-    lr_scheduler::constant::ConstantLr,
-    module::Module,
-    optim::AdamConfig,
+    data::{
+        dataloader::{DataLoader, DataLoaderBuilder, batcher::Batcher},
+        dataset::Dataset,
+    },
+    grad_clipping::GradientClippingConfig,
+    LearningRate,
+    lr_scheduler::LrScheduler,
+    module::{AutodiffModule, Module},
+    optim::{
+        AdamConfig, AdamWConfig, SgdConfig, decay::WeightDecayConfig, momentum::MomentumConfig,
+    },
     prelude::*,
-    record::{CompactRecorder, Recorder},
+    record::{BinFileRecorder, CompactRecorder, FullPrecisionSettings, PrettyJsonFileRecorder},
     tensor::backend::AutodiffBackend,
-    train::{LearnerBuilder, RegressionOutput, TrainOutput, TrainStep, ValidStep},
+    train::{
+        EarlyStoppingStrategy, LearnerBuilder, RegressionOutput, TrainOutput, TrainStep,
+        ValidStep,
+        checkpoint::MetricCheckpointingStrategy,
+        metric::{
+            Adaptor, ItemLazy, LossInput, LossMetric,
+            store::{Aggregate, Direction, EventStoreClient, Split},
+        },
+    },
+};
+use log::{Level, debug, info, log_enabled, warn};
+use rand::{Rng, SeedableRng, distributions::WeightedIndex, rngs::StdRng};
+use serde::{Deserialize, Serialize, de::IgnoredAny};
+use std::{
+    fmt, fs,
+    path::Path,
+    str::FromStr,
+    sync::{
+        Arc, Mutex,
+        atomic::{AtomicUsize, Ordering},
+    },
 };
-use rand::{Rng, thread_rng};
+
+/// 学習処理で発生するエラー。
+#[derive(Debug)]
+pub enum TrainError {
+    /// 学習設定が不正。
+    InvalidConfig(String),
+    /// 学習済みモデルの保存に失敗した。
+    Save(String),
+    /// 再開用のモデルレコードの読み込みに失敗した。
+    Load(String),
+    /// 損失が有限値でなくなった (NaNまたは無限大)。値は発散を検出したエポック。
+    Diverged(usize),
+}
+
+impl fmt::Display for TrainError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            TrainError::InvalidConfig(msg) => write!(f, "Invalid training config: {msg}"),
+            TrainError::Save(msg) => write!(f, "Failed to save trained model: {msg}"),
+            TrainError::Load(msg) => write!(f, "Failed to load model to resume from: {msg}"),
+            TrainError::Diverged(epoch) => write!(
+                f,
+                "Training diverged: loss became NaN or infinite at epoch {epoch}. \
+                 Try a lower --learning-rate or enable gradient clipping with --grad-clip"
+            ),
+        }
+    }
+}
+
+impl std::error::Error for TrainError {}
+
+/// 学習データセットの1エポックあたりのバッチ数。
+pub const TRAIN_BATCHES_PER_EPOCH: usize = 100;
+/// 検証データセットのバッチ数。
+pub const VALID_BATCHES: usize = 20;
+
+/// ペナルティの重みのウォームアップ開始時 (エポック0) の倍率。
+///
+/// 0にすると最初のエポックで寸法が物理的な範囲から大きく外れるため、わずかに残します。
+pub const PENALTY_WARMUP_START: f32 = 0.01;
+
+/// 学習曲線 (エポックごとの損失) を記録するCSVのファイル名 (成果物ディレクトリからの相対パス)。
+pub const TRAINING_LOG_FILE: &str = "training_log.csv";
+
+/// 周波数範囲を分割するビンの数。
+pub const NUM_FREQ_BINS: usize = 20;
+/// 検証用に取り置くビンの間隔。`VALID_BIN_INTERVAL`個のビンごとに1つを検証用にします。
+pub const VALID_BIN_INTERVAL: usize = 5;
+
+/// データセットが周波数範囲のどの部分から値を生成するかを表します。
+///
+/// 周波数範囲を`NUM_FREQ_BINS`個の等幅ビンに分割し、`VALID_BIN_INTERVAL`個ごとに
+/// 1つのビンを検証用として取り置きます (デフォルトでは20%)。学習用と検証用のビンは
+/// 交互に並ぶため、検証は範囲の外挿ではなく範囲内の汎化性能を測定します。
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum FrequencySplit {
+    /// 範囲全体から生成します。
+    Full,
+    /// 検証用ビンを除いた範囲から生成します。
+    Train,
+    /// 検証用に取り置いたビンからのみ生成します。
+    Valid,
+}
+
+impl FrequencySplit {
+    /// 指定されたビンがこの分割に含まれるかを返します。
+    pub fn contains_bin(&self, bin: usize) -> bool {
+        let is_valid_bin = bin % VALID_BIN_INTERVAL == VALID_BIN_INTERVAL - 1;
+        match self {
+            FrequencySplit::Full => true,
+            FrequencySplit::Train => !is_valid_bin,
+            FrequencySplit::Valid => is_valid_bin,
+        }
+    }
+
+    /// この分割に含まれるビンのインデックス。
+    fn bins(&self) -> Vec<usize> {
+        (0..NUM_FREQ_BINS)
+            .filter(|bin| self.contains_bin(*bin))
+            .collect()
+    }
+}
+
+/// 学習用の周波数を範囲内でどのように分布させるかを表します。
+#[derive(Config, Debug, Copy, PartialEq)]
+pub enum SamplingMode {
+    /// 周波数 (Hz) について一様に生成します (デフォルト)。
+    Uniform,
+    /// 周波数の対数について一様に生成します。
+    ///
+    /// 音高は周波数の比で決まるため、1オクターブあたりのサンプル数が範囲全体で等しくなります。
+    /// `Uniform`と比べて低い周波数のサンプルが増え、高い周波数では減ります。
+    LogUniform,
+}
+
+impl SamplingMode {
+    /// `low..high`の範囲から、この分布に従って周波数を1つ生成します。
+    fn sample<R: Rng>(&self, rng: &mut R, low: f32, high: f32) -> f32 {
+        match self {
+            SamplingMode::Uniform => rng.gen_range(low..high),
+            SamplingMode::LogUniform => rng.gen_range(low.ln()..high.ln()).exp(),
+        }
+    }
+}
+
+impl FromStr for SamplingMode {
+    type Err = String;
+
+    /// `uniform`、`log-uniform`を解析します。
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_ascii_lowercase().as_str() {
+            "uniform" => Ok(SamplingMode::Uniform),
+            "log-uniform" => Ok(SamplingMode::LogUniform),
+            _ => Err(format!("unknown sampling mode '{s}' (expected uniform or log-uniform)")),
+        }
+    }
+}
+
+/// 周波数が範囲内のどのビンに属するかを返します。
+pub fn frequency_bin(freq_range: (f32, f32), freq: f32) -> usize {
+    let width = (freq_range.1 - freq_range.0) / NUM_FREQ_BINS as f32;
+    let bin = ((freq - freq_range.0) / width).floor() as usize;
+    bin.min(NUM_FREQ_BINS - 1)
+}
 
 /// 学習データをオンザフライで生成するデータセット。
 ///
 /// 物理シミュレーションであるため、事前にデータファイルを用意する必要がなく、
 /// 必要になるたびにランダムな周波数を生成します。
+/// 乱数は`seed`とインデックスから決定的に生成されるため、同じシードであれば
+/// ワーカー数や取得順序に関わらず同じ周波数列が得られます。
 #[derive(Clone, Debug)]
 pub struct TuningForkDataset {
     /// データセットの見かけ上のサイズ。
     pub size: usize,
     /// 生成する周波数の範囲 (min, max)。
     pub freq_range: (f32, f32),
+    /// 範囲のどの部分から生成するか。
+    pub split: FrequencySplit,
+    /// 範囲内の周波数の分布。
+    pub sampling: SamplingMode,
+    /// 乱数のシード。
+    pub seed: u64,
 }
 
 impl Dataset<f32> for TuningForkDataset {
     /// データセットから一つのアイテム（周波数）を取得します。
     ///
-    /// この実装では、呼ばれるたびに新しいランダムな周波数を返します。
-    fn get(&self, _index: usize) -> Option<f32> {
-        let mut rng = thread_rng();
-        let frequency = rng.gen_range(self.freq_range.0..=self.freq_range.1);
-        Some(frequency)
+    /// この実装では、シードとインデックスから決まるランダムな周波数を返します。
+    fn get(&self, index: usize) -> Option<f32> {
+        // インデックスごとに独立した乱数生成器を決定的に初期化する
+        let item_seed = self
+            .seed
+            .wrapping_mul(0x9E37_79B9_7F4A_7C15)
+            .wrapping_add(index as u64);
+        let mut rng = StdRng::seed_from_u64(item_seed);
+        let (min, max) = self.freq_range;
+        if self.split == FrequencySplit::Full {
+            return Some(self.sampling.sample(&mut rng, min, max));
+        }
+
+        // 分割に含まれるビンを一つ選び、その中でサンプリングする
+        let bins = self.split.bins();
+        let width = (max - min) / NUM_FREQ_BINS as f32;
+        let bin_range = |bin: usize| {
+            let low = min + bin as f32 * width;
+            (low, low + width)
+        };
+        let bin = match self.sampling {
+            SamplingMode::Uniform => bins[rng.gen_range(0..bins.len())],
+            // ビンは等幅のため、対数で一様になるよう各ビンの対数での幅で重み付けして選ぶ
+            SamplingMode::LogUniform => {
+                let weights = bins.iter().map(|&bin| {
+                    let (low, high) = bin_range(bin);
+                    (high / low).ln()
+                });
+                let index = WeightedIndex::new(weights).expect("bin widths are positive");
+                bins[rng.sample(index)]
+            }
+        };
+        let (low, high) = bin_range(bin);
+        Some(self.sampling.sample(&mut rng, low, high))
     }
 
     /// データセットの長さを返します。
@@ -46,82 +252,1480 @@ impl Dataset<f32> for TuningForkDataset {
     }
 }
 
+/// 周波数範囲を等間隔に区切った値を返す決定的なデータセット。
+///
+/// 検証に使用することで、エポック間で比較可能な安定した検証損失が得られます。
+#[derive(Clone, Debug)]
+pub struct GridFrequencyDataset {
+    /// グリッド点の数。
+    pub size: usize,
+    /// 周波数範囲の下限 (Hz)。
+    pub freq_min: f32,
+    /// 周波数範囲の上限 (Hz)。
+    pub freq_max: f32,
+}
+
+impl Dataset<f32> for GridFrequencyDataset {
+    /// `freq_min + index * (freq_max - freq_min) / (size - 1)` を返します。
+    fn get(&self, index: usize) -> Option<f32> {
+        if index >= self.size {
+            return None;
+        }
+        if self.size == 1 {
+            return Some(self.freq_min);
+        }
+        let step = (self.freq_max - self.freq_min) / (self.size - 1) as f32;
+        Some(self.freq_min + index as f32 * step)
+    }
+
+    /// データセットの長さを返します。
+    fn len(&self) -> usize {
+        self.size
+    }
+}
+
+/// あらかじめ与えた周波数の列をそのまま返すインメモリのデータセット。
+///
+/// 乱数による生成を行わないため、テストで学習データを固定したり、
+/// 特定の周波数だけで学習したりする場合に使用します ([`run_with_dataset`]を参照)。
+#[derive(Clone, Debug)]
+pub struct FixedDataset {
+    /// 返す周波数 (Hz) の列。
+    pub frequencies: Vec<f32>,
+}
+
+impl FixedDataset {
+    /// 指定された周波数の列を返すデータセットを作成します。
+    pub fn new(frequencies: Vec<f32>) -> Self {
+        Self { frequencies }
+    }
+}
+
+impl Dataset<f32> for FixedDataset {
+    /// `index`番目の周波数を返します。
+    fn get(&self, index: usize) -> Option<f32> {
+        self.frequencies.get(index).copied()
+    }
+
+    /// データセットの長さを返します。
+    fn len(&self) -> usize {
+        self.frequencies.len()
+    }
+}
+
+/// 学習・検証ステップに渡されるミニバッチ。
+#[derive(Clone, Debug)]
+pub struct TuningForkBatch<B: Backend> {
+    /// 目標周波数のテンソル。形状は `[batch_size, 1]`。
+    pub frequencies: Tensor<B, 2>,
+    /// このバッチの損失計算に使用する設定。
+    pub loss_config: LossConfig,
+}
+
 /// データセットから取得したアイテムをミニバッチにまとめるバッチャ。
 ///
 /// `f32`のスライスを、指定されたバックエンドのテンソルに変換します。
 pub struct TuningForkBatcher<B: Backend> {
     _device: B::Device,
+    loss_config: LossConfig,
+    penalty_warmup: Option<PenaltyWarmup>,
+    // データローダーのワーカー間で共有される、材質を切り替えるためのバッチのカウンタ
+    material_cycle: Option<Arc<AtomicUsize>>,
+    temperature_sweep: Option<TemperatureSweep>,
+}
+
+/// バッチごとに温度を範囲内で切り替える設定。
+struct TemperatureSweep {
+    min: f32,
+    max: f32,
+    // データローダーのワーカー間で共有されるカウンタ
+    batches_seen: Arc<AtomicUsize>,
+}
+
+impl TemperatureSweep {
+    /// 黄金比の小数部 `(√5 − 1) / 2`。
+    const GOLDEN_RATIO_FRACTION: f64 = 0.618_033_988_749_894_8;
+
+    /// `batch`番目のバッチの温度を返します。
+    ///
+    /// `batch · φ`の小数部 (低食い違い量列) で範囲内の位置を決めるため、少ないバッチ数でも
+    /// 範囲全体を偏りなく、決定的に網羅します。
+    fn temperature(&self, batch: usize) -> f32 {
+        let position = (batch as f64 * Self::GOLDEN_RATIO_FRACTION).fract() as f32;
+        self.min + (self.max - self.min) * position
+    }
+}
+
+/// 周波数を設定された平均と標準偏差で正規化してから`[batch_size, 1]`のテンソルにまとめるバッチャ。
+///
+/// モデル内部の正規化 ([`TuningForkPINN::normalize_input`]) と同じ変換
+/// `(f - mean) / std` をバッチの作成時に行います。モデルの`forward`や書き出したONNXのグラフは
+/// 自身で正規化するため、このバッチャの出力を渡すと二重に正規化されます。正規化済みの周波数を
+/// 必要とする解析や、正規化を含まない独自のネットワークに使用してください。周波数の列のみを
+/// 生成し、材質・温度の特徴量の列は含みません。統計量はバッチャに保持されるため、
+/// [`Self::from_model`]で学習済みモデルから作成すれば、推論時にも学習時と同じ変換が適用されます。
+pub struct NormalizingBatcher<B: Backend> {
+    _device: B::Device,
+    mean: f32,
+    std: f32,
+}
+
+impl<B: Backend> NormalizingBatcher<B> {
+    /// 指定された平均と標準偏差 (Hz) で正規化するバッチャを作成します。
+    pub fn new(device: B::Device, mean: f32, std: f32) -> Self {
+        Self {
+            _device: device,
+            mean,
+            std,
+        }
+    }
+
+    /// モデルのレコードに保存された入力正規化の統計量を使うバッチャを作成します。
+    pub fn from_model<M: Backend>(device: B::Device, model: &TuningForkPINN<M>) -> Self {
+        let (mean, std) = model.input_normalization();
+        Self::new(device, mean, std)
+    }
+
+    /// 正規化に使う平均と標準偏差 (Hz) を返します。
+    pub fn normalization(&self) -> (f32, f32) {
+        (self.mean, self.std)
+    }
+}
+
+impl<B: Backend> Batcher<B, f32, Tensor<B, 2>> for NormalizingBatcher<B> {
+    /// `f32`のVecを正規化し、`[batch_size, 1]`形状のテンソルに変換します。
+    fn batch(&self, items: Vec<f32>, device: &B::Device) -> Tensor<B, 2> {
+        let normalized: Vec<f32> = items
+            .into_iter()
+            .map(|freq| (freq - self.mean) / self.std)
+            .collect();
+        Tensor::<B, 1>::from_floats(normalized.as_slice(), device).reshape([-1, 1])
+    }
+}
+
+/// バッチャが生成したバッチ数からエポックを求め、ペナルティの重みを段階的に上げる設定。
+struct PenaltyWarmup {
+    epochs: usize,
+    batches_per_epoch: usize,
+    // データローダーのワーカー間で共有されるカウンタ
+    batches_seen: Arc<AtomicUsize>,
 }
 
 impl<B: Backend> TuningForkBatcher<B> {
-    /// 新しいバッチャを作成します。
+    /// デフォルトの損失設定で新しいバッチャを作成します。
     pub fn new(device: B::Device) -> Self {
-        Self { _device: device }
+        Self {
+            _device: device,
+            loss_config: LossConfig::new(),
+            penalty_warmup: None,
+            material_cycle: None,
+            temperature_sweep: None,
+        }
+    }
+
+    /// バッチに付与する損失設定を指定します。
+    pub fn with_loss_config(mut self, loss_config: LossConfig) -> Self {
+        self.loss_config = loss_config;
+        self
+    }
+
+    /// 最初の`epochs`エポックで、ペナルティの重みを段階的に上げます
+    /// ([`penalty_warmup_factor`]を参照)。
+    ///
+    /// 現在のエポックは、生成したバッチ数を`batches_per_epoch`で割って求めます。
+    /// `epochs`が0の場合は何もしません。
+    pub fn with_penalty_warmup(mut self, epochs: usize, batches_per_epoch: usize) -> Self {
+        self.penalty_warmup = (epochs > 0).then(|| PenaltyWarmup {
+            epochs,
+            batches_per_epoch: batches_per_epoch.max(1),
+            batches_seen: Arc::new(AtomicUsize::new(0)),
+        });
+        self
+    }
+
+    /// `enabled`が`true`の場合、バッチごとに材質を[`Material::ALL`]の順に切り替え、
+    /// 損失設定の材質 (`loss_config.physics.material`) をそのバッチの材質にします
+    /// ([`TrainingConfig::material_input`]を参照)。
+    pub fn with_material_cycle(mut self, enabled: bool) -> Self {
+        self.material_cycle = enabled.then(|| Arc::new(AtomicUsize::new(0)));
+        self
+    }
+
+    /// `range`が指定された場合、バッチごとに温度 (℃) を範囲内で切り替え、損失設定の温度
+    /// (`loss_config.physics.temperature`) をそのバッチの温度にします
+    /// ([`TrainingConfig::temperature_input`]を参照)。
+    pub fn with_temperature_range(mut self, range: Option<(f32, f32)>) -> Self {
+        self.temperature_sweep = range.map(|(min, max)| TemperatureSweep {
+            min,
+            max,
+            batches_seen: Arc::new(AtomicUsize::new(0)),
+        });
+        self
     }
 }
 
-impl<B: Backend> Batcher<B, f32, Tensor<B, 2>> for TuningForkBatcher<B> {
+impl<B: Backend> Batcher<B, f32, TuningForkBatch<B>> for TuningForkBatcher<B> {
     /// `f32`のVecを`[batch_size, 1]`形状のテンソルに一括で変換します。
     ///
     /// この実装は、すべてのデータを一度にGPUに転送するため、
     /// `iter().map().collect()`よりも効率的です。
-    fn batch(&self, items: Vec<f32>, device: &B::Device) -> Tensor<B, 2> {
+    fn batch(&self, items: Vec<f32>, device: &B::Device) -> TuningForkBatch<B> {
         // データをフラットなスライスとして取得
         let data_slice = items.as_slice();
         // スライスから直接テンソルを作成
-        Tensor::<B, 1>::from_floats(data_slice, device).reshape([-1, 1])
+        let frequencies = Tensor::<B, 1>::from_floats(data_slice, device).reshape([-1, 1]);
+        let mut loss_config = match &self.penalty_warmup {
+            Some(warmup) => {
+                let epoch = warmup.batches_seen.fetch_add(1, Ordering::Relaxed)
+                    / warmup.batches_per_epoch;
+                scale_penalties(
+                    self.loss_config.clone(),
+                    penalty_warmup_factor(epoch, warmup.epochs),
+                )
+            }
+            None => self.loss_config.clone(),
+        };
+        if let Some(cycle) = &self.material_cycle {
+            let index = cycle.fetch_add(1, Ordering::Relaxed) % Material::ALL.len();
+            loss_config.physics.material = Material::ALL[index];
+        }
+        if let Some(sweep) = &self.temperature_sweep {
+            let batch = sweep.batches_seen.fetch_add(1, Ordering::Relaxed);
+            loss_config.physics.temperature = sweep.temperature(batch);
+        }
+        TuningForkBatch {
+            frequencies,
+            loss_config,
+        }
+    }
+}
+
+/// ペナルティの重みのウォームアップで、`epoch`エポック目 (0始まり) に適用する倍率を返します。
+///
+/// エポック0の[`PENALTY_WARMUP_START`]から`warmup_epochs`エポック目の1.0まで線形に増加し、
+/// 以降は1.0です。`warmup_epochs`が0の場合は常に1.0です。
+pub fn penalty_warmup_factor(epoch: usize, warmup_epochs: usize) -> f32 {
+    if warmup_epochs == 0 {
+        return 1.0;
+    }
+    let progress = epoch.min(warmup_epochs) as f32 / warmup_epochs as f32;
+    PENALTY_WARMUP_START + (1.0 - PENALTY_WARMUP_START) * progress
+}
+
+/// 物理的制約のペナルティ (比率・範囲・その他) の重みに`factor`を掛けた損失設定を返します。
+fn scale_penalties(config: LossConfig, factor: f32) -> LossConfig {
+    let (ratio, range, other) = (
+        config.penalty_weight_ratio,
+        config.penalty_weight_range,
+        config.penalty_weight_other,
+    );
+    config
+        .with_penalty_weight_ratio(ratio * factor)
+        .with_penalty_weight_range(range * factor)
+        .with_penalty_weight_other(other * factor)
+}
+
+/// 学習・検証ステップの出力。
+///
+/// 回帰出力に加えて、損失の内訳をメトリクスへ渡すために保持します。
+pub struct PinnOutput<B: Backend> {
+    /// 合計損失・予測寸法・目標周波数。
+    pub regression: RegressionOutput<B>,
+    /// 損失の各項 (重み適用前)。
+    pub components: LossComponents<B>,
+}
+
+impl<B: Backend> PinnOutput<B> {
+    /// バッチに対してモデルを実行し、予測寸法と目標周波数から損失を計算して出力を構築します。
+    ///
+    /// `consistency`が`true`で`LossConfig::consistency_weight`が正の場合は、目標周波数を
+    /// ずらしてもう一度推論し、自己一貫性項 ([`consistency_penalty`]) も計算します。
+    /// ずらす量は乱数で決まるため、検証では損失が決定的になるよう`false`にします。
+    fn new(model: &TuningForkPINN<B>, batch: TuningForkBatch<B>, consistency: bool) -> Self {
+        let config = &batch.loss_config;
+        let targets = batch.frequencies;
+        // 材質や温度を入力とするモデルには、このバッチの損失計算と同じ値を与える
+        let predicted_dims = model.forward(model.with_conditions(targets.clone(), &config.physics));
+        let mut components =
+            tuning_fork_loss_components(predicted_dims.clone(), targets.clone(), config);
+        if consistency && config.consistency_weight > 0.0 {
+            let perturbed = perturb_frequencies(targets.clone(), config.consistency_perturbation);
+            components.consistency_penalty =
+                consistency_penalty(
+                    predicted_dims.clone(),
+                    model.forward(model.with_conditions(perturbed, &config.physics)),
+                );
+        }
+        // メトリクスは寸法の列だけを扱うため、Q値の列は回帰出力から除く
+        let num_dims = config.physics.cross_section.num_dims();
+        let [_, num_outputs] = predicted_dims.dims();
+        let output = if num_outputs > num_dims {
+            predicted_dims.narrow(1, 0, num_dims)
+        } else {
+            predicted_dims
+        };
+        let regression = RegressionOutput {
+            loss: components.clone().total(config),
+            output,
+            targets,
+        };
+        Self {
+            regression,
+            components,
+        }
+    }
+}
+
+impl<B: Backend> ItemLazy for PinnOutput<B> {
+    type ItemSync = PinnOutput<NdArray>;
+
+    fn sync(self) -> Self::ItemSync {
+        let device = NdArrayDevice::default();
+        let to_sync = |tensor: Tensor<B, 1>| Tensor::from_data(tensor.into_data(), &device);
+        let components = LossComponents {
+            frequency_loss: to_sync(self.components.frequency_loss),
+            ratio_penalty: to_sync(self.components.ratio_penalty),
+            range_penalty: to_sync(self.components.range_penalty),
+            other_penalty: to_sync(self.components.other_penalty),
+            consistency_penalty: to_sync(self.components.consistency_penalty),
+            overtone_loss: to_sync(self.components.overtone_loss),
+            q_factor_loss: to_sync(self.components.q_factor_loss),
+        };
+        PinnOutput {
+            regression: self.regression.sync(),
+            components,
+        }
+    }
+}
+
+impl<B: Backend> Adaptor<LossInput<B>> for PinnOutput<B> {
+    fn adapt(&self) -> LossInput<B> {
+        LossInput::new(self.regression.loss.clone())
+    }
+}
+
+impl<B: Backend> Adaptor<RegressionOutput<B>> for PinnOutput<B> {
+    fn adapt(&self) -> RegressionOutput<B> {
+        RegressionOutput {
+            loss: self.regression.loss.clone(),
+            output: self.regression.output.clone(),
+            targets: self.regression.targets.clone(),
+        }
+    }
+}
+
+impl<B: Backend> Adaptor<LossComponents<B>> for PinnOutput<B> {
+    fn adapt(&self) -> LossComponents<B> {
+        self.components.clone()
     }
 }
 
 /// モデルの学習ステップを定義します。
-impl<B: AutodiffBackend> TrainStep<Tensor<B, 2>, RegressionOutput<B>> for TuningForkPINN<B> {
+impl<B: AutodiffBackend> TrainStep<TuningForkBatch<B>, PinnOutput<B>> for TuningForkPINN<B> {
     /// 1回の学習ステップを実行します。
     ///
     /// 1. モデルによる予測
     /// 2. 物理法則に基づいた損失の計算
     /// 3. 勾配の計算と逆伝播
-    fn step(&self, item: Tensor<B, 2>) -> TrainOutput<RegressionOutput<B>> {
-        let predicted_dims = self.forward(item.clone());
-        let loss = tuning_fork_loss(predicted_dims.clone(), item.clone());
-        let output = RegressionOutput {
-            loss: loss.clone(),
-            output: predicted_dims,
-            targets: item,
-        };
-        TrainOutput::new(self, loss.backward(), output)
+    fn step(&self, batch: TuningForkBatch<B>) -> TrainOutput<PinnOutput<B>> {
+        let output = PinnOutput::new(self, batch, true);
+        let grads = output.regression.loss.backward();
+        TrainOutput::new(self, grads, output)
     }
 }
 
 /// モデルの検証ステップを定義します。
-impl<B: Backend> ValidStep<Tensor<B, 2>, RegressionOutput<B>> for TuningForkPINN<B> {
+impl<B: Backend> ValidStep<TuningForkBatch<B>, PinnOutput<B>> for TuningForkPINN<B> {
     /// 1回の検証ステップを実行します。
     ///
-    /// 損失を計算し、学習の進捗をモニタリングします。
-    fn step(&self, item: Tensor<B, 2>) -> RegressionOutput<B> {
-        let predicted_dims = self.forward(item.clone());
-        let loss = tuning_fork_loss(predicted_dims.clone(), item.clone());
-        RegressionOutput {
-            loss,
-            output: predicted_dims,
-            targets: item,
+    /// 損失を計算し、学習の進捗をモニタリングします。エポック間で検証損失を比較できるよう、
+    /// 乱数で入力をずらす自己一貫性項は計算しません。
+    fn step(&self, batch: TuningForkBatch<B>) -> PinnOutput<B> {
+        PinnOutput::new(self, batch, false)
+    }
+}
+
+/// 検証損失の停滞を検出するトラッカー。
+///
+/// 検証損失が最良値から`min_delta`より大きく改善しないエポックが`patience`回続くと、
+/// 停止すべきと判断します。また、検証損失が最小となったエポックを記録します。
+///
+/// 損失がNaNや無限大になった場合は発散したとみなし、ただちに停止すべきと判断します。
+#[derive(Clone, Debug)]
+pub struct PlateauTracker {
+    patience: usize,
+    min_delta: f64,
+    best: Option<f64>,
+    epochs_without_improvement: usize,
+    lowest: Option<(usize, f64)>,
+    last: Option<f64>,
+    diverged_epoch: Option<usize>,
+}
+
+impl PlateauTracker {
+    /// 新しいトラッカーを作成します。
+    pub fn new(patience: usize, min_delta: f64) -> Self {
+        Self {
+            patience,
+            min_delta,
+            best: None,
+            epochs_without_improvement: 0,
+            lowest: None,
+            last: None,
+            diverged_epoch: None,
+        }
+    }
+
+    /// エポックの検証損失を記録し、学習を停止すべきかを返します。
+    pub fn update(&mut self, epoch: usize, loss: f64) -> bool {
+        if self.detect_divergence(epoch, loss) {
+            return true;
+        }
+        self.last = Some(loss);
+        if self.lowest.is_none_or(|(_, lowest)| loss < lowest) {
+            self.lowest = Some((epoch, loss));
+        }
+        match self.best {
+            Some(best) if best - loss <= self.min_delta => {
+                self.epochs_without_improvement += 1;
+            }
+            _ => {
+                self.best = Some(loss);
+                self.epochs_without_improvement = 0;
+            }
+        }
+        self.epochs_without_improvement >= self.patience
+    }
+
+    /// これまでの最良の検証損失 (`min_delta`を考慮した基準値)。
+    pub fn best(&self) -> Option<f64> {
+        self.best
+    }
+
+    /// 検証損失が最小となったエポック。
+    pub fn best_epoch(&self) -> Option<usize> {
+        self.lowest.map(|(epoch, _)| epoch)
+    }
+
+    /// 最後に記録された検証損失。
+    pub fn last_loss(&self) -> Option<f64> {
+        self.last
+    }
+
+    /// 損失が有限値でなければ、そのエポックで発散したと記録して`true`を返します。
+    ///
+    /// 学習損失など、停滞の判定に使わない損失の検査にも使用します。
+    pub fn detect_divergence(&mut self, epoch: usize, loss: f64) -> bool {
+        if loss.is_finite() {
+            return false;
+        }
+        self.diverged_epoch.get_or_insert(epoch);
+        true
+    }
+
+    /// 損失が発散した最初のエポック。発散していなければ`None`です。
+    pub fn diverged_epoch(&self) -> Option<usize> {
+        self.diverged_epoch
+    }
+}
+
+/// 検証損失の停滞に基づく早期終了戦略。
+///
+/// Burn標準の`MetricEarlyStoppingStrategy`は改善幅の閾値を持たないため、
+/// `min_delta`に対応した独自の戦略を実装しています。
+/// トラッカーは共有されるため、学習後に最良のエポックを参照できます。
+///
+/// エポックの終わりに必ず呼び出されるため、ロガーを設定すると学習曲線の記録も行います。
+#[derive(Clone, Debug)]
+pub struct ValidLossEarlyStopping {
+    tracker: Arc<Mutex<PlateauTracker>>,
+    logger: Option<MetricLogger>,
+}
+
+impl ValidLossEarlyStopping {
+    /// 新しい早期終了戦略を作成します。
+    pub fn new(patience: usize, min_delta: f64) -> Self {
+        Self {
+            tracker: Arc::new(Mutex::new(PlateauTracker::new(patience, min_delta))),
+            logger: None,
+        }
+    }
+
+    /// エポックごとの損失を記録するロガーを設定します。
+    pub fn with_logger(mut self, logger: MetricLogger) -> Self {
+        self.logger = Some(logger);
+        self
+    }
+
+    /// 検証損失が最小となったエポック。
+    pub fn best_epoch(&self) -> Option<usize> {
+        self.tracker.lock().unwrap().best_epoch()
+    }
+
+    /// 最終エポックの検証損失。
+    pub fn final_loss(&self) -> Option<f64> {
+        self.tracker.lock().unwrap().last_loss()
+    }
+
+    /// 学習損失または検証損失が発散したエポック。
+    pub fn diverged_epoch(&self) -> Option<usize> {
+        self.tracker.lock().unwrap().diverged_epoch()
+    }
+}
+
+impl EarlyStoppingStrategy for ValidLossEarlyStopping {
+    fn should_stop(&mut self, epoch: usize, store: &EventStoreClient) -> bool {
+        let train_loss = store.find_metric("Loss", epoch, Aggregate::Mean, Split::Train);
+        let valid_loss = store.find_metric("Loss", epoch, Aggregate::Mean, Split::Valid);
+        if log_enabled!(Level::Debug) {
+            log_loss_breakdown(epoch, store);
+        }
+        if let Some(logger) = &self.logger {
+            // 記録に失敗しても学習は継続する
+            if let Err(err) = logger.log_epoch(epoch, train_loss, valid_loss) {
+                warn!("⚠️  Failed to write training log: {err}");
+            }
+        }
+        let mut tracker = self.tracker.lock().unwrap();
+        // 発散した場合は残りのエポックを無駄にしないよう、ただちに停止する
+        if train_loss.is_some_and(|loss| tracker.detect_divergence(epoch, loss)) {
+            return true;
+        }
+        match valid_loss {
+            Some(loss) => tracker.update(epoch, loss),
+            None => false,
+        }
+    }
+}
+
+/// エポックごとの損失の内訳 (重み適用前の各項の平均値) をdebugレベルで出力します。
+fn log_loss_breakdown(epoch: usize, store: &EventStoreClient) {
+    for split in [Split::Train, Split::Valid] {
+        let breakdown = LossComponentKind::ALL
+            .iter()
+            .filter_map(|kind| {
+                store
+                    .find_metric(kind.name(), epoch, Aggregate::Mean, split)
+                    .map(|value| format!("{}={value:.6}", kind.name()))
+            })
+            .collect::<Vec<_>>()
+            .join(", ");
+        debug!("📉 Epoch {epoch} ({split:?}): {breakdown}");
+    }
+}
+
+/// 学習に使用するオプティマイザの種類。
+///
+/// オプティマイザを選べるようになる前の`config.json`では`optimizer`にAdamの設定がそのまま
+/// 保存されているため、その形式は[`Optimizer::Adam`]として読み込みます。
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(from = "OptimizerRecord")]
+pub enum Optimizer {
+    /// Adam (デフォルト)。[`TrainingConfig::weight_decay`]でL2正則化を加えられます。
+    Adam,
+    /// 重み減衰を勾配の更新から分離したAdamW。
+    AdamW {
+        /// 重み減衰の係数 (Burnのデフォルトは`1e-4`)。
+        weight_decay: f32,
+    },
+    /// モメンタム付きの確率的勾配降下法。[`TrainingConfig::weight_decay`]でL2正則化を
+    /// 加えられます。
+    Sgd {
+        /// モメンタムの係数 (0以上1未満、0でモメンタムなし)。
+        momentum: f32,
+    },
+}
+
+/// 設定ファイルに保存された[`Optimizer`]の形式。
+#[derive(Deserialize)]
+#[serde(untagged)]
+enum OptimizerRecord {
+    /// 現在の形式 (`"Adam"`、`{"AdamW": {"weight_decay": ...}}`など)。
+    Kind(OptimizerKind),
+    /// 以前の形式 (Adamの設定)。
+    LegacyAdam(LegacyAdamConfig),
+}
+
+/// [`Optimizer`]と同じ形式で読み込むための型。
+#[derive(Deserialize)]
+enum OptimizerKind {
+    Adam,
+    AdamW { weight_decay: f32 },
+    Sgd { momentum: f32 },
+}
+
+/// 以前の`config.json`に保存されていたAdamの設定。
+///
+/// 実際の値はコマンドラインから変更できなかったため読み捨て、`beta_1`の有無だけで判別します。
+#[derive(Deserialize)]
+struct LegacyAdamConfig {
+    #[serde(rename = "beta_1")]
+    _beta_1: IgnoredAny,
+}
+
+impl From<OptimizerRecord> for Optimizer {
+    fn from(record: OptimizerRecord) -> Self {
+        match record {
+            OptimizerRecord::Kind(OptimizerKind::Adam) | OptimizerRecord::LegacyAdam(_) => {
+                Optimizer::Adam
+            }
+            OptimizerRecord::Kind(OptimizerKind::AdamW { weight_decay }) => {
+                Optimizer::AdamW { weight_decay }
+            }
+            OptimizerRecord::Kind(OptimizerKind::Sgd { momentum }) => Optimizer::Sgd { momentum },
+        }
+    }
+}
+
+impl FromStr for Optimizer {
+    type Err = String;
+
+    /// `adam`、`adamw:<weight_decay>`、`sgd:<momentum>` の形式を解析します。
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let parts: Vec<&str> = s.split(':').collect();
+        let float = |value: &str| {
+            value
+                .parse::<f32>()
+                .map_err(|_| format!("invalid number '{value}' in optimizer '{s}'"))
+        };
+        match parts.as_slice() {
+            ["adam"] => Ok(Optimizer::Adam),
+            ["adamw", weight_decay] => Ok(Optimizer::AdamW {
+                weight_decay: float(weight_decay)?,
+            }),
+            ["sgd", momentum] => Ok(Optimizer::Sgd {
+                momentum: float(momentum)?,
+            }),
+            _ => Err(format!(
+                "unknown optimizer '{s}'. Use adam, adamw:<weight_decay> or sgd:<momentum>"
+            )),
         }
     }
 }
 
+/// 学習率スケジュールの種類。
+///
+/// 学習率はイテレーション (ミニバッチ) ごとに更新され、各式はBurn標準の
+/// スケジューラと同じ定義に従います。`t`は0から始まるイテレーション番号です。
+#[derive(Config, Debug, PartialEq)]
+pub enum LrSchedule {
+    /// 一定の学習率。
+    Constant,
+    /// 指数減衰: `lr * gamma^t`。
+    Exponential {
+        /// 1イテレーションあたりの減衰率。
+        gamma: f64,
+    },
+    /// コサインアニーリング: 学習全体で`lr`から`min_lr`まで減衰します。
+    Cosine {
+        /// 最終的な学習率。
+        min_lr: f64,
+    },
+    /// ステップ減衰: `size`イテレーションごとに`gamma`倍します。
+    Step {
+        /// 学習率を更新する間隔 (イテレーション数)。
+        size: usize,
+        /// 更新ごとの減衰率。
+        gamma: f64,
+    },
+    /// ウォームアップ付きコサインアニーリング。
+    ///
+    /// 最初の`warmup_epochs`エポックで`lr * (t + 1) / w`と線形に`lr`まで上げ
+    /// (`w`はウォームアップのイテレーション数)、残りのイテレーションで`min_lr`まで
+    /// コサイン減衰します。学習初期の巨大な周波数損失による発散を防ぎます。
+    WarmupCosine {
+        /// ウォームアップのエポック数 (1エポックは[`TRAIN_BATCHES_PER_EPOCH`]イテレーション)。
+        warmup_epochs: usize,
+        /// 最終的な学習率。
+        min_lr: f64,
+    },
+}
+
+impl LrSchedule {
+    /// `iteration`回目の学習率を計算します。
+    ///
+    /// # Arguments
+    ///
+    /// * `initial_lr` - 初期学習率。
+    /// * `num_iters` - 学習全体のイテレーション数。
+    /// * `iteration` - 0から始まるイテレーション番号。
+    pub fn learning_rate(&self, initial_lr: f64, num_iters: usize, iteration: usize) -> f64 {
+        match self {
+            LrSchedule::Constant => initial_lr,
+            LrSchedule::Exponential { gamma } => initial_lr * gamma.powi(iteration as i32),
+            LrSchedule::Cosine { min_lr } => {
+                let progress = iteration.min(num_iters) as f64 / num_iters.max(1) as f64;
+                let cosine = (std::f64::consts::PI * progress).cos();
+                min_lr + 0.5 * (initial_lr - min_lr) * (1.0 + cosine)
+            }
+            LrSchedule::Step { size, gamma } => {
+                initial_lr * gamma.powi((iteration / (*size).max(1)) as i32)
+            }
+            LrSchedule::WarmupCosine {
+                warmup_epochs,
+                min_lr,
+            } => {
+                let warmup_iters = warmup_epochs * TRAIN_BATCHES_PER_EPOCH;
+                if iteration < warmup_iters {
+                    return initial_lr * (iteration + 1) as f64 / warmup_iters as f64;
+                }
+                // ウォームアップ後の残りのイテレーションでコサイン減衰する
+                LrSchedule::Cosine { min_lr: *min_lr }.learning_rate(
+                    initial_lr,
+                    num_iters.saturating_sub(warmup_iters),
+                    iteration - warmup_iters,
+                )
+            }
+        }
+    }
+
+    /// このスケジュールに従うスケジューラを作成します。
+    pub fn init(&self, initial_lr: f64, num_iters: usize) -> PinnLrScheduler {
+        PinnLrScheduler {
+            schedule: self.clone(),
+            initial_lr,
+            num_iters,
+            iteration: 0,
+        }
+    }
+}
+
+impl FromStr for LrSchedule {
+    type Err = String;
+
+    /// `constant`、`exponential:<gamma>`、`cosine:<min_lr>`、`step:<size>:<gamma>`、
+    /// `warmup-cosine:<warmup_epochs>:<min_lr>` の形式を解析します。
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let parts: Vec<&str> = s.split(':').collect();
+        let float = |value: &str| {
+            value
+                .parse::<f64>()
+                .map_err(|_| format!("invalid number '{value}' in lr schedule '{s}'"))
+        };
+        match parts.as_slice() {
+            ["constant"] => Ok(LrSchedule::Constant),
+            ["exponential", gamma] => Ok(LrSchedule::Exponential {
+                gamma: float(gamma)?,
+            }),
+            ["cosine", min_lr] => Ok(LrSchedule::Cosine {
+                min_lr: float(min_lr)?,
+            }),
+            ["step", size, gamma] => Ok(LrSchedule::Step {
+                size: size
+                    .parse()
+                    .map_err(|_| format!("invalid step size '{size}' in lr schedule '{s}'"))?,
+                gamma: float(gamma)?,
+            }),
+            ["warmup-cosine", warmup_epochs, min_lr] => Ok(LrSchedule::WarmupCosine {
+                warmup_epochs: warmup_epochs.parse().map_err(|_| {
+                    format!("invalid warmup epochs '{warmup_epochs}' in lr schedule '{s}'")
+                })?,
+                min_lr: float(min_lr)?,
+            }),
+            _ => Err(format!(
+                "unknown lr schedule '{s}'. Use constant, exponential:<gamma>, cosine:<min_lr>, step:<size>:<gamma> or warmup-cosine:<warmup_epochs>:<min_lr>"
+            )),
+        }
+    }
+}
+
+/// [`LrSchedule`]に従って学習率を返すスケジューラ。
+///
+/// 状態はイテレーション番号のみのため、チェックポイントにはその値を保存します。
+#[derive(Clone, Debug)]
+pub struct PinnLrScheduler {
+    schedule: LrSchedule,
+    initial_lr: f64,
+    num_iters: usize,
+    iteration: usize,
+}
+
+impl LrScheduler for PinnLrScheduler {
+    type Record<B: Backend> = usize;
+
+    fn step(&mut self) -> LearningRate {
+        let lr = self
+            .schedule
+            .learning_rate(self.initial_lr, self.num_iters, self.iteration);
+        self.iteration += 1;
+        lr
+    }
+
+    fn to_record<B: Backend>(&self) -> Self::Record<B> {
+        self.iteration
+    }
+
+    fn load_record<B: Backend>(mut self, record: Self::Record<B>) -> Self {
+        self.iteration = record;
+        self
+    }
+}
+
 /// 学習プロセス全体の設定を保持します。
 #[derive(Config)]
 pub struct TrainingConfig {
-    /// オプティマイザの設定。
-    pub optimizer: AdamConfig,
+    /// オプティマイザの種類 ([`Optimizer`]を参照)。
+    #[config(default = "Optimizer::Adam")]
+    pub optimizer: Optimizer,
+    /// 学習済みモデルやチェックポイントを保存するディレクトリ。
+    #[config(default = "DEFAULT_ARTIFACT_DIR.to_string()")]
+    pub artifact_dir: String,
+    /// モデルとチェックポイントの保存形式。
+    #[config(default = "RecordFormat::NamedMpk")]
+    pub record_format: RecordFormat,
     /// 学習率。
     #[config(default = 1e-4)]
     pub learning_rate: f64,
+    /// 学習率スケジュール。
+    #[config(default = "LrSchedule::Constant")]
+    pub lr_schedule: LrSchedule,
     /// 学習エポック数。
     #[config(default = 10000)]
     pub num_epochs: usize,
     /// バッチサイズ。
     #[config(default = 16384)]
     pub batch_size: usize,
+    /// `ratio_penalty`（プロング長 > 柄長）に対する重み。
+    #[config(default = "PENALTY_WEIGHT_RATIO")]
+    pub penalty_weight_ratio: f32,
+    /// `range_penalty`（プロングの直径・長さの範囲）に対する重み。
+    #[config(default = "PENALTY_WEIGHT_RANGE")]
+    pub penalty_weight_range: f32,
+    /// その他の寸法の範囲ペナルティに対する重み。
+    #[config(default = "PENALTY_WEIGHT_OTHER")]
+    pub penalty_weight_other: f32,
+    /// 寸法ごとの範囲ペナルティに対する重み ([`LossConfig::dimension_weights`]を参照)。
+    #[config(default = "[1.0; model_dims::NUM_DIMS]")]
+    pub dimension_weights: [f32; model_dims::NUM_DIMS],
+    /// 自己一貫性項に対する重み ([`LossConfig::consistency_weight`]を参照)。
+    #[config(default = 0.0)]
+    pub consistency_weight: f32,
+    /// 倍音の周波数損失に対する重み ([`LossConfig::overtone_weight`]を参照)。0の場合は無効です。
+    #[config(default = 0.0)]
+    pub overtone_weight: f32,
+    /// 基本周波数に対する倍音の周波数の目標比 ([`LossConfig::overtone_ratio`]を参照)。
+    #[config(default = "OVERTONE_RATIO")]
+    pub overtone_ratio: f32,
+    /// 寸法に加えて音叉のQ値 (`log10(Q)`) を予測するかどうか ([`ModelConfig::q_factor`]を参照)。
+    #[config(default = false)]
+    pub predict_q_factor: bool,
+    /// Q値の損失に対する重み ([`LossConfig::q_factor_weight`]を参照)。
+    /// `predict_q_factor`が有効な場合にのみ使用します。
+    #[config(default = 1.0)]
+    pub q_factor_weight: f32,
+    /// 周波数損失を目標周波数で正規化するかどうか ([`LossConfig::normalize`]を参照)。
+    #[config(default = true)]
+    pub normalize_frequency_loss: bool,
+    /// 周波数の残差に適用する損失関数 ([`LossConfig::frequency_loss`]を参照)。
+    #[config(default = "FrequencyLossKind::Squared")]
+    pub frequency_loss: FrequencyLossKind,
+    /// 音叉の材質。
+    #[config(default = "Material::Steel304")]
+    pub material: Material,
+    /// すべての材質 ([`Material::ALL`]) で1つのモデルを学習し、材質をモデルの入力に加えるか
+    /// どうか ([`ModelConfig::material_input`]を参照)。
+    ///
+    /// 有効な場合、ミニバッチごとに材質を順に切り替え、周波数損失にはそのバッチの材質を
+    /// 使用します。`material`は推論時の既定の材質と、検証 (検証損失と周波数誤差のメトリクス)
+    /// の材質にのみ使われます。
+    #[config(default = false)]
+    pub material_input: bool,
+    /// プロングの断面形状。モデルの出力次元数もこれに合わせて決まります。
+    #[config(default = "CrossSectionKind::Circular")]
+    pub cross_section: CrossSectionKind,
+    /// 音叉を調律する曲げ振動モードの次数 (1〜3)。
+    #[config(default = 1)]
+    pub mode: u8,
+    /// Timoshenko補正を適用するかどうか ([`PhysicsConfig::timoshenko`]を参照)。
+    #[config(default = false)]
+    pub timoshenko: bool,
+    /// プロングの先端と付け根の断面寸法の比 ([`PhysicsConfig::taper_ratio`]を参照)。
+    /// 1.0の場合は一様な梁です。
+    #[config(default = 1.0)]
+    pub taper_ratio: f32,
+    /// 音叉の温度 (℃) ([`PhysicsConfig::temperature`]を参照)。
+    #[config(default = "REFERENCE_TEMPERATURE")]
+    pub temperature: f32,
+    /// 温度をモデルの入力に加え、`train_temp_min`〜`train_temp_max`の温度で学習するかどうか
+    /// ([`ModelConfig::temperature_input`]を参照)。
+    ///
+    /// 有効な場合、ミニバッチごとに温度を範囲内で切り替え、周波数損失にはそのバッチの温度での
+    /// ヤング率を使用します。`temperature`は推論時の既定の温度と、検証時の温度として
+    /// 使われます。
+    #[config(default = false)]
+    pub temperature_input: bool,
+    /// 学習する温度範囲の下限 (℃)。`temperature_input`が有効な場合にのみ使用します。
+    #[config(default = -10.0)]
+    pub train_temp_min: f32,
+    /// 学習する温度範囲の上限 (℃)。`temperature_input`が有効な場合にのみ使用します。
+    #[config(default = 50.0)]
+    pub train_temp_max: f32,
+    /// 学習用の周波数範囲の下限 (Hz)。
+    ///
+    /// 検証データはこの範囲から取り置いたビンで生成されます ([`FrequencySplit`]を参照)。
+    #[config(default = 200.0)]
+    pub train_freq_min: f32,
+    /// 学習用の周波数範囲の上限 (Hz)。
+    #[config(default = 1800.0)]
+    pub train_freq_max: f32,
+    /// 学習用の周波数の範囲内での分布 ([`SamplingMode`]を参照)。
+    #[config(default = "SamplingMode::Uniform")]
+    pub sampling: SamplingMode,
+    /// 乱数のシード。
+    ///
+    /// データの生成に加え、バックエンドの乱数 (重みの初期化・ドロップアウトなど) の初期化にも
+    /// 使用します。
+    #[config(default = 42)]
+    pub seed: u64,
+    /// 学習用・検証用のデータローダーのワーカー数。
+    ///
+    /// 0の場合はワーカーのスレッドを使わず、学習ループと同じスレッドでバッチを同期的に
+    /// 生成します (シングルコアのCIなど)。複数のワーカーではバッチが処理される順序が
+    /// 実行ごとに変わります。同じシードでバイト単位で同一のモデルを得るには0か1を指定します。
+    #[config(default = 4)]
+    pub num_workers: usize,
+    /// 検証損失が改善しないまま許容するエポック数。これを超えると学習を早期終了します。
+    #[config(default = 100)]
+    pub patience: usize,
+    /// 改善とみなす検証損失の最小減少量。
+    #[config(default = 0.0)]
+    pub min_delta: f64,
+    /// 物理的制約のペナルティの重みを段階的に上げるエポック数。0の場合は最初から全重みを使います。
+    ///
+    /// 学習初期は周波数損失を優先し、[`penalty_warmup_factor`]に従って重みを上げて
+    /// `penalty_warmup_epochs`エポック目に設定値に達します。検証損失は比較できるよう常に
+    /// 設定値の重みで計算します。
+    #[config(default = 0)]
+    pub penalty_warmup_epochs: usize,
+    /// 隠れ層のユニット数 ([`ModelConfig::hidden_size`]を参照)。
+    #[config(default = 128)]
+    pub hidden_size: usize,
+    /// 隠れ層の数 ([`ModelConfig::num_hidden_layers`]を参照)。
+    #[config(default = 3)]
+    pub num_hidden_layers: usize,
+    /// 隠れ層の活性化関数 ([`ModelConfig::activation`]を参照)。
+    #[config(default = "Activation::Relu")]
+    pub activation: Activation,
+    /// 全結合層の重みの初期化方法 ([`ModelConfig::init_kind`]を参照)。
+    #[config(default = "InitKind::Default")]
+    pub init_kind: InitKind,
+    /// 各隠れ層にバッチ正規化を挿入するかどうか ([`ModelConfig::batch_norm`]を参照)。
+    #[config(default = false)]
+    pub batch_norm: bool,
+    /// 2層目以降の隠れ層に残差接続を加えるかどうか ([`ModelConfig::residual`]を参照)。
+    #[config(default = false)]
+    pub residual: bool,
+    /// 隠れ層のドロップアウトの確率 ([`ModelConfig::dropout`]を参照)。
+    #[config(default = 0.0)]
+    pub dropout: f64,
+    /// 出力層の`softplus`の鋭さ (β、[`ModelConfig::softplus_beta`]を参照)。
+    #[config(default = 1.0)]
+    pub softplus_beta: f64,
+    /// 勾配ノルムの上限。`None`の場合はクリッピングしません。
+    ///
+    /// 学習初期は周波数損失が非常に大きく勾配が爆発しやすいため、クリッピングで
+    /// 1ステップの更新量を抑えられます。Adamは勾配をスケール正規化するため、
+    /// クリッピング後も実際の更新幅は主に学習率で決まります。発散する場合は
+    /// クリッピングと併せて学習率を下げてください。
+    pub grad_clip: Option<f32>,
+    /// L2正則化 (重み減衰) の係数。`None`の場合は正則化しません。
+    ///
+    /// 勾配に`weight_decay * 重み`を加える結合型の重み減衰で、`Adam`と`Sgd`に適用されます。
+    /// ペナルティ項の境界付近に過度に適合するのを抑えられます。推奨値は`1e-5`〜`1e-4`程度で、
+    /// 大きすぎると周波数損失が十分に下がらなくなります。`AdamW`では使用できないため、
+    /// [`Optimizer::AdamW`]の`weight_decay`を指定してください。
+    pub weight_decay: Option<f32>,
+    /// 隠れ層 (全結合層とバッチ正規化) を凍結し、出力層だけを学習するかどうか。
+    ///
+    /// 別の材質に合わせて学習済みモデルをファインチューニングする場合に、`resume_path`と
+    /// 組み合わせて使用します。隠れ層のパラメータは勾配の計算対象から外すため
+    /// ([`TuningForkPINN::freeze_hidden_layers`])、オプティマイザは出力層だけを更新します。
+    /// バッチ正規化の移動平均 (学習対象のパラメータではない統計量) は引き続き更新されます。
+    #[config(default = false)]
+    pub freeze_hidden: bool,
+    /// 出力層を物理モデルの解析解に近い寸法から始めるかどうか ([`warm_start`]を参照)。
+    ///
+    /// `resume_path`から学習を再開する場合は無視します。
+    #[config(default = false)]
+    pub warm_start: bool,
+    /// 学習後の評価 ([`evaluate_tolerance`]) で許容する周波数の誤差 (Hz)。
+    #[config(default = 1.0)]
+    pub tolerance_hz: f32,
+}
+
+impl TrainingConfig {
+    /// 勾配クリッピングと重み減衰を反映したAdamの設定を返します。
+    pub fn adam_config(&self) -> AdamConfig {
+        AdamConfig::new()
+            .with_grad_clipping(self.grad_clipping())
+            .with_weight_decay(self.weight_decay.map(WeightDecayConfig::new))
+    }
+
+    /// 勾配クリッピングを反映したAdamWの設定を返します。
+    pub fn adamw_config(&self, weight_decay: f32) -> AdamWConfig {
+        AdamWConfig::new()
+            .with_weight_decay(weight_decay)
+            .with_grad_clipping(self.grad_clipping())
+    }
+
+    /// 勾配クリッピングと重み減衰を反映したSGDの設定を返します。
+    ///
+    /// モメンタムが0の場合は、モメンタムなしのSGDになります。
+    pub fn sgd_config(&self, momentum: f32) -> SgdConfig {
+        let momentum = (momentum > 0.0).then(|| {
+            MomentumConfig::new()
+                .with_momentum(f64::from(momentum))
+                .with_dampening(0.0)
+        });
+        SgdConfig::new()
+            .with_momentum(momentum)
+            .with_weight_decay(self.weight_decay.map(WeightDecayConfig::new))
+            .with_gradient_clipping(self.grad_clipping())
+    }
+
+    /// 勾配クリッピングの設定。
+    fn grad_clipping(&self) -> Option<GradientClippingConfig> {
+        self.grad_clip.map(GradientClippingConfig::Norm)
+    }
+
+    /// 設定値の整合性を検証します。
+    ///
+    /// # Errors
+    ///
+    /// エポック数・バッチサイズが0、学習率が正でない、周波数範囲が正でない、下限が上限以上、
+    /// Huber損失の閾値が正でない、オプティマイザや倍音の設定が不正、または振動モードが
+    /// 対応範囲外の場合に`TrainError::InvalidConfig`を返します。
+    pub fn validate(&self) -> Result<(), TrainError> {
+        if self.num_epochs == 0 || self.batch_size == 0 {
+            return Err(TrainError::InvalidConfig(format!(
+                "num_epochs and batch_size must be at least 1, got ({}, {})",
+                self.num_epochs, self.batch_size
+            )));
+        }
+        if self.tolerance_hz.is_nan() || self.tolerance_hz < 0.0 {
+            return Err(TrainError::InvalidConfig(format!(
+                "tolerance_hz must be non-negative, got {}",
+                self.tolerance_hz
+            )));
+        }
+        if self.learning_rate.is_nan() || self.learning_rate <= 0.0 {
+            return Err(TrainError::InvalidConfig(format!(
+                "learning_rate must be positive, got {}",
+                self.learning_rate
+            )));
+        }
+        let (min, max) = (self.train_freq_min, self.train_freq_max);
+        if min.is_nan() || max.is_nan() || min <= 0.0 || max <= 0.0 {
+            return Err(TrainError::InvalidConfig(format!(
+                "frequency range must be positive, got ({min}, {max})"
+            )));
+        }
+        if min >= max {
+            return Err(TrainError::InvalidConfig(format!(
+                "frequency min must be less than max, got ({min}, {max})"
+            )));
+        }
+        if let Some(max_norm) = self.grad_clip
+            && (max_norm.is_nan() || max_norm <= 0.0)
+        {
+            return Err(TrainError::InvalidConfig(format!(
+                "grad_clip must be positive, got {max_norm}"
+            )));
+        }
+        if let Some(penalty) = self.weight_decay
+            && (penalty.is_nan() || penalty < 0.0)
+        {
+            return Err(TrainError::InvalidConfig(format!(
+                "weight_decay must be non-negative, got {penalty}"
+            )));
+        }
+        match self.optimizer {
+            Optimizer::Adam => {}
+            Optimizer::AdamW { weight_decay } => {
+                if weight_decay.is_nan() || weight_decay < 0.0 {
+                    return Err(TrainError::InvalidConfig(format!(
+                        "adamw weight_decay must be non-negative, got {weight_decay}"
+                    )));
+                }
+                if self.weight_decay.is_some() {
+                    return Err(TrainError::InvalidConfig(
+                        "weight_decay cannot be combined with adamw; use adamw:<weight_decay>"
+                            .to_string(),
+                    ));
+                }
+            }
+            Optimizer::Sgd { momentum } => {
+                if !(0.0..1.0).contains(&momentum) {
+                    return Err(TrainError::InvalidConfig(format!(
+                        "sgd momentum must be in [0, 1), got {momentum}"
+                    )));
+                }
+            }
+        }
+        if self.overtone_weight.is_nan() || self.overtone_weight < 0.0 {
+            return Err(TrainError::InvalidConfig(format!(
+                "overtone_weight must be non-negative, got {}",
+                self.overtone_weight
+            )));
+        }
+        if self.overtone_weight > 0.0 {
+            if self.overtone_ratio.is_nan() || self.overtone_ratio <= 0.0 {
+                return Err(TrainError::InvalidConfig(format!(
+                    "overtone_ratio must be positive, got {}",
+                    self.overtone_ratio
+                )));
+            }
+            // 倍音には1つ上のモードを使うため、そのモードにも対応している必要がある
+            PhysicsConfig::new()
+                .with_mode(self.mode.saturating_add(1))
+                .validate()
+                .map_err(|err| TrainError::InvalidConfig(format!("overtone: {err}")))?;
+        }
+        if self
+            .dimension_weights
+            .iter()
+            .any(|weight| !weight.is_finite() || *weight < 0.0)
+        {
+            return Err(TrainError::InvalidConfig(format!(
+                "dimension_weights must be non-negative, got {:?}",
+                self.dimension_weights
+            )));
+        }
+        if self.predict_q_factor && (self.q_factor_weight.is_nan() || self.q_factor_weight < 0.0)
+        {
+            return Err(TrainError::InvalidConfig(format!(
+                "q_factor_weight must be non-negative, got {}",
+                self.q_factor_weight
+            )));
+        }
+        if self.consistency_weight.is_nan() || self.consistency_weight < 0.0 {
+            return Err(TrainError::InvalidConfig(format!(
+                "consistency_weight must be non-negative, got {}",
+                self.consistency_weight
+            )));
+        }
+        if self.hidden_size == 0 || self.num_hidden_layers == 0 {
+            return Err(TrainError::InvalidConfig(format!(
+                "hidden_size and num_hidden_layers must be at least 1, got {} and {}",
+                self.hidden_size, self.num_hidden_layers
+            )));
+        }
+        if !(0.0..1.0).contains(&self.dropout) {
+            return Err(TrainError::InvalidConfig(format!(
+                "dropout must be in [0, 1), got {}",
+                self.dropout
+            )));
+        }
+        if !(self.softplus_beta.is_finite() && self.softplus_beta > 0.0) {
+            return Err(TrainError::InvalidConfig(format!(
+                "softplus_beta must be a positive finite number, got {}",
+                self.softplus_beta
+            )));
+        }
+        if let FrequencyLossKind::Huber { delta } = self.frequency_loss
+            && (delta.is_nan() || delta <= 0.0)
+        {
+            return Err(TrainError::InvalidConfig(format!(
+                "huber delta must be positive, got {delta}"
+            )));
+        }
+        if self.temperature_input {
+            let (min, max) = (self.train_temp_min, self.train_temp_max);
+            if !min.is_finite() || !max.is_finite() || min >= max {
+                return Err(TrainError::InvalidConfig(format!(
+                    "temperature min must be less than max, got ({min}, {max})"
+                )));
+            }
+            // 学習する温度範囲の両端で、すべての材質のヤング率が正である必要がある
+            let materials = if self.material_input {
+                Material::ALL.to_vec()
+            } else {
+                vec![self.material]
+            };
+            for material in materials {
+                for temperature in [min, max] {
+                    PhysicsConfig::new()
+                        .with_material(material)
+                        .with_temperature(temperature)
+                        .validate()
+                        .map_err(TrainError::InvalidConfig)?;
+                }
+            }
+        }
+        self.loss_config()
+            .physics
+            .validate()
+            .map_err(TrainError::InvalidConfig)
+    }
+
+    /// 学習・検証ステップで使用する損失設定を構築します。
+    pub fn loss_config(&self) -> LossConfig {
+        LossConfig::new()
+            .with_normalize(self.normalize_frequency_loss)
+            .with_frequency_loss(self.frequency_loss.clone())
+            .with_physics(
+                PhysicsConfig::new()
+                    .with_material(self.material)
+                    .with_cross_section(self.cross_section)
+                    .with_mode(self.mode)
+                    .with_timoshenko(self.timoshenko)
+                    .with_taper_ratio(self.taper_ratio)
+                    .with_temperature(self.temperature),
+            )
+            .with_penalty_weight_ratio(self.penalty_weight_ratio)
+            .with_penalty_weight_range(self.penalty_weight_range)
+            .with_penalty_weight_other(self.penalty_weight_other)
+            .with_dimension_weights(self.dimension_weights)
+            .with_consistency_weight(self.consistency_weight)
+            .with_overtone_weight(self.overtone_weight)
+            .with_overtone_ratio(self.overtone_ratio)
+            .with_q_factor_weight(if self.predict_q_factor {
+                self.q_factor_weight
+            } else {
+                0.0
+            })
+    }
+
+    /// 温度を入力とする場合 (`temperature_input`) に、学習する温度範囲 (℃) を返します。
+    pub fn temperature_range(&self) -> Option<(f32, f32)> {
+        self.temperature_input.then_some((self.train_temp_min, self.train_temp_max))
+    }
+
+    /// 断面形状に合わせた出力次元数のモデル設定を構築します。
+    pub fn model_config(&self) -> ModelConfig {
+        // 学習データの周波数は一様分布なので、その平均と標準偏差で入力を正規化する
+        let (min, max) = (self.train_freq_min, self.train_freq_max);
+        ModelConfig::new()
+            .with_freq_mean((min + max) / 2.0)
+            .with_freq_std((max - min) / 12f32.sqrt())
+            .with_output_size(self.cross_section.num_dims())
+            .with_q_factor(self.predict_q_factor)
+            .with_material_input(self.material_input)
+            .with_temperature_input(self.temperature_input)
+            .with_hidden_size(self.hidden_size)
+            .with_num_hidden_layers(self.num_hidden_layers)
+            .with_activation(self.activation.clone())
+            .with_init_kind(self.init_kind.clone())
+            .with_batch_norm(self.batch_norm)
+            .with_residual(self.residual)
+            .with_dropout(self.dropout)
+            .with_softplus_beta(self.softplus_beta)
+    }
+}
+
+/// 学習の開始点となるモデルを初期化します。
+///
+/// `resume_path`が指定された場合は、そのモデルレコードの重みから学習を再開します。
+/// 指定されなければランダムに初期化します。レコードの形式は拡張子から判定し、
+/// 拡張子がない場合は`.mpk`とみなします。
+///
+/// # Errors
+///
+/// モデルレコードの読み込みに失敗した場合に`TrainError::Load`を返します。
+pub fn init_model<B: Backend>(
+    model_config: &ModelConfig,
+    resume_path: Option<&str>,
+    device: &B::Device,
+) -> Result<TuningForkPINN<B>, TrainError> {
+    let model = model_config.init::<B>(device);
+    let Some(path) = resume_path else {
+        return Ok(model);
+    };
+
+    let format = RecordFormat::from_path(path).unwrap_or(RecordFormat::NamedMpk);
+    let record = record::load_record::<B, _>(format, path.into(), device)
+        .map_err(|err| TrainError::Load(format!("{path}: {err}")))?;
+    Ok(model.load_record(record))
+}
+
+/// 物理モデルに基づく初期化 ([`warm_start`]) で、出力層の当てはめに使用する周波数の数。
+pub const WARM_START_SAMPLES: usize = 64;
+
+/// 物理モデルに基づく初期化 ([`warm_start`]) で、出力層の当てはめに使用するリッジ回帰の
+/// 正則化の強さ (特徴量の2乗和の平均に対する比)。
+pub const WARM_START_RIDGE: f64 = 1e-3;
+
+/// 出力層を、学習範囲の周波数に対する寸法の解析解 ([`analytic_dimensions`]) に近い値を
+/// 出力するよう初期化します (`TrainingConfig::warm_start`)。
+///
+/// 学習範囲を対数等間隔に[`WARM_START_SAMPLES`]点で標本化し、各周波数の解析解を目標として
+/// 出力層の重みとバイアスを最小二乗法で当てはめます ([`TuningForkPINN::fit_output_layer`])。
+/// 隠れ層は初期値のままなので、初期の予測は範囲の中ほどの周波数で解析解に近くなります。
+/// 解析解はプロングの長さ以外を範囲の中央に固定した大まかな値で、最適な寸法ではありません。
+/// 学習の初期のエポックを安定させるための経験的な工夫として使用してください。
+/// 矩形断面では、プロングの厚さの目標を幅 (円形断面の直径) と同じにします。
+///
+/// # Errors
+///
+/// 隠れ層の出力が退化していて出力層を当てはめられない場合に`TrainError::InvalidConfig`を
+/// 返します。
+pub fn warm_start<B: Backend>(
+    model: TuningForkPINN<B>,
+    config: &TrainingConfig,
+    device: &B::Device,
+) -> Result<TuningForkPINN<B>, TrainError> {
+    let physics = config.loss_config().physics;
+    let (log_min, log_max) = (config.train_freq_min.ln(), config.train_freq_max.ln());
+    let freqs: Vec<f32> = (0..WARM_START_SAMPLES)
+        .map(|i| {
+            let t = i as f32 / (WARM_START_SAMPLES - 1) as f32;
+            (log_min + t * (log_max - log_min)).exp()
+        })
+        .collect();
+    let targets: Vec<Vec<f32>> = freqs
+        .iter()
+        .map(|&freq| {
+            let dims = analytic_dimensions(freq, &physics);
+            let mut target = dims.to_array().to_vec();
+            if config.cross_section == CrossSectionKind::Rectangular {
+                target.push(dims.prong_diameter);
+            }
+            target
+        })
+        .collect();
+
+    let input = Tensor::<B, 1>::from_floats(freqs.as_slice(), device).reshape([-1, 1]);
+    let input = model.with_conditions(input, &physics);
+    model
+        .fit_output_layer(input, &targets, WARM_START_RIDGE)
+        .map_err(|err| TrainError::InvalidConfig(format!("cannot warm-start the model: {err}")))
+}
+
+/// 学習後の評価 ([`evaluate_tolerance`]) で学習範囲に等間隔に並べる周波数の数。
+pub const TOLERANCE_GRID_SIZE: usize = 200;
+
+/// 学習済みモデルを、学習に使用しない検証用のビン ([`FrequencySplit::Valid`]) の周波数で
+/// 評価します。
+///
+/// 学習範囲に等間隔に並べた[`TOLERANCE_GRID_SIZE`]点のうち検証用のビンに属する周波数について、
+/// 予測寸法から物理モデル ([`predicted_frequency_with_config`]) で周波数を逆算し、目標との誤差を
+/// `config.tolerance_hz`と比較します。
+pub fn evaluate_tolerance<B: Backend>(
+    model: &TuningForkPINN<B>,
+    config: &TrainingConfig,
+    device: &B::Device,
+) -> ToleranceReport {
+    let freq_range = (config.train_freq_min, config.train_freq_max);
+    let grid = GridFrequencyDataset {
+        size: TOLERANCE_GRID_SIZE,
+        freq_min: freq_range.0,
+        freq_max: freq_range.1,
+    };
+    let freqs: Vec<f32> = grid
+        .iter()
+        .filter(|&freq| FrequencySplit::Valid.contains_bin(frequency_bin(freq_range, freq)))
+        .collect();
+    let physics = config.loss_config().physics;
+    let input = Tensor::<B, 1>::from_floats(freqs.as_slice(), device).reshape([-1, 1]);
+    let dims = model.forward(model.with_conditions(input, &physics));
+    let realized: Vec<f32> = predicted_frequency_with_config(dims, &physics)
+        .into_data()
+        .convert::<f32>()
+        .into_vec()
+        .unwrap();
+    let errors: Vec<f32> = realized
+        .iter()
+        .zip(&freqs)
+        .map(|(realized, target)| realized - target)
+        .collect();
+    ToleranceReport::from_errors(&errors, config.tolerance_hz)
+}
+
+/// 学習の開始点となるモデルを初期化し、新規に学習する場合は設定に応じて[`warm_start`]を
+/// 適用します。
+fn init_training_model<B: Backend>(
+    config: &TrainingConfig,
+    resume_path: Option<&str>,
+    device: &B::Device,
+) -> Result<TuningForkPINN<B>, TrainError> {
+    let mut model = init_model::<B>(&config.model_config(), resume_path, device)?;
+    if config.warm_start && resume_path.is_none() {
+        info!("🔥 Warm-starting the output layer from the analytic dimensions");
+        model = warm_start(model, config, device)?;
+    }
+    if config.freeze_hidden {
+        model = model.freeze_hidden_layers();
+    }
+    Ok(model)
+}
+
+/// 検証損失が最小だった`best_epoch`のチェックポイントのパスを返します。
+///
+/// チェックポイントは`Learner`が早期終了戦略 ([`ValidLossEarlyStopping`]) に渡すのと同じ
+/// エポック番号で`{artifact_dir}/checkpoint/model-{epoch}`に保存されます。
+///
+/// # Errors
+///
+/// チェックポイントが存在しない (番号が一致しない、または削除された) 場合に
+/// `TrainError::Save`を返します。別のエポックのモデルを最良として保存しないよう、
+/// 他のチェックポイントで代用はしません。
+pub fn best_checkpoint_path(
+    artifact_dir: &str,
+    best_epoch: usize,
+    format: RecordFormat,
+) -> Result<String, TrainError> {
+    let path = format!("{artifact_dir}/checkpoint/model-{best_epoch}.{}", format.extension());
+    if !Path::new(&path).is_file() {
+        return Err(TrainError::Save(format!(
+            "checkpoint of the best epoch {best_epoch} not found at '{path}'"
+        )));
+    }
+    Ok(path)
+}
+
+/// 設定から学習用のデータセットを構築します。
+fn train_dataset(config: &TrainingConfig) -> TuningForkDataset {
+    TuningForkDataset {
+        size: config.batch_size * TRAIN_BATCHES_PER_EPOCH,
+        freq_range: (config.train_freq_min, config.train_freq_max),
+        split: FrequencySplit::Train,
+        sampling: config.sampling,
+        seed: config.seed,
+    }
+}
+
+/// 1エポックあたりの学習バッチ数を返します (最後の端数のバッチを含む)。
+fn batches_per_epoch<D: Dataset<f32>>(dataset: &D, batch_size: usize) -> usize {
+    dataset.len().div_ceil(batch_size).max(1)
+}
+
+/// 学習用・検証用のデータローダーを構築します。
+fn build_dataloaders<B: AutodiffBackend, D: Dataset<f32> + 'static>(
+    config: &TrainingConfig,
+    dataset_train: D,
+    device: &B::Device,
+) -> (TrainDataLoader<B>, ValidDataLoader<B>) {
+    // 学習用データローダー
+    let train_batches = batches_per_epoch(&dataset_train, config.batch_size);
+    let batcher_train = TuningForkBatcher::<B>::new(device.clone())
+        .with_loss_config(config.loss_config())
+        .with_penalty_warmup(config.penalty_warmup_epochs, train_batches)
+        .with_material_cycle(config.material_input)
+        .with_temperature_range(config.temperature_range());
+    let mut builder_train = DataLoaderBuilder::new(batcher_train).batch_size(config.batch_size);
+    // ワーカー数を指定しない場合、バッチは学習ループのスレッドで同期的に生成される
+    if config.num_workers > 0 {
+        builder_train = builder_train.num_workers(config.num_workers);
+    }
+    let dataloader_train = builder_train.build(dataset_train);
+
+    // 検証用データローダー
+    // 学習範囲全体を等間隔のグリッドで評価する。グリッドは学習から取り置いたビンも含むため、
+    // 範囲内の汎化性能をエポック間で比較可能な形で測定できる。材質と温度は乱数やワーカー間で
+    // 共有されるカウンタで切り替えると検証損失が定まらないため、設定の材質・温度に固定する。
+    let batcher_valid = TuningForkBatcher::<B::InnerBackend>::new(device.clone())
+        .with_loss_config(config.loss_config());
+    let mut builder_valid = DataLoaderBuilder::new(batcher_valid).batch_size(config.batch_size);
+    if config.num_workers > 0 {
+        builder_valid = builder_valid.num_workers(config.num_workers);
+    }
+    let dataloader_valid = builder_valid.build(GridFrequencyDataset {
+        size: config.batch_size * VALID_BATCHES,
+        freq_min: config.train_freq_min,
+        freq_max: config.train_freq_max,
+    });
+
+    (dataloader_train, dataloader_valid)
 }
 
 /// 学習プロセスを実行します。
@@ -132,54 +1736,264 @@ pub struct TrainingConfig {
 ///
 /// # Arguments
 ///
+/// * `config` - 学習設定。
+/// * `resume_path` - 学習を再開するモデルレコードのパス。`None`の場合は新規に学習します。
 /// * `device` - 学習に使用するデバイス。
-pub fn run<B: AutodiffBackend>(device: B::Device)
+///
+/// # Errors
+///
+/// 設定が不正な場合、またはモデルの読み込み・保存に失敗した場合に`TrainError`を返します。
+pub fn run<B: AutodiffBackend>(
+    config: TrainingConfig,
+    resume_path: Option<String>,
+    device: B::Device,
+) -> Result<(), TrainError>
 where
     B::InnerBackend: Backend,
 {
-    let config = TrainingConfig::new(AdamConfig::new());
-    let artifact_dir = "./artifacts";
+    let dataset = train_dataset(&config);
+    run_with_dataset::<B, _>(config, resume_path, dataset, device)
+}
 
-    // 学習用データローダー
-    let batcher_train = TuningForkBatcher::<B>::new(device.clone());
-    let dataloader_train = DataLoaderBuilder::new(batcher_train)
-        .batch_size(config.batch_size)
-        .num_workers(64)
-        .build(TuningForkDataset {
-            size: config.batch_size * 100,
-            freq_range: (200.0, 1800.0), // 学習用の周波数範囲
-        });
+/// 指定した学習用データセットで学習プロセスを実行します。
+///
+/// [`run`]と同じですが、設定から生成する[`TuningForkDataset`]の代わりに`dataset_train`の
+/// 周波数で学習します。1エポックは`dataset_train`を一巡するまでです。
+/// 検証には[`run`]と同じく学習範囲全体のグリッドを使用します。
+///
+/// # Errors
+///
+/// 設定が不正な場合、またはモデルの読み込み・保存に失敗した場合に`TrainError`を返します。
+pub fn run_with_dataset<B: AutodiffBackend, D: Dataset<f32> + 'static>(
+    config: TrainingConfig,
+    resume_path: Option<String>,
+    dataset_train: D,
+    device: B::Device,
+) -> Result<(), TrainError>
+where
+    B::InnerBackend: Backend,
+{
+    config.validate()?;
+    if dataset_train.is_empty() {
+        return Err(TrainError::InvalidConfig("training dataset must not be empty".to_string()));
+    }
+    // 重みの初期化やドロップアウトも再現できるよう、バックエンドの乱数をシードで初期化する
+    B::seed(&device, config.seed);
+    let model = init_training_model::<B>(&config, resume_path.as_deref(), &device)?;
+    if let Some(path) = &resume_path {
+        info!("🔁 Resuming training from '{path}'");
+    }
+    if config.freeze_hidden {
+        if resume_path.is_none() {
+            warn!(
+                "Hidden layers are frozen at their random initialization; \
+                 use --resume to fine-tune a trained model"
+            );
+        }
+        info!("🧊 Hidden layers are frozen; only the output layer is trained");
+    }
+    info!("{}", model.summary());
+    let artifact_dir = config.artifact_dir.as_str();
 
-    // 検証用データローダー
-    let batcher_valid = TuningForkBatcher::<B::InnerBackend>::new(device.clone());
-    let dataloader_valid = DataLoaderBuilder::new(batcher_valid)
-        .batch_size(config.batch_size)
-        .num_workers(64)
-        .build(TuningForkDataset {
-            size: config.batch_size * 20,
-            freq_range: (1800.0, 2000.0), // 検証用の周波数範囲
-        });
+    // 推論時に同じ物理モデルを再構築できるよう、学習設定をモデルと一緒に保存する
+    fs::create_dir_all(artifact_dir)
+        .map_err(|err| TrainError::Save(format!("{artifact_dir}: {err}")))?;
+    config
+        .save(format!("{artifact_dir}/{TRAINING_CONFIG_FILE}"))
+        .map_err(|err| TrainError::Save(format!("{TRAINING_CONFIG_FILE}: {err}")))?;
+
+    let train_batches = batches_per_epoch(&dataset_train, config.batch_size);
+    let (dataloader_train, dataloader_valid) =
+        build_dataloaders::<B, _>(&config, dataset_train, &device);
 
-    let scheduler = ConstantLr::new(config.learning_rate);
+    let num_iters = config.num_epochs * train_batches;
+    let scheduler = config.lr_schedule.init(config.learning_rate, num_iters);
+    let logger = MetricLogger::create(format!("{artifact_dir}/{TRAINING_LOG_FILE}"))
+        .map_err(|err| TrainError::Save(format!("{TRAINING_LOG_FILE}: {err}")))?;
+    let early_stopping =
+        ValidLossEarlyStopping::new(config.patience, config.min_delta).with_logger(logger);
 
-    // Learnerを構築
-    let learner = LearnerBuilder::new(artifact_dir)
-        .devices(vec![device.clone()])
-        .num_epochs(config.num_epochs)
-        .build(
-            TuningForkPINN::<B>::new(&device),
-            config.optimizer.init(),
-            scheduler,
-        );
+    // オプティマイザごとに型が異なるため、Learnerの構築と学習はジェネリックな関数で行う
+    info!("🚀 Starting training on {:?}...", device);
+    let learner_components = LearnerComponents {
+        config: &config,
+        dataloader_train,
+        dataloader_valid,
+        scheduler,
+        early_stopping: early_stopping.clone(),
+        device: device.clone(),
+    };
+    let model_trained = match config.optimizer {
+        Optimizer::Adam => learner_components.fit(model, config.adam_config().init()),
+        Optimizer::AdamW { weight_decay } => {
+            learner_components.fit(model, config.adamw_config(weight_decay).init())
+        }
+        Optimizer::Sgd { momentum } => {
+            learner_components.fit(model, config.sgd_config(momentum).init())
+        }
+    };
 
-    println!("🚀 Starting training on {:?}...", device);
-    let model_trained = learner.fit(dataloader_train, dataloader_valid);
+    if let Some(epoch) = early_stopping.diverged_epoch() {
+        return Err(TrainError::Diverged(epoch));
+    }
 
     // 学習済みモデルを保存
+    let format = config.record_format;
+    let ext = format.extension();
+    let model_valid = model_trained.valid();
     let model_record = model_trained.into_record();
-    CompactRecorder::new()
-        .record(model_record, format!("{artifact_dir}/model").into())
-        .expect("Failed to save trained model");
+    record::save_record::<B, _>(format, model_record, format!("{artifact_dir}/model").into())
+        .map_err(|err| TrainError::Save(err.to_string()))?;
+
+    info!("✅ Model saved to '{artifact_dir}/model.{ext}'");
+
+    // 検証損失が最小だったエポックのチェックポイントを最良モデルとして保存
+    let model_best = match early_stopping.best_epoch() {
+        Some(best_epoch) => {
+            let checkpoint = best_checkpoint_path(artifact_dir, best_epoch, format)?;
+            fs::copy(&checkpoint, format!("{artifact_dir}/model_best.{ext}"))
+                .map_err(|err| TrainError::Save(format!("{checkpoint}: {err}")))?;
+            info!("✅ Best model (epoch {best_epoch}) saved to '{artifact_dir}/model_best.{ext}'");
+            let record = record::load_record::<B::InnerBackend, _>(
+                format,
+                checkpoint.clone().into(),
+                &device,
+            )
+            .map_err(|err| TrainError::Load(format!("{checkpoint}: {err}")))?;
+            model_valid.load_record(record)
+        }
+        None => model_valid,
+    };
 
-    println!("\n✅ Model saved to '{artifact_dir}/model.mpk'");
+    // 推論に使われる最良モデルを、学習に使用しない検証用のビンで評価し、周波数の誤差が
+    // 許容値に収まる割合を報告する
+    let report = evaluate_tolerance(&model_best, &config, &device);
+    info!("🎯 Held-out grid: {report}");
+
+    // モデルの来歴を保存
+    ModelMetadata::new(&config, early_stopping.final_loss())
+        .save(format!("{artifact_dir}/{METADATA_FILE}"))
+        .map_err(|err| TrainError::Save(format!("{METADATA_FILE}: {err}")))?;
+    Ok(())
+}
+
+/// 学習を開始せずに、設定とモデルを検証します (`train --dry-run`)。
+///
+/// [`run`]と同じ手順で設定の検証、モデルとデータローダーの構築を行い、解決された
+/// ハイパーパラメータとモデルの概要 (パラメータ数を含む) を標準出力に表示します。
+/// 学習は行わず、成果物ディレクトリにも何も書き込みません。
+///
+/// # Errors
+///
+/// 設定が不正な場合、または再開用のモデルの読み込みに失敗した場合に`TrainError`を返します。
+pub fn dry_run<B: AutodiffBackend>(
+    config: &TrainingConfig,
+    resume_path: Option<&str>,
+    device: B::Device,
+) -> Result<(), TrainError> {
+    config.validate()?;
+    B::seed(&device, config.seed);
+    let model = init_training_model::<B>(config, resume_path, &device)?;
+    let _dataloaders = build_dataloaders::<B, _>(config, train_dataset(config), &device);
+    let _scheduler = config
+        .lr_schedule
+        .init(config.learning_rate, config.num_epochs * TRAIN_BATCHES_PER_EPOCH);
+
+    let resolved = serde_json::to_string_pretty(config)
+        .map_err(|err| TrainError::InvalidConfig(err.to_string()))?;
+    println!("--- Resolved Training Config ---\n{resolved}");
+    println!("\n--- Model Summary ---\n{}", model.summary());
+    Ok(())
+}
+
+/// [`crate::backend::dispatch`]で選択したバックエンドで学習するアクション (`train`サブコマンド)。
+#[derive(Clone)]
+pub struct TrainAction {
+    /// 学習設定。
+    pub config: TrainingConfig,
+    /// 学習を再開するモデルレコードのパス。`None`の場合は新規に学習します。
+    pub resume_path: Option<String>,
+    /// 学習を開始せずに、設定とモデルを検証するだけにするかどうか ([`dry_run`]を参照)。
+    pub dry_run: bool,
+}
+
+#[cfg(feature = "native")]
+impl BackendAction for TrainAction {
+    type Output = Result<(), TrainError>;
+
+    fn run<B: AutodiffBackend>(self, device: B::Device) -> Self::Output {
+        if self.dry_run {
+            dry_run::<B>(&self.config, self.resume_path.as_deref(), device)
+        } else {
+            info!("🚀 Starting training on {device:?}...");
+            run::<B>(self.config, self.resume_path, device)
+        }
+    }
+}
+
+/// 学習用のデータローダー。
+type TrainDataLoader<B> = Arc<dyn DataLoader<B, TuningForkBatch<B>>>;
+/// 検証用のデータローダー (自動微分なしのバックエンド)。
+type ValidDataLoader<B> = Arc<
+    dyn DataLoader<
+            <B as AutodiffBackend>::InnerBackend,
+            TuningForkBatch<<B as AutodiffBackend>::InnerBackend>,
+        >,
+>;
+
+/// オプティマイザ以外の、Learnerの構築に必要な要素。
+struct LearnerComponents<'a, B: AutodiffBackend> {
+    config: &'a TrainingConfig,
+    dataloader_train: TrainDataLoader<B>,
+    dataloader_valid: ValidDataLoader<B>,
+    scheduler: PinnLrScheduler,
+    early_stopping: ValidLossEarlyStopping,
+    device: B::Device,
+}
+
+impl<B: AutodiffBackend> LearnerComponents<'_, B> {
+    /// 指定されたオプティマイザでLearnerを構築し、学習済みのモデルを返します。
+    fn fit<O>(self, model: TuningForkPINN<B>, optimizer: O) -> TuningForkPINN<B>
+    where
+        O: burn::optim::Optimizer<TuningForkPINN<B>, B> + 'static,
+    {
+        let config = self.config;
+        let artifact_dir = config.artifact_dir.as_str();
+
+        // Learnerを構築 (損失の各項、検証データの周波数誤差 (Hz)、制約を満たす予測の割合も
+        // メトリクスとして表示する)
+        let mut builder = LearnerBuilder::new(artifact_dir)
+            .metric_train_numeric(LossMetric::new())
+            .metric_valid_numeric(LossMetric::new())
+            .metric_valid_numeric(FrequencyErrorMetric::new(config.loss_config().physics))
+            .metric_train_numeric(PhysicalValidityMetric::new())
+            .metric_valid_numeric(PhysicalValidityMetric::new());
+        for kind in LossComponentKind::ALL {
+            builder = builder
+                .metric_train_numeric(LossComponentMetric::new(kind))
+                .metric_valid_numeric(LossComponentMetric::new(kind));
+        }
+        // 検証損失が最小のエポックのチェックポイントのみを保持する
+        let builder = match config.record_format {
+            RecordFormat::NamedMpk => builder.with_file_checkpointer(CompactRecorder::new()),
+            RecordFormat::Bincode => {
+                builder.with_file_checkpointer(BinFileRecorder::<FullPrecisionSettings>::new())
+            }
+            RecordFormat::PrettyJson => builder
+                .with_file_checkpointer(PrettyJsonFileRecorder::<FullPrecisionSettings>::new()),
+        };
+        let learner = builder
+            .with_checkpointing_strategy(MetricCheckpointingStrategy::new(
+                &LossMetric::<B>::new(),
+                Aggregate::Mean,
+                Direction::Lowest,
+                Split::Valid,
+            ))
+            .early_stopping(self.early_stopping)
+            .devices(vec![self.device])
+            .num_epochs(config.num_epochs)
+            .build(model, optimizer, self.scheduler);
+
+        learner.fit(self.dataloader_train, self.dataloader_valid)
+    }
 }
@@ -0,0 +1,52 @@
+//! # WebAssemblyのエントリーポイント
+//!
+//! ブラウザで推論を実行するための`wasm-bindgen`の関数を提供します (`wasm`フィーチャー)。
+//! 推論には`ndarray`バックエンド (CPU) を使用します。
+//!
+//! ブラウザにはファイルシステムがないため、モデルは`--record-format bincode`で保存した
+//! `model.bin`の内容をJavaScript側で取得し、バイト列として渡します。モデルの構成を復元する
+//! ため、学習設定 (`config.json`) の内容も文字列として渡します。
+//!
+//! ```javascript
+//! import init, { predict } from "./pkg/burn_tuningfork_pinn.js";
+//!
+//! await init();
+//! const weights = new Uint8Array(await (await fetch("model.bin")).arrayBuffer());
+//! const config = await (await fetch("config.json")).text();
+//! const dims = predict(440.0, weights, config);
+//! ```
+
+use crate::infer::{self, InferError};
+use crate::physics::PhysicsConfig;
+use burn::backend::ndarray::{NdArray, NdArrayDevice};
+use wasm_bindgen::prelude::*;
+
+type WasmBackend = NdArray<f32>;
+
+/// 周波数 (Hz) に対する音叉の寸法 (m) を、バイト列のモデルで推論します。
+///
+/// 寸法は`[handle_length, handle_diameter, prong_length, prong_diameter, prong_gap]`の順の
+/// 配列 (`Float32Array`) として返します。`config`は学習設定 (`config.json`) の内容で、
+/// モデルの構成と、材質や温度を入力とするモデルに与える材質・温度を復元します。省略すると
+/// デフォルトの構成・材質で学習したモデルとみなします。
+///
+/// # Errors
+///
+/// 周波数が不正、またはモデルや学習設定を読み込めない場合にJavaScriptの`Error`を
+/// 送出します。
+#[wasm_bindgen]
+pub fn predict(freq: f32, weights: &[u8], config: Option<String>) -> Result<Vec<f32>, JsError> {
+    predict_dims(freq, weights, config.as_deref()).map_err(|err| JsError::new(&err.to_string()))
+}
+
+fn predict_dims(freq: f32, weights: &[u8], config: Option<&str>) -> Result<Vec<f32>, InferError> {
+    infer::validate_frequencies(&[freq])?;
+    let physics = match config {
+        Some(json) => infer::training_config_from_json(json)?.loss_config().physics,
+        None => PhysicsConfig::new(),
+    };
+    let device = NdArrayDevice::Cpu;
+    let model = infer::load_model_from_bytes::<WasmBackend>(weights, config, &device)?;
+    let dims = model.predict_batch_with_physics(&[freq], &physics, &device)?;
+    Ok(dims[0].to_array().to_vec())
+}
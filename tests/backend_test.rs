@@ -0,0 +1,51 @@
+//! バックエンドの選択に対するユニットテスト
+
+use burn_tuningfork_pinn::backend::{self, BackendError, BackendOptions, Dtype};
+use burn_tuningfork_pinn::train::{TrainAction, TrainingConfig};
+use std::{fs, path::Path};
+
+#[test]
+fn test_dispatch_ndarray_train_runs_one_epoch() {
+    let artifact_dir = std::env::temp_dir().join("tuningfork_pinn_test_dispatch_ndarray");
+    let artifact_dir = artifact_dir.to_str().unwrap();
+    if Path::new(artifact_dir).exists() {
+        fs::remove_dir_all(artifact_dir).unwrap();
+    }
+    let action = TrainAction {
+        config: TrainingConfig::new()
+            .with_num_epochs(1)
+            .with_batch_size(64)
+            .with_artifact_dir(artifact_dir.to_string()),
+        resume_path: None,
+        dry_run: false,
+    };
+
+    let result = backend::dispatch("ndarray", &BackendOptions::default(), action)
+        .expect("ndarray should be available");
+
+    assert!(result.is_ok(), "training should succeed: {:?}", result.err());
+    assert!(Path::new(&format!("{artifact_dir}/model.mpk")).exists());
+    fs::remove_dir_all(artifact_dir).unwrap();
+}
+
+#[test]
+fn test_dispatch_rejects_unknown_backend_and_unsupported_dtype() {
+    let action = TrainAction {
+        config: TrainingConfig::new(),
+        resume_path: None,
+        dry_run: true,
+    };
+    let f64_options = BackendOptions {
+        dtype: Dtype::F64,
+        ..Default::default()
+    };
+
+    let unknown = backend::dispatch("opencl", &BackendOptions::default(), action.clone());
+    let unsupported = backend::dispatch("wgpu", &f64_options, action);
+
+    assert!(matches!(unknown, Err(BackendError::Unknown(name)) if name == "opencl"));
+    assert!(matches!(
+        unsupported,
+        Err(BackendError::UnsupportedDtype { dtype: Dtype::F64, .. })
+    ));
+}
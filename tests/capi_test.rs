@@ -0,0 +1,61 @@
+//! C APIに対するユニットテスト (`capi`フィーチャー)
+
+use std::ffi::CString;
+
+use burn::backend::ndarray::{NdArray, NdArrayDevice};
+use burn::module::Module;
+use burn::record::{CompactRecorder, Recorder};
+
+use burn_tuningfork_pinn::capi::{
+    self, TFPINN_ERR_INVALID_INPUT, TFPINN_ERR_NULL_POINTER, TFPINN_OK,
+};
+use burn_tuningfork_pinn::model::ModelConfig;
+
+type B = NdArray<f32>;
+
+#[test]
+fn test_load_predict_free() {
+    let device: NdArrayDevice = Default::default();
+    let artifact_dir = std::env::temp_dir().join("tuningfork_pinn_capi_test");
+    std::fs::create_dir_all(&artifact_dir).unwrap();
+    let model = ModelConfig::new().init::<B>(&device);
+    CompactRecorder::new()
+        .record(model.into_record(), artifact_dir.join("model"))
+        .unwrap();
+    let dir = CString::new(artifact_dir.to_str().unwrap()).unwrap();
+
+    unsafe {
+        let model = capi::tfpinn_load(dir.as_ptr());
+        assert!(!model.is_null());
+
+        let mut dims = [0.0f32; 5];
+        assert_eq!(capi::tfpinn_predict(model, 440.0, dims.as_mut_ptr()), TFPINN_OK);
+        assert!(dims.iter().all(|&d| d > 0.0), "{dims:?}");
+
+        let mut untouched = [0.0f32; 5];
+        assert_eq!(
+            capi::tfpinn_predict(model, -1.0, untouched.as_mut_ptr()),
+            TFPINN_ERR_INVALID_INPUT
+        );
+        assert_eq!(untouched, [0.0; 5]);
+
+        capi::tfpinn_free(model);
+    }
+    std::fs::remove_dir_all(&artifact_dir).unwrap();
+}
+
+#[test]
+fn test_failures_return_null_or_error_code() {
+    let missing = CString::new("./nonexistent_artifacts").unwrap();
+    let mut dims = [0.0f32; 5];
+
+    unsafe {
+        assert!(capi::tfpinn_load(missing.as_ptr()).is_null());
+        assert!(capi::tfpinn_load(std::ptr::null()).is_null());
+        assert_eq!(
+            capi::tfpinn_predict(std::ptr::null(), 440.0, dims.as_mut_ptr()),
+            TFPINN_ERR_NULL_POINTER
+        );
+        capi::tfpinn_free(std::ptr::null_mut());
+    }
+}
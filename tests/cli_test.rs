@@ -0,0 +1,79 @@
+//! `train`サブコマンドの引数に対するユニットテスト
+
+use burn::config::Config;
+use clap::Parser;
+
+use burn_tuningfork_pinn::cli::TrainArgs;
+use burn_tuningfork_pinn::constants::physics::Material;
+use burn_tuningfork_pinn::model::{Activation, InitKind};
+use burn_tuningfork_pinn::train::{TrainError, TrainingConfig};
+
+/// `TrainArgs`を組み込んだテスト用のコマンド
+#[derive(Parser, Debug)]
+struct TestCli {
+    #[command(flatten)]
+    train: TrainArgs,
+}
+
+fn to_config(args: &[&str]) -> Result<TrainingConfig, TrainError> {
+    let cli = TestCli::try_parse_from(std::iter::once("test").chain(args.iter().copied())).unwrap();
+    cli.train.to_config()
+}
+
+#[test]
+fn test_cli_flags_override_config_file() {
+    let path = std::env::temp_dir().join("tuningfork_pinn_cli_test_config.json");
+    TrainingConfig::new()
+        .with_num_epochs(50)
+        .with_batch_size(128)
+        .with_material(Material::Brass)
+        .save(&path)
+        .unwrap();
+
+    let config = to_config(&["--config", path.to_str().unwrap(), "--epochs", "7"]).unwrap();
+
+    assert_eq!(config.num_epochs, 7);
+    assert_eq!(config.batch_size, 128);
+    assert_eq!(config.material, Material::Brass);
+    std::fs::remove_file(&path).unwrap();
+}
+
+#[test]
+fn test_missing_config_file_is_an_error() {
+    let result = to_config(&["--config", "does/not/exist.json"]);
+
+    assert!(matches!(result, Err(TrainError::InvalidConfig(_))));
+}
+
+#[test]
+fn test_without_config_file_uses_defaults() {
+    let config = to_config(&["--batch-size", "64"]).unwrap();
+
+    assert_eq!(config.batch_size, 64);
+    assert_eq!(config.num_epochs, TrainingConfig::new().num_epochs);
+}
+
+#[test]
+fn test_architecture_flags_build_the_model_config() {
+    let config = to_config(&[
+        "--hidden-size",
+        "64",
+        "--num-hidden-layers",
+        "5",
+        "--activation",
+        "tanh",
+        "--init-kind",
+        "xavier-uniform",
+        "--batch-norm",
+        "--residual",
+    ])
+    .unwrap();
+    let model_config = config.model_config();
+
+    assert_eq!(model_config.hidden_size, 64);
+    assert_eq!(model_config.num_hidden_layers, 5);
+    assert_eq!(model_config.activation, Activation::Tanh);
+    assert_eq!(model_config.init_kind, InitKind::XavierUniform);
+    assert!(model_config.batch_norm);
+    assert!(model_config.residual);
+}
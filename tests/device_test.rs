@@ -0,0 +1,29 @@
+//! デバイスの選択に対するユニットテスト
+
+use burn::backend::ndarray::{NdArray, NdArrayDevice};
+
+use burn_tuningfork_pinn::device::{self, DeviceError};
+
+#[test]
+fn test_available_backends_include_default_build() {
+    let backends = device::available_backends();
+
+    assert!(backends.contains(&"ndarray"));
+    assert!(backends.contains(&"wgpu"));
+    assert_eq!(backends.contains(&"tch"), cfg!(feature = "tch"));
+    assert_eq!(backends.contains(&"candle"), cfg!(feature = "candle"));
+}
+
+#[test]
+fn test_out_of_range_wgpu_index_returns_error() {
+    let result = device::wgpu_device(Some(10_000));
+
+    assert!(matches!(result, Err(DeviceError::Unavailable(_))));
+}
+
+#[test]
+fn test_probe_accepts_ndarray_device() {
+    let device: NdArrayDevice = Default::default();
+
+    assert!(device::probe_device::<NdArray>(&device).is_ok());
+}
@@ -0,0 +1,369 @@
+//! モデルのエクスポートに対するユニットテスト
+
+use burn::backend::Autodiff;
+use burn::backend::ndarray::{NdArray, NdArrayDevice};
+use burn::module::{AutodiffModule, Module};
+use burn::record::{CompactRecorder, Recorder};
+use burn::tensor::Tensor;
+use safetensors::SafeTensors;
+
+use burn_tuningfork_pinn::constants::physics::Material;
+use burn_tuningfork_pinn::export::{self, ExportError};
+use burn_tuningfork_pinn::infer::InferError;
+use burn_tuningfork_pinn::model::{Activation, ModelConfig, TuningForkPINN};
+use burn_tuningfork_pinn::physics::PhysicsConfig;
+
+type B = NdArray<f32>;
+
+/// バイト列に部分列が含まれるかを返します。
+fn contains(haystack: &[u8], needle: &str) -> bool {
+    haystack
+        .windows(needle.len())
+        .any(|window| window == needle.as_bytes())
+}
+
+#[test]
+fn test_onnx_export_writes_non_empty_file() {
+    let device: NdArrayDevice = Default::default();
+    let artifact_dir = std::env::temp_dir().join("tuningfork_pinn_export_onnx_test");
+    std::fs::create_dir_all(&artifact_dir).unwrap();
+    let model = ModelConfig::new().init::<B>(&device);
+    CompactRecorder::new()
+        .record(model.into_record(), artifact_dir.join("model"))
+        .unwrap();
+    let output = artifact_dir.join("model.onnx");
+
+    export::run_onnx::<B>(artifact_dir.to_str().unwrap(), &output, device).unwrap();
+
+    let bytes = std::fs::read(&output).unwrap();
+    assert!(!bytes.is_empty());
+    assert!(contains(&bytes, export::ONNX_INPUT_NAME));
+    assert!(contains(&bytes, export::ONNX_OUTPUT_NAME));
+    std::fs::remove_dir_all(&artifact_dir).unwrap();
+}
+
+#[test]
+fn test_onnx_graph_contains_expected_ops() {
+    let device: NdArrayDevice = Default::default();
+    let model = ModelConfig::new()
+        .with_activation(Activation::Gelu)
+        .init::<B>(&device);
+
+    let bytes = export::to_onnx(&model);
+
+    for op in ["Gemm", "Erf", "Softplus", "layer_4.weight"] {
+        assert!(contains(&bytes, op), "missing {op}");
+    }
+    assert!(!contains(&bytes, "Relu"));
+}
+
+#[test]
+fn test_onnx_export_without_model_returns_load_error() {
+    let device: NdArrayDevice = Default::default();
+    let output = std::env::temp_dir().join("tuningfork_pinn_missing_model.onnx");
+
+    let result = export::run_onnx::<B>("./nonexistent_artifacts", &output, device);
+
+    assert!(matches!(
+        result,
+        Err(ExportError::Load(InferError::ModelNotFound(_)))
+    ));
+}
+
+#[test]
+fn test_safetensors_export_round_trips_names_and_shapes() {
+    let device: NdArrayDevice = Default::default();
+    let model = ModelConfig::new().init::<B>(&device);
+
+    let bytes = export::to_safetensors(&model).unwrap();
+
+    let tensors = SafeTensors::deserialize(&bytes).unwrap();
+    let expected = [
+        ("input.freq_mean", vec![1]),
+        ("input.freq_std", vec![1]),
+        ("layer_1.weight", vec![1, 128]),
+        ("layer_1.bias", vec![128]),
+        ("layer_2.weight", vec![128, 128]),
+        ("layer_2.bias", vec![128]),
+        ("layer_3.weight", vec![128, 128]),
+        ("layer_3.bias", vec![128]),
+        ("layer_4.weight", vec![128, 5]),
+        ("layer_4.bias", vec![5]),
+    ];
+    assert_eq!(tensors.names().len(), expected.len());
+    for (name, shape) in expected {
+        let tensor = tensors.tensor(name).unwrap();
+        assert_eq!(tensor.shape(), shape.as_slice(), "tensor: {name}");
+    }
+}
+
+/// テスト用に読み出したprotobufのフィールドの値。
+enum Field<'a> {
+    Varint(u64),
+    Bytes(&'a [u8]),
+}
+
+/// protobufのメッセージを`(フィールド番号, 値)`の列に分解します (varintと長さ付きのみ対応)。
+fn decode_fields(mut bytes: &[u8]) -> Vec<(u64, Field<'_>)> {
+    fn varint(bytes: &mut &[u8]) -> u64 {
+        let mut value = 0;
+        for shift in (0..).step_by(7) {
+            let byte = bytes[0];
+            *bytes = &bytes[1..];
+            value |= u64::from(byte & 0x7f) << shift;
+            if byte & 0x80 == 0 {
+                break;
+            }
+        }
+        value
+    }
+
+    let mut fields = Vec::new();
+    while !bytes.is_empty() {
+        let key = varint(&mut bytes);
+        let value = match key & 0x7 {
+            0 => Field::Varint(varint(&mut bytes)),
+            2 => {
+                let len = varint(&mut bytes) as usize;
+                let (value, rest) = bytes.split_at(len);
+                bytes = rest;
+                Field::Bytes(value)
+            }
+            wire_type => panic!("unsupported wire type {wire_type}"),
+        };
+        fields.push((key >> 3, value));
+    }
+    fields
+}
+
+/// 長さ付きフィールドの値を文字列として返します。
+fn field_str<'a>(field: &Field<'a>) -> &'a str {
+    match field {
+        Field::Bytes(bytes) => std::str::from_utf8(bytes).unwrap(),
+        Field::Varint(_) => panic!("expected a length-delimited field"),
+    }
+}
+
+/// 形状と値を持つ`float`テンソル (0次元・1次元・2次元のみ)。
+#[derive(Clone, Debug)]
+struct OnnxTensor {
+    dims: Vec<usize>,
+    values: Vec<f32>,
+}
+
+impl OnnxTensor {
+    /// 要素ごとの単項演算を適用します。
+    fn map(&self, f: impl Fn(f32) -> f32) -> Self {
+        Self {
+            dims: self.dims.clone(),
+            values: self.values.iter().map(|&value| f(value)).collect(),
+        }
+    }
+
+    /// 要素ごとの二項演算を適用します。`rhs`は同じ形状・スカラー・最後の次元の1次元テンソルに
+    /// ブロードキャストします。
+    fn zip(&self, rhs: &Self, f: impl Fn(f32, f32) -> f32) -> Self {
+        let width = *self.dims.last().unwrap();
+        let values = self
+            .values
+            .iter()
+            .enumerate()
+            .map(|(i, &value)| {
+                let other = match rhs.values.len() {
+                    1 => rhs.values[0],
+                    len if len == self.values.len() => rhs.values[i],
+                    len if len == width => rhs.values[i % width],
+                    len => panic!("cannot broadcast {len} values to {:?}", self.dims),
+                };
+                f(value, other)
+            })
+            .collect();
+        Self {
+            dims: self.dims.clone(),
+            values,
+        }
+    }
+}
+
+/// 誤差関数 (Abramowitz & Stegun 7.1.26、絶対誤差1.5e-7以下)。
+fn erf(x: f32) -> f32 {
+    let t = 1.0 / (1.0 + 0.327_591_1 * x.abs());
+    let poly = t
+        * (0.254_829_6
+            + t * (-0.284_496_7 + t * (1.421_413_7 + t * (-1.453_152_1 + t * 1.061_405_4))));
+    (1.0 - poly * (-x * x).exp()).copysign(x)
+}
+
+/// `to_onnx`が出力する演算だけに対応した最小限のインタプリタで、ONNXのグラフを評価します。
+fn evaluate_onnx(bytes: &[u8], input: OnnxTensor) -> OnnxTensor {
+    let graph = decode_fields(bytes)
+        .into_iter()
+        .find_map(|(number, field)| match (number, field) {
+            (7, Field::Bytes(graph)) => Some(graph),
+            _ => None,
+        })
+        .expect("ModelProto has no graph");
+
+    let mut values = std::collections::HashMap::new();
+    values.insert(export::ONNX_INPUT_NAME.to_string(), input);
+    let mut nodes = Vec::new();
+    for (number, field) in decode_fields(graph) {
+        match number {
+            1 => nodes.push(field),
+            5 => {
+                let (mut name, mut dims, mut raw) = (String::new(), Vec::new(), &[][..]);
+                for (number, field) in decode_fields(match field {
+                    Field::Bytes(bytes) => bytes,
+                    Field::Varint(_) => unreachable!(),
+                }) {
+                    match (number, field) {
+                        (1, Field::Varint(dim)) => dims.push(dim as usize),
+                        (8, field) => name = field_str(&field).to_string(),
+                        (9, Field::Bytes(bytes)) => raw = bytes,
+                        _ => {}
+                    }
+                }
+                let values_f32 = raw
+                    .chunks_exact(4)
+                    .map(|chunk| f32::from_le_bytes(chunk.try_into().unwrap()))
+                    .collect();
+                let tensor = OnnxTensor {
+                    dims,
+                    values: values_f32,
+                };
+                values.insert(name, tensor);
+            }
+            _ => {}
+        }
+    }
+
+    for node in nodes {
+        let (mut inputs, mut output, mut op_type) = (Vec::new(), String::new(), String::new());
+        let Field::Bytes(node) = node else { unreachable!() };
+        for (number, field) in decode_fields(node) {
+            match number {
+                1 => inputs.push(values[field_str(&field)].clone()),
+                2 => output = field_str(&field).to_string(),
+                4 => op_type = field_str(&field).to_string(),
+                _ => {}
+            }
+        }
+        let result = match (op_type.as_str(), inputs.as_slice()) {
+            ("Add", [a, b]) => a.zip(b, |a, b| a + b),
+            ("Sub", [a, b]) => a.zip(b, |a, b| a - b),
+            ("Mul", [a, b]) => a.zip(b, |a, b| a * b),
+            ("Div", [a, b]) => a.zip(b, |a, b| a / b),
+            ("Relu", [x]) => x.map(|x| x.max(0.0)),
+            ("Tanh", [x]) => x.map(f32::tanh),
+            ("Sigmoid", [x]) => x.map(|x| 1.0 / (1.0 + (-x).exp())),
+            ("Erf", [x]) => x.map(erf),
+            ("Softplus", [x]) => x.map(|x| x.exp().ln_1p()),
+            ("Gemm", [a, b, c]) => {
+                let [batch, d_input] = a.dims[..] else { unreachable!() };
+                let [_, d_output] = b.dims[..] else { unreachable!() };
+                let values = (0..batch * d_output)
+                    .map(|i| {
+                        let (row, col) = (i / d_output, i % d_output);
+                        (0..d_input)
+                            .map(|k| a.values[row * d_input + k] * b.values[k * d_output + col])
+                            .sum::<f32>()
+                            + c.values[col]
+                    })
+                    .collect();
+                OnnxTensor {
+                    dims: vec![batch, d_output],
+                    values,
+                }
+            }
+            ("BatchNormalization", [x, scale, bias, mean, var]) => {
+                // ONNXの`epsilon`の既定値
+                let epsilon = 1e-5;
+                let normalized = x.zip(mean, |x, mean| x - mean);
+                let std = var.map(|var| (var + epsilon).sqrt());
+                normalized
+                    .zip(&std, |x, std| x / std)
+                    .zip(scale, |x, scale| x * scale)
+                    .zip(bias, |x, bias| x + bias)
+            }
+            (op_type, inputs) => panic!("unsupported op {op_type} with {} inputs", inputs.len()),
+        };
+        values.insert(output, result);
+    }
+    values.remove(export::ONNX_OUTPUT_NAME).expect("graph has no output")
+}
+
+/// エクスポートしたグラフの出力が、同じ入力に対する`model.forward`と一致することを確かめます。
+fn assert_onnx_matches_forward(model: &TuningForkPINN<B>, physics: &PhysicsConfig) {
+    let device: NdArrayDevice = Default::default();
+    let frequencies = [200.0, 440.0, 1000.0, 2000.0];
+    let input = model.with_conditions(
+        Tensor::<B, 1>::from_floats(frequencies, &device).unsqueeze_dim(1),
+        physics,
+    );
+    let expected = model.forward(input.clone()).into_data().into_vec::<f32>().unwrap();
+
+    let input = OnnxTensor {
+        dims: input.dims().to_vec(),
+        values: input.into_data().into_vec::<f32>().unwrap(),
+    };
+    let actual = evaluate_onnx(&export::to_onnx(model), input);
+
+    assert_eq!(actual.dims, vec![frequencies.len(), model.output_size()]);
+    for (i, (actual, expected)) in actual.values.iter().zip(&expected).enumerate() {
+        assert!(
+            (actual - expected).abs() <= 1e-4 * expected.abs().max(1.0),
+            "output {i}: onnx {actual} != forward {expected}"
+        );
+    }
+}
+
+#[test]
+fn test_onnx_graph_matches_forward_for_default_model() {
+    let device: NdArrayDevice = Default::default();
+    let model = ModelConfig::new().init::<B>(&device);
+
+    assert_onnx_matches_forward(&model, &PhysicsConfig::new());
+}
+
+#[test]
+fn test_onnx_graph_matches_forward_with_trained_batch_norm() {
+    let device: NdArrayDevice = Default::default();
+    let model = ModelConfig::new()
+        .with_batch_norm(true)
+        .with_residual(true)
+        .init::<Autodiff<B>>(&device);
+    // 学習モードのフォワードパスで移動平均・移動分散を初期値から動かす
+    let frequencies = Tensor::<Autodiff<B>, 1>::from_floats([300.0, 700.0, 1500.0], &device);
+    let _ = model.forward(frequencies.unsqueeze_dim(1));
+    let model = model.valid();
+    let moved = model.batch_norms()[0].running_mean.value().abs().sum().into_scalar();
+    assert!(moved > 0.0, "running mean was not updated");
+
+    assert_onnx_matches_forward(&model, &PhysicsConfig::new());
+}
+
+#[test]
+fn test_onnx_graph_matches_forward_with_softplus_beta() {
+    let device: NdArrayDevice = Default::default();
+    let model = ModelConfig::new()
+        .with_softplus_beta(10.0)
+        .with_activation(Activation::Gelu)
+        .init::<B>(&device);
+
+    assert_onnx_matches_forward(&model, &PhysicsConfig::new());
+}
+
+#[test]
+fn test_onnx_graph_matches_forward_with_condition_inputs() {
+    let device: NdArrayDevice = Default::default();
+    let model = ModelConfig::new()
+        .with_material_input(true)
+        .with_temperature_input(true)
+        .with_activation(Activation::SiLU)
+        .init::<B>(&device);
+    let physics = PhysicsConfig::new()
+        .with_material(Material::Brass)
+        .with_temperature(60.0);
+
+    assert_onnx_matches_forward(&model, &physics);
+}
@@ -0,0 +1,88 @@
+//! 音叉の形状の生成に対するユニットテスト
+
+use burn_tuningfork_pinn::geometry;
+use burn_tuningfork_pinn::infer::ForkDimensions;
+
+fn sample_dims() -> ForkDimensions {
+    ForkDimensions {
+        handle_length: 0.08,
+        handle_diameter: 0.01,
+        prong_length: 0.1234,
+        prong_diameter: 0.0056,
+        prong_gap: 0.008,
+    }
+}
+
+#[test]
+fn test_openscad_script_contains_predicted_cylinders() {
+    let script = geometry::to_openscad(&sample_dims());
+
+    // 柄: 長さ80mm・直径10mm
+    assert!(script.contains("cylinder(h = 80.000, d = 10.000);"), "{script}");
+    // プロング: 中心間の距離が間隔 + 直径 (13.6mm) となる2本の円柱
+    let prong = "cylinder(h = 123.400, d = 5.600);";
+    assert_eq!(script.matches(prong).count(), 2, "{script}");
+    assert!(script.contains("translate([-6.800, 0, 85.600])"), "{script}");
+    assert!(script.contains("translate([6.800, 0, 85.600])"), "{script}");
+}
+
+#[test]
+fn test_mesh_facet_count_matches_tessellation() {
+    for segments in [3, 16, geometry::DEFAULT_SEGMENTS] {
+        let mesh = geometry::to_mesh(&sample_dims(), segments);
+        // 円柱3本 (側面2·n + 上下の面2·n) + ブリッジの直方体 (12)
+        assert_eq!(mesh.len(), 12 * segments + 12);
+    }
+}
+
+#[test]
+fn test_mesh_normals_point_outward() {
+    let mesh = geometry::to_mesh(&sample_dims(), 8);
+    for triangle in &mesh {
+        let normal = triangle.normal();
+        let length = normal.iter().map(|x| x * x).sum::<f32>().sqrt();
+        assert!((length - 1.0).abs() < 1e-4, "{triangle:?}");
+    }
+    // 柄の底面 (z = 0) の三角形は下向き
+    let bottom = mesh
+        .iter()
+        .filter(|t| t.vertices.iter().all(|v| v[2] == 0.0))
+        .collect::<Vec<_>>();
+    assert_eq!(bottom.len(), 8);
+    assert!(bottom.iter().all(|t| t.normal()[2] < -0.999));
+}
+
+#[test]
+fn test_stl_is_valid_ascii_stl() {
+    let segments = 16;
+    let stl = geometry::to_stl(&sample_dims(), segments);
+    let mut lines = stl.lines().map(str::trim);
+
+    assert!(lines.next().unwrap().starts_with("solid "));
+    let mut facets = 0;
+    loop {
+        let line = lines.next().expect("unexpected end of STL");
+        if line.starts_with("endsolid") {
+            break;
+        }
+        let normal = line.strip_prefix("facet normal ").expect(line);
+        assert_eq!(parse_floats(normal).len(), 3, "{line}");
+        assert_eq!(lines.next(), Some("outer loop"));
+        for _ in 0..3 {
+            let line = lines.next().unwrap();
+            let vertex = line.strip_prefix("vertex ").expect(line);
+            assert_eq!(parse_floats(vertex).len(), 3, "{line}");
+        }
+        assert_eq!(lines.next(), Some("endloop"));
+        assert_eq!(lines.next(), Some("endfacet"));
+        facets += 1;
+    }
+    assert_eq!(lines.next(), None);
+    assert_eq!(facets, 12 * segments + 12);
+}
+
+fn parse_floats(text: &str) -> Vec<f32> {
+    text.split_whitespace()
+        .map(|x| x.parse::<f32>().expect(x))
+        .collect()
+}
@@ -0,0 +1,783 @@
+//! 推論ロジックに対するユニットテスト
+
+use burn::backend::Autodiff;
+use burn::backend::ndarray::{NdArray, NdArrayDevice};
+use burn::config::Config;
+use burn::module::Module;
+use burn::tensor::Tensor;
+use burn::tensor::backend::Backend;
+
+use burn_tuningfork_pinn::infer::{
+    self, ComparisonRow, ForkDimensions, InferError, InferenceReport, OutputFormat, Unit, Winner,
+};
+use burn_tuningfork_pinn::constants::{bounds, physics::Material};
+use burn_tuningfork_pinn::model::{Activation, ModelConfig, TuningForkPINN};
+use burn_tuningfork_pinn::physics::{self, PhysicsConfig};
+use burn_tuningfork_pinn::record::{self, RecordFormat};
+use burn_tuningfork_pinn::train::{self, TrainingConfig};
+
+use std::path::PathBuf;
+
+type B = NdArray<f32>;
+
+/// `model`を一時ディレクトリ内の成果物ディレクトリ`name`に保存し、そのパスを返します。
+///
+/// `CompactRecorder`は半精度で保存するため、読み込んだモデルがメモリ上のモデルと同じ予測を
+/// するよう単精度の形式で保存する。
+fn save_model_to_temp_dir(name: &str, model: &TuningForkPINN<B>) -> PathBuf {
+    let artifact_dir = std::env::temp_dir().join(name);
+    std::fs::create_dir_all(&artifact_dir).unwrap();
+    let path = artifact_dir.join("model");
+    record::save_record::<B, _>(RecordFormat::Bincode, model.clone().into_record(), path).unwrap();
+    artifact_dir
+}
+
+#[test]
+fn test_run_with_missing_model_returns_model_not_found() {
+    let device: NdArrayDevice = Default::default();
+
+    let physics = PhysicsConfig::new();
+    let result = infer::run::<B>("./nonexistent_artifacts", 440.0, Some(&physics), device);
+
+    assert!(matches!(result, Err(InferError::ModelNotFound(_))));
+}
+
+#[test]
+fn test_run_loads_model_from_given_artifact_dir() {
+    let device: NdArrayDevice = Default::default();
+    let model = ModelConfig::new().init::<B>(&device);
+    let artifact_dir = save_model_to_temp_dir("tuningfork_pinn_infer_artifact_dir_test", &model);
+
+    let report = infer::run::<B>(artifact_dir.to_str().unwrap(), 440.0, None, device);
+
+    assert_eq!(report.unwrap().target_frequency, 440.0);
+    std::fs::remove_dir_all(&artifact_dir).unwrap();
+}
+
+#[test]
+fn test_physics_config_is_restored_from_saved_training_config() {
+    let artifact_dir = std::env::temp_dir().join("tuningfork_pinn_infer_saved_config_test");
+    std::fs::create_dir_all(&artifact_dir).unwrap();
+    let artifact_dir_str = artifact_dir.to_str().unwrap();
+    TrainingConfig::new()
+        .with_material(Material::Aluminum)
+        .with_mode(2)
+        .save(artifact_dir.join(infer::TRAINING_CONFIG_FILE))
+        .unwrap();
+
+    let physics = infer::physics_config_for(artifact_dir_str).unwrap();
+
+    assert_eq!(physics.material, Material::Aluminum);
+    assert_eq!(physics.mode, 2);
+    std::fs::remove_dir_all(&artifact_dir).unwrap();
+}
+
+#[test]
+fn test_run_rebuilds_the_saved_architecture() {
+    let device: NdArrayDevice = Default::default();
+    let config = TrainingConfig::new()
+        .with_hidden_size(32)
+        .with_num_hidden_layers(5)
+        .with_activation(Activation::Tanh)
+        .with_residual(true)
+        .with_batch_norm(true);
+    let model = config.model_config().init::<B>(&device);
+    let artifact_dir = save_model_to_temp_dir("tuningfork_pinn_infer_architecture_test", &model);
+    config.save(artifact_dir.join(infer::TRAINING_CONFIG_FILE)).unwrap();
+
+    let report = infer::run::<B>(artifact_dir.to_str().unwrap(), 440.0, None, device).unwrap();
+
+    assert_eq!(report.dimensions, model.predict(440.0, &device).unwrap());
+    std::fs::remove_dir_all(&artifact_dir).unwrap();
+}
+
+#[test]
+fn test_run_ignores_stale_records_in_another_format() {
+    let device: NdArrayDevice = Default::default();
+    let config = TrainingConfig::new().with_record_format(RecordFormat::Bincode);
+    B::seed(&device, 1);
+    let model = config.model_config().init::<B>(&device);
+    let artifact_dir = save_model_to_temp_dir("tuningfork_pinn_infer_stale_format_test", &model);
+    config.save(artifact_dir.join(infer::TRAINING_CONFIG_FILE)).unwrap();
+    // 以前に`mpk`形式で学習したときの最良モデルが残っている
+    B::seed(&device, 2);
+    let stale = config.model_config().init::<B>(&device);
+    let stale_path = artifact_dir.join("model_best");
+    record::save_record::<B, _>(RecordFormat::NamedMpk, stale.into_record(), stale_path).unwrap();
+
+    let report = infer::run::<B>(artifact_dir.to_str().unwrap(), 440.0, None, device).unwrap();
+
+    assert_eq!(report.dimensions, model.predict(440.0, &device).unwrap());
+    std::fs::remove_dir_all(&artifact_dir).unwrap();
+}
+
+#[test]
+fn test_physics_config_defaults_without_saved_training_config() {
+    let physics = infer::physics_config_for("./nonexistent_artifacts").unwrap();
+
+    assert_eq!(physics.material, Material::Steel304);
+    assert_eq!(physics.mode, 1);
+}
+
+#[test]
+fn test_run_with_invalid_frequency_returns_invalid_input() {
+    let device: NdArrayDevice = Default::default();
+
+    let physics = PhysicsConfig::new();
+    let result = infer::run::<B>("./nonexistent_artifacts", -1.0, Some(&physics), device);
+
+    assert!(matches!(result, Err(InferError::InvalidInput(_))));
+}
+
+#[test]
+fn test_run_with_unsupported_mode_returns_invalid_input() {
+    let device: NdArrayDevice = Default::default();
+
+    let physics = PhysicsConfig::new().with_mode(4);
+    let result = infer::run::<B>("./nonexistent_artifacts", 440.0, Some(&physics), device);
+
+    assert!(matches!(result, Err(InferError::InvalidInput(_))));
+}
+
+#[test]
+fn test_run_batch_with_empty_input_returns_empty_vec() {
+    let device: NdArrayDevice = Default::default();
+
+    // モデルが存在しないディレクトリでも、空入力ならモデルを読み込まずに成功する
+    let result = infer::run_batch::<B>(
+        "./nonexistent_artifacts",
+        &[],
+        None,
+        infer::DEFAULT_CHUNK_SIZE,
+        device,
+        None,
+    );
+
+    assert_eq!(result.unwrap(), Vec::new());
+}
+
+#[test]
+fn test_run_batch_reports_progress_once_per_chunk() {
+    let device: NdArrayDevice = Default::default();
+    let model = ModelConfig::new().init::<B>(&device);
+    let artifact_dir = save_model_to_temp_dir("tuningfork_pinn_infer_progress_test", &model);
+    let freqs: Vec<f32> = (0..25).map(|i| 200.0 + i as f32 * 10.0).collect();
+    let mut calls = Vec::new();
+    let mut on_progress = |done: usize, total: usize| calls.push((done, total));
+
+    let results = infer::run_batch::<B>(
+        artifact_dir.to_str().unwrap(),
+        &freqs,
+        None,
+        10,
+        device,
+        Some(&mut on_progress),
+    )
+    .unwrap();
+
+    assert_eq!(results.len(), 25);
+    assert_eq!(calls, vec![(10, 25), (20, 25), (25, 25)]);
+    std::fs::remove_dir_all(&artifact_dir).unwrap();
+}
+
+#[test]
+fn test_run_batch_uses_the_saved_material_for_material_input_models() {
+    let device: NdArrayDevice = Default::default();
+    let config = TrainingConfig::new()
+        .with_material_input(true)
+        .with_material(Material::Brass);
+    let model = config.model_config().init::<B>(&device);
+    let artifact_dir =
+        save_model_to_temp_dir("tuningfork_pinn_infer_batch_material_test", &model);
+    config.save(artifact_dir.join(infer::TRAINING_CONFIG_FILE)).unwrap();
+    let artifact_dir_str = artifact_dir.to_str().unwrap();
+    let freqs = [440.0, 880.0];
+    let brass = PhysicsConfig::new().with_material(Material::Brass);
+    let aluminum = PhysicsConfig::new().with_material(Material::Aluminum);
+
+    let saved = infer::run_batch::<B>(artifact_dir_str, &freqs, None, 10, device, None).unwrap();
+    let overridden =
+        infer::run_batch::<B>(artifact_dir_str, &freqs, Some(&aluminum), 10, device, None)
+            .unwrap();
+
+    let expected = |physics| model.predict_batch_with_physics(&freqs, physics, &device).unwrap();
+    assert_eq!(saved, expected(&brass));
+    assert_eq!(overridden, expected(&aluminum));
+    std::fs::remove_dir_all(&artifact_dir).unwrap();
+}
+
+#[test]
+fn test_chunked_run_batch_matches_unchunked() {
+    let device: NdArrayDevice = Default::default();
+    let model = ModelConfig::new().init::<B>(&device);
+    let artifact_dir = save_model_to_temp_dir("tuningfork_pinn_infer_chunk_test", &model);
+    let artifact_dir_str = artifact_dir.to_str().unwrap();
+    let freqs: Vec<f32> = (0..10).map(|i| 200.0 + i as f32 * 150.0).collect();
+
+    let run = |chunk_size| {
+        infer::run_batch::<B>(artifact_dir_str, &freqs, None, chunk_size, device, None)
+    };
+    let chunked = run(3).unwrap();
+    let unchunked = run(freqs.len()).unwrap();
+    let zero_chunk = run(0);
+
+    assert_eq!(chunked.len(), 10);
+    assert_eq!(chunked, unchunked);
+    assert_eq!(unchunked, model.predict_batch(&freqs, &device).unwrap());
+    assert!(matches!(zero_chunk, Err(InferError::InvalidInput(_))));
+    std::fs::remove_dir_all(&artifact_dir).unwrap();
+}
+
+#[test]
+fn test_repl_prints_one_result_per_frequency_and_skips_invalid_lines() {
+    let device: NdArrayDevice = Default::default();
+    let model = ModelConfig::new().init::<B>(&device);
+    let input: &[u8] = b"440\nnot-a-number\n\n880\n";
+    let mut output = Vec::new();
+
+    let count = infer::repl(&model, &PhysicsConfig::new(), input, &mut output, &device).unwrap();
+
+    let output = String::from_utf8(output).unwrap();
+    let results: Vec<&str> = output.lines().filter(|line| line.contains("🎵")).collect();
+    assert_eq!(count, 2);
+    assert_eq!(results.len(), 2);
+    assert!(results[0].contains("440 Hz") && results[1].contains("880 Hz"));
+    assert!(output.contains("cannot parse frequency 'not-a-number'"));
+}
+
+#[test]
+fn test_parse_frequencies_accepts_commas_and_whitespace() {
+    let freqs = infer::parse_frequencies("440, 880,1760\n3520").unwrap();
+
+    assert_eq!(freqs, vec![440.0, 880.0, 1760.0, 3520.0]);
+}
+
+#[test]
+fn test_parse_frequencies_rejects_garbage() {
+    let result = infer::parse_frequencies("440,abc");
+
+    assert!(matches!(result, Err(InferError::InvalidInput(_))));
+}
+
+#[test]
+fn test_csv_round_trip_three_frequencies() {
+    let input = "frequency\n440\n880\n1760\n";
+    let freqs = infer::read_frequencies_csv(input.as_bytes()).unwrap();
+    assert_eq!(freqs, vec![440.0, 880.0, 1760.0]);
+
+    let dims: Vec<ForkDimensions> = freqs
+        .iter()
+        .map(|f| ForkDimensions {
+            handle_length: 0.1,
+            handle_diameter: 0.01,
+            prong_length: 1.0 / f,
+            prong_diameter: 0.005,
+            prong_gap: 0.01,
+        })
+        .collect();
+
+    let mut buffer = Vec::new();
+    infer::write_dimensions_csv(&mut buffer, &freqs, &dims).unwrap();
+    let written = String::from_utf8(buffer).unwrap();
+
+    let mut lines = written.lines();
+    assert_eq!(
+        lines.next(),
+        Some("frequency,handle_length,handle_diameter,prong_length,prong_diameter,prong_gap")
+    );
+    assert_eq!(lines.count(), 3);
+
+    // 書き出したCSVを再度読み込むと、同じ周波数列が得られる
+    let reread = infer::read_frequencies_csv(written.as_bytes()).unwrap();
+    assert_eq!(reread, freqs);
+}
+
+#[test]
+fn test_read_frequencies_csv_without_header() {
+    let freqs = infer::read_frequencies_csv("440\n880\n".as_bytes()).unwrap();
+
+    assert_eq!(freqs, vec![440.0, 880.0]);
+}
+
+#[test]
+fn test_read_frequencies_csv_rejects_invalid_row() {
+    let result = infer::read_frequencies_csv("frequency\n440\nabc\n".as_bytes());
+
+    assert!(matches!(result, Err(InferError::CsvParse(_))));
+}
+
+#[test]
+fn test_inference_report_computes_absolute_error() {
+    let dims = ForkDimensions {
+        handle_length: 0.1,
+        handle_diameter: 0.01,
+        prong_length: 0.08,
+        prong_diameter: 0.005,
+        prong_gap: 0.01,
+    };
+
+    let report = InferenceReport::new(440.0, dims, 433.0);
+
+    assert!((report.frequency_error - 7.0).abs() < 1e-4);
+    assert!(report.exceeds(5.0));
+    assert!(!report.exceeds(10.0));
+}
+
+#[test]
+fn test_millimeters_are_meters_times_1000() {
+    let dims = ForkDimensions {
+        handle_length: 0.075,
+        handle_diameter: 0.008,
+        prong_length: 0.069,
+        prong_diameter: 0.0048,
+        prong_gap: 0.0051,
+    };
+
+    let meters = dims.to_unit(Unit::Meters);
+    let millimeters = dims.to_unit(Unit::Millimeters);
+
+    assert_eq!(meters, dims);
+    let pairs = [
+        (meters.handle_length, millimeters.handle_length),
+        (meters.handle_diameter, millimeters.handle_diameter),
+        (meters.prong_length, millimeters.prong_length),
+        (meters.prong_diameter, millimeters.prong_diameter),
+        (meters.prong_gap, millimeters.prong_gap),
+    ];
+    for (m, mm) in pairs {
+        assert_eq!(mm, m * 1000.0);
+    }
+    assert_eq!("mm".parse::<Unit>(), Ok(Unit::Millimeters));
+}
+
+#[test]
+fn test_sweep_frequencies_includes_both_ends() {
+    let freqs = infer::sweep_frequencies(200.0, 2000.0, 20.0).unwrap();
+
+    assert_eq!(freqs.len(), 91);
+    assert_eq!(freqs[0], 200.0);
+    assert_eq!(freqs[1], 220.0);
+    assert!((freqs[90] - 2000.0).abs() < 1e-3);
+}
+
+#[test]
+fn test_sweep_frequencies_rejects_invalid_range() {
+    assert!(matches!(
+        infer::sweep_frequencies(2000.0, 200.0, 20.0),
+        Err(InferError::InvalidInput(_))
+    ));
+    assert!(matches!(
+        infer::sweep_frequencies(200.0, 2000.0, 0.0),
+        Err(InferError::InvalidInput(_))
+    ));
+    assert!(matches!(
+        infer::sweep_frequencies(200.0, 2000.0, -5.0),
+        Err(InferError::InvalidInput(_))
+    ));
+}
+
+#[test]
+fn test_uncertainty_without_dropout_has_zero_std_dev() {
+    let device: NdArrayDevice = Default::default();
+    let model = ModelConfig::new()
+        .with_dropout(0.0)
+        .init::<Autodiff<B>>(&device);
+    let physics = PhysicsConfig::new();
+
+    let uncertainty =
+        infer::predict_with_uncertainty(&model, 440.0, 16, &physics, &device).unwrap();
+
+    let std_dev = uncertainty.std_dev;
+    for value in [
+        std_dev.handle_length,
+        std_dev.handle_diameter,
+        std_dev.prong_length,
+        std_dev.prong_diameter,
+        std_dev.prong_gap,
+    ] {
+        // 全サンプルが同一なので、平均の丸め誤差を除いて0になる
+        assert!(value.abs() < 1e-6, "std dev: {value}");
+    }
+}
+
+#[test]
+fn test_uncertainty_with_dropout_varies_across_samples() {
+    let device: NdArrayDevice = Default::default();
+    let model = ModelConfig::new()
+        .with_dropout(0.5)
+        .init::<Autodiff<B>>(&device);
+    let physics = PhysicsConfig::new();
+
+    let uncertainty =
+        infer::predict_with_uncertainty(&model, 440.0, 64, &physics, &device).unwrap();
+
+    assert!(uncertainty.std_dev.prong_length > 0.0);
+}
+
+#[test]
+fn test_uncertainty_uses_the_given_material() {
+    let device: NdArrayDevice = Default::default();
+    let model = TrainingConfig::new()
+        .with_material_input(true)
+        .model_config()
+        .init::<Autodiff<B>>(&device);
+    let brass = PhysicsConfig::new().with_material(Material::Brass);
+
+    let uncertainty =
+        infer::predict_with_uncertainty(&model, 440.0, 4, &brass, &device).unwrap();
+
+    let expected = model.predict_batch_with_physics(&[440.0], &brass, &device);
+    let expected = expected.unwrap().remove(0).to_array();
+    for (mean, expected) in uncertainty.mean.to_array().iter().zip(expected) {
+        assert!((mean - expected).abs() < 1e-6 * expected.abs().max(1.0));
+    }
+}
+
+#[test]
+fn test_uncertainty_rejects_zero_samples() {
+    let device: NdArrayDevice = Default::default();
+    let model = ModelConfig::new().init::<Autodiff<B>>(&device);
+    let physics = PhysicsConfig::new();
+
+    let result = infer::predict_with_uncertainty(&model, 440.0, 0, &physics, &device);
+
+    assert!(matches!(result, Err(InferError::InvalidInput(_))));
+}
+
+#[test]
+fn test_sensitivity_matches_finite_difference() {
+    let device: NdArrayDevice = Default::default();
+    let model = ModelConfig::new()
+        .with_activation(Activation::Tanh)
+        .init::<Autodiff<B>>(&device);
+    let (freq, step) = (440.0, 1.0);
+
+    let gradients = infer::sensitivity(&model, freq, &PhysicsConfig::new(), &device).unwrap();
+
+    let upper = model.predict(freq + step, &device).unwrap().to_array();
+    let lower = model.predict(freq - step, &device).unwrap().to_array();
+    for (i, gradient) in gradients.iter().enumerate() {
+        let finite_difference = (upper[i] - lower[i]) / (2.0 * step);
+        let tolerance = 1e-2 * finite_difference.abs() + 1e-6;
+        assert!(
+            (gradient - finite_difference).abs() < tolerance,
+            "output {i}: autodiff {gradient}, finite difference {finite_difference}"
+        );
+    }
+}
+
+#[test]
+fn test_sensitivity_rejects_batch_norm_models() {
+    let device: NdArrayDevice = Default::default();
+    let model = ModelConfig::new()
+        .with_batch_norm(true)
+        .init::<Autodiff<B>>(&device);
+
+    let result = infer::sensitivity(&model, 440.0, &PhysicsConfig::new(), &device);
+
+    assert!(matches!(result, Err(InferError::InvalidInput(_))));
+}
+
+#[test]
+fn test_validate_reports_out_of_range_prong_diameter() {
+    let dims = ForkDimensions {
+        handle_length: 0.10,
+        handle_diameter: 0.01,
+        prong_length: 0.08,
+        prong_diameter: 0.03,
+        prong_gap: 0.01,
+    };
+
+    let violations = dims.validate();
+
+    assert_eq!(violations.len(), 1);
+    assert_eq!(violations[0].field, "prong_diameter");
+    assert_eq!(violations[0].value, 0.03);
+    assert_eq!((violations[0].min, violations[0].max), (0.002, 0.02));
+}
+
+#[test]
+fn test_clamped_report_moves_out_of_range_diameter_to_boundary() {
+    let dims = ForkDimensions {
+        handle_length: 0.10,
+        handle_diameter: 0.01,
+        prong_length: 0.08,
+        prong_diameter: 0.03,
+        prong_gap: 0.01,
+    };
+    let config = PhysicsConfig::new();
+    let realized = physics::frequency_from_dims_with_config(&dims, &config);
+    let report = InferenceReport::new(440.0, dims, realized);
+
+    let clamped = report.with_clamped_dimensions(&config);
+
+    assert_eq!(clamped.dimensions.prong_diameter, bounds::PRONG_DIAMETER_MAX);
+    assert_eq!(clamped.dimensions.prong_length, dims.prong_length);
+    assert!(clamped.dimensions.validate().is_empty());
+    assert_eq!(clamped.unclamped_dimensions, Some(dims));
+    let flagged = clamped.clamped_fields();
+    assert_eq!(flagged.len(), 1);
+    assert_eq!(flagged[0].field, "prong_diameter");
+    assert_eq!(flagged[0].value, 0.03);
+    let expected = physics::frequency_from_dims_with_config(&clamped.dimensions, &config);
+    assert_eq!(clamped.realized_frequency, expected);
+    assert!(report.clamped_fields().is_empty());
+}
+
+#[test]
+fn test_validate_accepts_dimensions_within_bounds() {
+    let dims = ForkDimensions {
+        handle_length: 0.10,
+        handle_diameter: 0.01,
+        prong_length: 0.08,
+        prong_diameter: 0.005,
+        prong_gap: 0.01,
+    };
+
+    assert!(dims.validate().is_empty());
+}
+
+#[test]
+fn test_report_json_round_trips() {
+    let dims = ForkDimensions {
+        handle_length: 0.10,
+        handle_diameter: 0.01,
+        prong_length: 0.08,
+        prong_diameter: 0.005,
+        prong_gap: 0.01,
+    };
+    let report = InferenceReport::new(440.0, dims, 441.5);
+
+    let json = infer::to_json(&report, None, None, None);
+
+    let parsed: InferenceReport = serde_json::from_str(&json).unwrap();
+    assert_eq!(parsed.dimensions, dims);
+    assert_eq!(parsed.target_frequency, 440.0);
+    assert_eq!(parsed.realized_frequency, 441.5);
+    assert_eq!(parsed.frequency_error, 1.5);
+    assert!(!json.contains("uncertainty"));
+    assert!(!json.contains("sensitivity"));
+}
+
+#[test]
+fn test_report_json_includes_sensitivity() {
+    let dims = ForkDimensions {
+        handle_length: 0.10,
+        handle_diameter: 0.01,
+        prong_length: 0.08,
+        prong_diameter: 0.005,
+        prong_gap: 0.01,
+    };
+    let report = InferenceReport::new(440.0, dims, 441.5);
+    let gradients = [1e-4, -2e-5, -3e-4, 4e-6, 0.0];
+
+    let json = infer::to_json(&report, None, Some(&gradients), None);
+
+    let value: serde_json::Value = serde_json::from_str(&json).unwrap();
+    let sensitivity: ForkDimensions =
+        serde_json::from_value(value["sensitivity"].clone()).unwrap();
+    assert_eq!(sensitivity.to_array(), gradients);
+}
+
+#[test]
+fn test_sensitivity_uses_the_given_material() {
+    let device: NdArrayDevice = Default::default();
+    let model = TrainingConfig::new()
+        .with_material_input(true)
+        .model_config()
+        .init::<Autodiff<B>>(&device);
+    let brass = PhysicsConfig::new().with_material(Material::Brass);
+
+    let steel = infer::sensitivity(&model, 440.0, &PhysicsConfig::new(), &device).unwrap();
+    let brass = infer::sensitivity(&model, 440.0, &brass, &device).unwrap();
+
+    assert_ne!(steel, brass);
+}
+
+#[test]
+fn test_output_format_from_str() {
+    assert_eq!("json".parse::<OutputFormat>(), Ok(OutputFormat::Json));
+    assert_eq!("TEXT".parse::<OutputFormat>(), Ok(OutputFormat::Text));
+    assert!("yaml".parse::<OutputFormat>().is_err());
+}
+
+#[test]
+fn test_load_model_from_bincode_bytes() {
+    let device: NdArrayDevice = Default::default();
+    let path = std::env::temp_dir().join("tuningfork_pinn_infer_bytes_test");
+    let model = ModelConfig::new().init::<B>(&device);
+    let expected = model.predict(440.0, &device).unwrap();
+    record::save_record::<B, _>(RecordFormat::Bincode, model.into_record(), path.clone())
+        .unwrap();
+    let bytes = std::fs::read(path.with_extension("bin")).unwrap();
+
+    let loaded = infer::load_model_from_bytes::<B>(&bytes, None, &device).unwrap();
+
+    assert_eq!(loaded.predict(440.0, &device).unwrap(), expected);
+    std::fs::remove_file(path.with_extension("bin")).unwrap();
+}
+
+#[test]
+fn test_load_model_from_invalid_bytes_returns_record_load() {
+    let device: NdArrayDevice = Default::default();
+
+    let result = infer::load_model_from_bytes::<B>(&[0xff; 16], None, &device);
+
+    assert!(matches!(result, Err(InferError::RecordLoad(_))));
+}
+
+#[test]
+fn test_load_model_from_bytes_restores_model_config_from_json() {
+    let device: NdArrayDevice = Default::default();
+    let config = TrainingConfig::new().with_material_input(true);
+    let model = config.model_config().init::<B>(&device);
+    let physics = PhysicsConfig::new().with_material(Material::Brass);
+    let input = model.with_conditions(Tensor::from_floats([[440.0], [880.0]], &device), &physics);
+    let expected = model.forward(input.clone()).into_data().into_vec::<f32>().unwrap();
+    let bytes = infer::save_model_to_bytes(&model).unwrap();
+    let json = serde_json::to_string(&config).unwrap();
+
+    let loaded = infer::load_model_from_bytes::<B>(&bytes, Some(&json), &device).unwrap();
+
+    assert!(loaded.has_material_input());
+    let actual = loaded.forward(input).into_data().into_vec::<f32>().unwrap();
+    assert_eq!(actual, expected);
+}
+
+#[test]
+fn test_softplus_beta_is_restored_from_saved_training_config() {
+    let device: NdArrayDevice = Default::default();
+    let path = std::env::temp_dir().join("tuningfork_pinn_infer_softplus_beta_config.json");
+    let config = TrainingConfig::new().with_softplus_beta(10.0);
+    let model = config.model_config().init::<B>(&device);
+    let bytes = infer::save_model_to_bytes(&model).unwrap();
+    config.save(&path).unwrap();
+    let json = std::fs::read_to_string(&path).unwrap();
+
+    let loaded = infer::load_model_from_bytes::<B>(&bytes, Some(&json), &device).unwrap();
+    // βはレコードにも保存されるため、学習設定なしで読み込んでも復元される
+    let weights_only = infer::load_model_from_bytes::<B>(&bytes, None, &device).unwrap();
+
+    assert_eq!(loaded.softplus_beta(), 10.0);
+    assert_eq!(weights_only.softplus_beta(), 10.0);
+    std::fs::remove_file(&path).unwrap();
+}
+
+#[test]
+fn test_load_model_from_bytes_with_invalid_config_returns_config_load() {
+    let device: NdArrayDevice = Default::default();
+    let model = ModelConfig::new().init::<B>(&device);
+    let bytes = infer::save_model_to_bytes(&model).unwrap();
+
+    let result = infer::load_model_from_bytes::<B>(&bytes, Some("{not json"), &device);
+
+    assert!(matches!(result, Err(InferError::ConfigLoad(_))));
+}
+
+#[test]
+fn test_model_bytes_round_trip_reproduces_forward_pass() {
+    let device: NdArrayDevice = Default::default();
+    let model = ModelConfig::new().init::<B>(&device);
+    let input = Tensor::<B, 2>::from_floats([[200.0], [440.0], [1760.0]], &device);
+    let expected = model.forward(input.clone()).into_data().into_vec::<f32>().unwrap();
+
+    let bytes = infer::save_model_to_bytes(&model).unwrap();
+    let loaded = infer::load_model_from_bytes::<B>(&bytes, None, &device).unwrap();
+    let actual = loaded.forward(input).into_data().into_vec::<f32>().unwrap();
+
+    // バイト列は単精度で保存されるため、出力は完全に一致する
+    assert_eq!(actual, expected);
+}
+
+#[test]
+fn test_fork_dimensions_round_trip_through_json() {
+    let dims = ForkDimensions {
+        handle_length: 0.1,
+        handle_diameter: 0.01,
+        prong_length: 0.08,
+        prong_diameter: 0.005,
+        prong_gap: 0.01,
+    };
+
+    let json = dims.to_json();
+
+    assert!(json.contains("\"prong_length\""));
+    assert_eq!(ForkDimensions::from_json(&json).unwrap(), dims);
+    assert!(matches!(
+        ForkDimensions::from_json(r#"{"handle_length": 0.1}"#),
+        Err(InferError::InvalidInput(_))
+    ));
+}
+
+#[test]
+fn test_winner_has_the_smaller_frequency_error() {
+    let row = |error_a: f32, error_b: f32| ComparisonRow {
+        frequency: 440.0,
+        error_a,
+        error_b,
+    };
+    assert_eq!(row(1.0, 2.0).winner(), Winner::A);
+    assert_eq!(row(3.0, 2.0).winner(), Winner::B);
+    assert_eq!(row(2.0, 2.0).winner(), Winner::Tie);
+    // Aは勝ち数が多いが、1つの周波数で大きく外すため平均誤差では負ける
+    let rows = [row(1.0, 2.0), row(1.0, 2.0), row(30.0, 2.0)];
+    assert_eq!(infer::overall_winner(&rows), Winner::B);
+
+    // 解析解に当てはめたモデル (A) は、ランダムに初期化したモデル (B) より誤差が小さい
+    let device: NdArrayDevice = Default::default();
+    let artifact_dir = std::env::temp_dir().join("tuningfork_pinn_infer_compare_test");
+    let config = TrainingConfig::new();
+    let random = config.model_config().init::<B>(&device);
+    let warm = train::warm_start(random.clone(), &config, &device).unwrap();
+    let dir_a = save_model_to_temp_dir("tuningfork_pinn_infer_compare_test/a", &warm);
+    let dir_b = save_model_to_temp_dir("tuningfork_pinn_infer_compare_test/b", &random);
+    let (dir_a, dir_b) = (dir_a.to_str().unwrap(), dir_b.to_str().unwrap());
+    let freqs = [300.0, 440.0, 600.0, 880.0];
+
+    let rows = infer::run_compare::<B>(dir_a, dir_b, &freqs, 2, device).unwrap();
+    let self_rows = infer::run_compare::<B>(dir_a, dir_a, &freqs, 2, device).unwrap();
+
+    assert_eq!(rows.len(), freqs.len());
+    assert_eq!(infer::overall_winner(&rows), Winner::A);
+    assert!(self_rows.iter().all(|row| row.winner() == Winner::Tie));
+    std::fs::remove_dir_all(&artifact_dir).unwrap();
+}
+
+#[test]
+fn test_run_eval_with_empty_input_returns_invalid_input() {
+    let device: NdArrayDevice = Default::default();
+    let model = ModelConfig::new().init::<B>(&device);
+    let artifact_dir = save_model_to_temp_dir("tuningfork_pinn_infer_empty_eval_test", &model);
+    let freqs = infer::read_frequencies_csv("frequency\n".as_bytes()).unwrap();
+
+    let result = infer::run_eval::<B>(artifact_dir.to_str().unwrap(), &freqs, 2, device);
+
+    assert!(matches!(result, Err(InferError::InvalidInput(_))));
+    std::fs::remove_dir_all(&artifact_dir).unwrap();
+}
+
+#[test]
+fn test_run_eval_reports_mean_frequency_error_for_csv_targets() {
+    let device: NdArrayDevice = Default::default();
+    let model = ModelConfig::new().init::<B>(&device);
+    let artifact_dir = save_model_to_temp_dir("tuningfork_pinn_infer_eval_test", &model);
+    let csv = "frequency\n261.63\n440\n880\n";
+    let freqs = infer::read_frequencies_csv(csv.as_bytes()).unwrap();
+
+    let summary = infer::run_eval::<B>(artifact_dir.to_str().unwrap(), &freqs, 2, device).unwrap();
+
+    let physics = PhysicsConfig::new();
+    let errors: Vec<f32> = model
+        .predict_batch(&freqs, &device)
+        .unwrap()
+        .iter()
+        .zip(&freqs)
+        .map(|(dims, freq)| (physics::frequency_from_dims_with_config(dims, &physics) - freq).abs())
+        .collect();
+    let expected_mean = errors.iter().sum::<f32>() / errors.len() as f32;
+    assert_eq!(summary.num_predictions, 3);
+    assert!((summary.mean_error_hz - expected_mean).abs() < 1e-3 * expected_mean.max(1.0));
+    assert_eq!(summary.max_error_hz, errors.iter().copied().fold(0.0, f32::max));
+    assert!((0.0..=100.0).contains(&summary.validity_percent));
+    std::fs::remove_dir_all(&artifact_dir).unwrap();
+}
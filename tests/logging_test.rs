@@ -0,0 +1,31 @@
+//! ログの詳細度フラグに対するユニットテスト
+
+use clap::Parser;
+use log::LevelFilter;
+
+use burn_tuningfork_pinn::logging::Verbosity;
+
+/// `Verbosity`を組み込んだテスト用のコマンド
+#[derive(Parser, Debug)]
+struct TestCli {
+    #[command(flatten)]
+    verbosity: Verbosity,
+}
+
+fn parse(args: &[&str]) -> LevelFilter {
+    let cli = TestCli::try_parse_from(std::iter::once("test").chain(args.iter().copied())).unwrap();
+    cli.verbosity.level_filter()
+}
+
+#[test]
+fn test_double_verbose_yields_debug() {
+    assert_eq!(parse(&["-vv"]), LevelFilter::Debug);
+}
+
+#[test]
+fn test_verbosity_levels() {
+    assert_eq!(parse(&[]), LevelFilter::Warn);
+    assert_eq!(parse(&["-v"]), LevelFilter::Info);
+    assert_eq!(parse(&["--verbose", "--verbose", "--verbose"]), LevelFilter::Trace);
+    assert_eq!(parse(&["-q", "-vv"]), LevelFilter::Error);
+}
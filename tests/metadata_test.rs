@@ -0,0 +1,33 @@
+//! モデルのメタデータに対するユニットテスト
+
+use burn_tuningfork_pinn::constants::physics::Material;
+use burn_tuningfork_pinn::infer;
+use burn_tuningfork_pinn::metadata::{METADATA_FILE, ModelMetadata};
+use burn_tuningfork_pinn::train::TrainingConfig;
+
+#[test]
+fn test_metadata_round_trips_with_configured_material() {
+    let artifact_dir = std::env::temp_dir().join("tuningfork_pinn_metadata_test");
+    std::fs::create_dir_all(&artifact_dir).unwrap();
+    let config = TrainingConfig::new()
+        .with_material(Material::Brass)
+        .with_train_freq_min(300.0)
+        .with_train_freq_max(600.0);
+    let metadata = ModelMetadata::new(&config, Some(0.125));
+
+    metadata.save(artifact_dir.join(METADATA_FILE)).unwrap();
+    let loaded = infer::load_metadata(artifact_dir.to_str().unwrap())
+        .unwrap()
+        .unwrap();
+
+    assert_eq!(loaded, metadata);
+    assert_eq!(loaded.material, Material::Brass);
+    assert_eq!((loaded.train_freq_min, loaded.train_freq_max), (300.0, 600.0));
+    assert_eq!(loaded.crate_version, env!("CARGO_PKG_VERSION"));
+    std::fs::remove_dir_all(&artifact_dir).unwrap();
+}
+
+#[test]
+fn test_missing_metadata_loads_as_none() {
+    assert!(infer::load_metadata("./nonexistent_artifacts").unwrap().is_none());
+}
@@ -0,0 +1,139 @@
+//! カスタムメトリクスに対するユニットテスト
+
+use burn::backend::ndarray::{NdArray, NdArrayDevice};
+use burn::data::dataloader::Progress;
+use burn::tensor::Tensor;
+use burn::train::RegressionOutput;
+use burn::train::metric::{Metric, MetricMetadata, Numeric};
+
+use burn_tuningfork_pinn::metrics::{
+    EvaluationSummary, FrequencyErrorMetric, MetricLogger, PhysicalValidityMetric,
+    ToleranceReport, error_percentile,
+};
+use burn_tuningfork_pinn::physics::{PhysicsConfig, predicted_frequency_with_config};
+
+type B = NdArray<f32>;
+
+/// テスト用のメトリクスのメタデータを作成します。
+fn metadata() -> MetricMetadata {
+    MetricMetadata {
+        progress: Progress::new(1, 1),
+        epoch: 1,
+        epoch_total: 1,
+        iteration: 1,
+        lr: None,
+    }
+}
+
+#[test]
+fn test_frequency_error_metric_reports_mean_absolute_error_in_hz() {
+    let device: NdArrayDevice = Default::default();
+    let physics = PhysicsConfig::new();
+    let dims = Tensor::<B, 2>::from_floats(
+        [[0.10, 0.01, 0.08, 0.005, 0.01], [0.09, 0.012, 0.06, 0.006, 0.008]],
+        &device,
+    );
+    // 予測周波数から +10Hz と -30Hz ずらした目標周波数を与える
+    let predicted = predicted_frequency_with_config(dims.clone(), &physics)
+        .into_data()
+        .into_vec::<f32>()
+        .unwrap();
+    let targets =
+        Tensor::<B, 2>::from_floats([[predicted[0] + 10.0], [predicted[1] - 30.0]], &device);
+    let item = RegressionOutput {
+        loss: Tensor::zeros([1], &device),
+        output: dims,
+        targets,
+    };
+    let mut metric = FrequencyErrorMetric::<B>::new(physics);
+
+    metric.update(&item, &metadata());
+
+    assert!((metric.value() - 20.0).abs() < 1e-2, "value: {}", metric.value());
+}
+
+#[test]
+fn test_physical_validity_metric_reports_percentage_of_valid_rows() {
+    let device: NdArrayDevice = Default::default();
+    let dims = Tensor::<B, 2>::from_floats(
+        [
+            [0.10, 0.01, 0.08, 0.005, 0.01],
+            [0.09, 0.012, 0.06, 0.006, 0.008],
+            [0.10, 0.01, 0.08, 0.03, 0.01], // プロングの直径が上限 (0.02m) を超える
+        ],
+        &device,
+    );
+    let item = RegressionOutput {
+        loss: Tensor::zeros([1], &device),
+        output: dims,
+        targets: Tensor::<B, 2>::from_floats([[440.0], [440.0], [440.0]], &device),
+    };
+    let mut metric = PhysicalValidityMetric::<B>::new();
+
+    metric.update(&item, &metadata());
+
+    assert!((metric.value() - 66.7).abs() < 0.1, "value: {}", metric.value());
+}
+
+#[test]
+fn test_metric_logger_writes_header_and_one_row_per_epoch() {
+    let path = std::env::temp_dir().join("tuningfork_pinn_metric_logger_test.csv");
+    std::fs::write(&path, "stale contents\n").unwrap();
+
+    let logger = MetricLogger::create(&path).unwrap();
+    logger.log_epoch(1, Some(0.5), Some(0.25)).unwrap();
+
+    let contents = std::fs::read_to_string(&path).unwrap();
+    let lines: Vec<&str> = contents.lines().collect();
+    assert_eq!(lines, [MetricLogger::HEADER, "1,0.5,0.25"]);
+    std::fs::remove_file(&path).unwrap();
+}
+
+#[test]
+fn test_metric_logger_leaves_missing_values_empty() {
+    let path = std::env::temp_dir().join("tuningfork_pinn_metric_logger_missing_test.csv");
+
+    let logger = MetricLogger::create(&path).unwrap();
+    logger.log_epoch(2, Some(1.0), None).unwrap();
+
+    let contents = std::fs::read_to_string(&path).unwrap();
+    assert_eq!(contents.lines().nth(1), Some("2,1,"));
+    std::fs::remove_file(&path).unwrap();
+}
+
+#[test]
+fn test_tolerance_report_computes_percentage_within_tolerance() {
+    // 10件中、|誤差| ≤ 1Hz は6件 (境界値の±1.0を含む)
+    let errors = [0.1, -0.2, 0.5, -1.0, 1.0, 0.0, 1.5, -2.0, 3.0, -10.0];
+
+    let report = ToleranceReport::from_errors(&errors, 1.0);
+
+    assert_eq!(report.num_predictions, 10);
+    assert_eq!(report.num_within, 6);
+    assert!((report.percent_within() - 60.0).abs() < 1e-4);
+    assert!((report.mean_error_hz - 1.93).abs() < 1e-4);
+    assert!((report.median_error_hz - 1.0).abs() < 1e-6);
+    assert_eq!(ToleranceReport::from_errors(&[], 1.0).percent_within(), 0.0);
+}
+
+#[test]
+fn test_evaluation_summary_computes_percentiles_and_validity() {
+    let errors: Vec<f32> = (0..=100)
+        .map(|i| if i % 2 == 0 { i as f32 } else { -(i as f32) })
+        .collect();
+    let valid = [0.1, 0.01, 0.08, 0.005, 0.01];
+    let invalid = [1.0, 0.01, 0.08, 0.005, 0.01];
+
+    let dims: Vec<_> = (0..errors.len())
+        .map(|i| if i % 4 == 3 { invalid } else { valid })
+        .collect();
+
+    let summary = EvaluationSummary::new(&errors, &dims);
+
+    assert_eq!(summary.num_predictions, 101);
+    assert!((summary.mean_error_hz - 50.0).abs() < 1e-4);
+    assert_eq!(summary.max_error_hz, 100.0);
+    assert!((summary.p90_error_hz - 90.0).abs() < 1e-4);
+    assert!((summary.validity_percent - 100.0 * 76.0 / 101.0).abs() < 1e-4);
+    assert!((error_percentile(&[-1.0, 3.0], 50.0) - 2.0).abs() < 1e-6);
+}
@@ -0,0 +1,419 @@
+//! モデルのアーキテクチャに対するユニットテスト
+
+use burn::backend::Autodiff;
+use burn::backend::ndarray::{NdArray, NdArrayDevice};
+use burn::tensor::Tensor;
+use burn::tensor::backend::Backend;
+use burn::tensor::activation::{relu, softplus};
+
+use burn::module::{AutodiffModule, Module};
+use burn_tuningfork_pinn::constants::physics::Material;
+use burn_tuningfork_pinn::infer::InferError;
+use burn_tuningfork_pinn::model::{
+    Activation, InitKind, ModelConfig, TuningForkPINN, material_feature, temperature_feature,
+};
+use burn_tuningfork_pinn::physics::{CrossSectionKind, PhysicsConfig};
+use burn_tuningfork_pinn::train::TrainingConfig;
+
+type B = NdArray<f32>;
+
+#[test]
+fn test_forward_shape_for_each_activation() {
+    let device: NdArrayDevice = Default::default();
+    let activations = [
+        Activation::Relu,
+        Activation::Tanh,
+        Activation::Gelu,
+        Activation::SiLU,
+    ];
+
+    for activation in activations {
+        let model = ModelConfig::new()
+            .with_activation(activation.clone())
+            .init::<B>(&device);
+        let input = Tensor::<B, 2>::from_floats([[200.0], [440.0], [880.0], [1760.0]], &device);
+
+        let output = model.forward(input);
+
+        assert_eq!(output.dims(), [4, 5], "activation: {activation:?}");
+    }
+}
+
+#[test]
+fn test_num_parameters_for_default_architecture() {
+    let device: NdArrayDevice = Default::default();
+    let model = ModelConfig::new().init::<B>(&device);
+
+    // 1→128→128→128→5 の各層の重み + バイアスと、入力正規化の平均・標準偏差、softplusのβ
+    let expected = (128 + 128) + 2 * (128 * 128 + 128) + (128 * 5 + 5) + 3;
+
+    assert_eq!(model.num_parameters(), expected);
+}
+
+#[test]
+fn test_summary_lists_each_layer() {
+    let device: NdArrayDevice = Default::default();
+    let model = ModelConfig::new().init::<B>(&device);
+
+    let summary = model.summary();
+
+    assert!(summary.contains("hidden_layers.0"));
+    assert!(summary.contains("hidden_layers.2"));
+    assert!(summary.contains("output_layer"));
+    assert!(summary.contains(&format!("total params: {}", model.num_parameters())));
+}
+
+#[test]
+fn test_normalized_input_for_midpoint_frequency_is_near_zero() {
+    let device: NdArrayDevice = Default::default();
+    let config = TrainingConfig::new();
+    let model = config.model_config().init::<B>(&device);
+    let midpoint = (config.train_freq_min + config.train_freq_max) / 2.0;
+    let input = Tensor::<B, 2>::from_floats(
+        [[midpoint], [config.train_freq_min], [config.train_freq_max]],
+        &device,
+    );
+
+    let normalized = model
+        .normalize_input(input)
+        .into_data()
+        .into_vec::<f32>()
+        .unwrap();
+
+    // 一様分布の端は平均から ±√3 標準偏差の位置にある
+    let sqrt_3 = 3f32.sqrt();
+    assert!(normalized[0].abs() < 1e-4);
+    assert!((normalized[1] + sqrt_3).abs() < 1e-4);
+    assert!((normalized[2] - sqrt_3).abs() < 1e-4);
+}
+
+/// 2番目の隠れ層 (`hidden_size`→`hidden_size`) の重みの標本分散を計算します。
+fn hidden_weight_variance(init_kind: InitKind, hidden_size: usize) -> f32 {
+    let device: NdArrayDevice = Default::default();
+    let model = ModelConfig::new()
+        .with_hidden_size(hidden_size)
+        .with_init_kind(init_kind)
+        .init::<B>(&device);
+    let record = model.into_record();
+    let weights = record.hidden_layers[1]
+        .weight
+        .val()
+        .into_data()
+        .into_vec::<f32>()
+        .unwrap();
+
+    let n = weights.len() as f32;
+    let mean = weights.iter().sum::<f32>() / n;
+    weights.iter().map(|w| (w - mean).powi(2)).sum::<f32>() / n
+}
+
+#[test]
+fn test_kaiming_normal_weight_variance() {
+    let hidden_size = 512;
+
+    let variance = hidden_weight_variance(InitKind::KaimingNormal, hidden_size);
+
+    let expected = 2.0 / hidden_size as f32;
+    assert!(
+        (variance - expected).abs() / expected < 0.05,
+        "variance: {variance}, expected: {expected}"
+    );
+}
+
+#[test]
+fn test_xavier_uniform_weight_variance() {
+    let hidden_size = 512;
+
+    let variance = hidden_weight_variance(InitKind::XavierUniform, hidden_size);
+
+    let expected = 2.0 / (hidden_size + hidden_size) as f32;
+    assert!(
+        (variance - expected).abs() / expected < 0.05,
+        "variance: {variance}, expected: {expected}"
+    );
+}
+
+#[test]
+fn test_residual_changes_output_but_not_shape() {
+    let device: NdArrayDevice = Default::default();
+    let plain = ModelConfig::new().init::<B>(&device);
+    // 同じ重みで残差接続の有無だけを変える
+    let residual = ModelConfig::new()
+        .with_residual(true)
+        .init::<B>(&device)
+        .load_record(plain.clone().into_record());
+    let input = Tensor::<B, 2>::from_floats([[200.0], [440.0], [880.0], [1760.0]], &device);
+
+    let plain_output = plain.forward(input.clone());
+    let residual_output = residual.forward(input);
+
+    assert_eq!(plain_output.dims(), residual_output.dims());
+    let max_diff = (plain_output - residual_output)
+        .abs()
+        .max()
+        .into_scalar();
+    assert!(max_diff > 1e-6, "max difference: {max_diff}");
+}
+
+#[test]
+fn test_batch_norm_forward_in_train_and_eval_modes() {
+    let device: NdArrayDevice = Default::default();
+    let model = ModelConfig::new()
+        .with_batch_norm(true)
+        .init::<Autodiff<B>>(&device);
+    let input =
+        Tensor::<Autodiff<B>, 2>::from_floats([[200.0], [440.0], [880.0], [1760.0]], &device);
+
+    // 自動微分が有効なバックエンドではミニバッチの統計量を使う (学習モード)
+    let train_output = model.forward(input.clone());
+    // `valid`で得られるモデルは移動平均を使う (推論モード)
+    let eval_output = model.valid().forward(input.inner());
+
+    assert_eq!(train_output.dims(), [4, 5]);
+    assert_eq!(eval_output.dims(), [4, 5]);
+    let finite = |values: Vec<f32>| values.iter().all(|value| value.is_finite());
+    assert!(finite(train_output.into_data().to_vec().unwrap()));
+    assert!(finite(eval_output.into_data().to_vec().unwrap()));
+}
+
+#[test]
+fn test_predict_matches_forward() {
+    let device: NdArrayDevice = Default::default();
+    let model = ModelConfig::new().init::<B>(&device);
+
+    let dims = model.predict(440.0, &device).unwrap();
+    let output: Vec<f32> = model
+        .forward(Tensor::<B, 2>::from_floats([[440.0]], &device))
+        .into_data()
+        .to_vec()
+        .unwrap();
+
+    assert_eq!(
+        [
+            dims.handle_length,
+            dims.handle_diameter,
+            dims.prong_length,
+            dims.prong_diameter,
+            dims.prong_gap,
+        ],
+        output.as_slice()
+    );
+}
+
+#[test]
+fn test_predict_batch_of_single_frequency_equals_predict() {
+    let device: NdArrayDevice = Default::default();
+    let model = ModelConfig::new().init::<B>(&device);
+
+    let batch = model.predict_batch(&[440.0], &device).unwrap();
+
+    assert_eq!(batch, vec![model.predict(440.0, &device).unwrap()]);
+    assert!(model.predict_batch(&[], &device).unwrap().is_empty());
+}
+
+#[test]
+fn test_predict_with_rectangular_model_returns_error() {
+    let device: NdArrayDevice = Default::default();
+    let config = TrainingConfig::new().with_cross_section(CrossSectionKind::Rectangular);
+    let model = config.model_config().init::<B>(&device);
+
+    assert!(matches!(
+        model.predict(440.0, &device),
+        Err(InferError::RecordLoad(_))
+    ));
+    assert!(matches!(
+        model.predict_batch(&[], &device),
+        Err(InferError::RecordLoad(_))
+    ));
+}
+
+#[test]
+fn test_fit_output_layer_ignores_dropout() {
+    let device: NdArrayDevice = Default::default();
+    let model = ModelConfig::new()
+        .with_dropout(0.5)
+        .init::<Autodiff<B>>(&device);
+    let freqs: Vec<f32> = (0..16).map(|i| 200.0 + i as f32 * 100.0).collect();
+    let input = Tensor::<Autodiff<B>, 1>::from_floats(freqs.as_slice(), &device).reshape([-1, 1]);
+    let targets = vec![vec![0.05, 0.005, 0.1, 0.005, 0.01]; freqs.len()];
+
+    // ドロップアウトのマスクが当てはめに入ると、同じ入力でも係数が毎回変わる
+    let fit = || {
+        let fitted = model.clone().fit_output_layer(input.clone(), &targets, 1e-3).unwrap();
+        fitted.valid().forward(input.clone().inner()).into_data()
+    };
+
+    assert_eq!(fit(), fit());
+}
+
+#[test]
+fn test_fit_output_layer_with_singular_system_returns_error() {
+    let device: NdArrayDevice = Default::default();
+    let model = ModelConfig::new().init::<B>(&device);
+    let input = Tensor::<B, 2>::from_floats([[440.0], [880.0]], &device);
+    let targets = vec![vec![0.05, 0.005, 0.1, 0.005, 0.01]; 2];
+
+    // 正則化がなく、標本数が隠れ層の特徴量の数より少ないため係数が定まらない
+    let result = model.fit_output_layer(input, &targets, 0.0);
+
+    assert!(matches!(result, Err(InferError::InvalidInput(_))));
+}
+
+#[test]
+fn test_predict_batch_with_material_input_model_returns_error() {
+    let device: NdArrayDevice = Default::default();
+    let model = TrainingConfig::new()
+        .with_material_input(true)
+        .model_config()
+        .init::<B>(&device);
+    let physics = PhysicsConfig::new().with_material(Material::Brass);
+
+    assert!(matches!(
+        model.predict_batch(&[440.0], &device),
+        Err(InferError::InvalidInput(_))
+    ));
+    assert_eq!(
+        model.predict_batch_with_physics(&[440.0], &physics, &device).unwrap().len(),
+        1
+    );
+}
+
+#[test]
+fn test_q_factor_model_forward_returns_six_outputs() {
+    let device: NdArrayDevice = Default::default();
+    let config = TrainingConfig::new().with_predict_q_factor(true);
+    let model = config.model_config().init::<B>(&device);
+    let input = Tensor::<B, 2>::from_floats([[200.0], [440.0], [880.0]], &device);
+
+    let output = model.forward(input);
+
+    assert_eq!(output.dims(), [3, 6]);
+    assert!(model.has_q_factor());
+    assert_eq!(model.output_size(), 5);
+    assert_eq!(model.predict_batch(&[440.0, 880.0], &device).unwrap().len(), 2);
+    let q_factors = model
+        .predict_q_factor_batch(&[440.0, 880.0], &PhysicsConfig::new(), &device)
+        .unwrap();
+    assert_eq!(q_factors.len(), 2);
+    assert!(q_factors.iter().all(|q| q.is_finite() && *q >= 1.0));
+
+    let model = ModelConfig::new().init::<B>(&device);
+    assert!(!model.has_q_factor());
+    assert!(model.predict_q_factor_batch(&[440.0], &PhysicsConfig::new(), &device).is_none());
+}
+
+#[test]
+fn test_softplus_of_raw_output_matches_forward() {
+    let device: NdArrayDevice = Default::default();
+    let model = ModelConfig::new().with_q_factor(true).init::<B>(&device);
+    let input = Tensor::<B, 2>::from_floats([[200.0], [440.0], [1800.0]], &device);
+
+    let raw = model.forward_raw(input.clone());
+    let output = model.forward(input);
+
+    assert_eq!(raw.dims(), output.dims());
+    let diff = (softplus(raw, 1.0) - output).abs().max().into_scalar();
+    assert!(diff < 1e-6, "softplus(forward_raw(x)) should match forward(x): {diff}");
+}
+
+#[test]
+fn test_load_record_restores_softplus_beta() {
+    let device: NdArrayDevice = Default::default();
+    let sharp = ModelConfig::new()
+        .with_softplus_beta(10.0)
+        .init::<B>(&device);
+
+    let loaded = ModelConfig::new()
+        .init::<B>(&device)
+        .load_record(sharp.into_record());
+
+    assert_eq!(loaded.softplus_beta(), 10.0);
+}
+
+#[test]
+fn test_larger_softplus_beta_is_closer_to_relu() {
+    let device: NdArrayDevice = Default::default();
+    // 同じシードで初期化して重みを揃える (βはレコードに含まれるため、レコードでは揃えられない)
+    B::seed(&device, 0);
+    let smooth = ModelConfig::new().init::<B>(&device);
+    B::seed(&device, 0);
+    let sharp = ModelConfig::new()
+        .with_softplus_beta(10.0)
+        .init::<B>(&device);
+    assert_eq!(smooth.softplus_beta(), 1.0);
+    assert_eq!(sharp.softplus_beta(), 10.0);
+
+    let input = Tensor::<B, 2>::from_floats([[200.0], [440.0], [1000.0], [1800.0]], &device);
+    let raw = smooth.forward_raw(input.clone());
+    // 重みが同じなのでsoftplusを適用する前の値も同じ
+    let raw_diff = (sharp.forward_raw(input.clone()) - raw.clone()).abs().max().into_scalar();
+    assert!(raw_diff < 1e-6, "raw outputs should not depend on beta: {raw_diff}");
+
+    // softplus_β(x) − relu(x) = ln(1 + e^(−β|x|)) / β は、0付近の小さな入力で最も大きい
+    let gap = |model: &TuningForkPINN<B>| {
+        (model.forward(input.clone()) - relu(raw.clone())).abs().max().into_scalar()
+    };
+    let (smooth_gap, sharp_gap) = (gap(&smooth), gap(&sharp));
+    assert!(
+        sharp_gap < smooth_gap,
+        "beta = 10 should be closer to relu than beta = 1: {sharp_gap} vs {smooth_gap}"
+    );
+    assert!(sharp_gap <= std::f32::consts::LN_2 / 10.0 + 1e-6, "gap too large: {sharp_gap}");
+}
+
+#[test]
+fn test_material_input_model_accepts_two_column_input() {
+    let device: NdArrayDevice = Default::default();
+    let model = TrainingConfig::new()
+        .with_material_input(true)
+        .model_config()
+        .init::<B>(&device);
+    let input = Tensor::<B, 2>::from_floats(
+        [
+            [440.0, material_feature(Material::Steel304)],
+            [440.0, material_feature(Material::Brass)],
+            [880.0, material_feature(Material::Aluminum)],
+        ],
+        &device,
+    );
+
+    let output = model.forward(input);
+
+    assert!(model.has_material_input());
+    assert_eq!(output.dims(), [3, 5]);
+    let frequencies = Tensor::<B, 2>::from_floats([[440.0], [880.0]], &device);
+    let brass = PhysicsConfig::new().with_material(Material::Brass);
+    assert_eq!(model.with_conditions(frequencies, &brass).dims(), [2, 2]);
+    let steel = model
+        .predict_batch_with_physics(&[440.0], &PhysicsConfig::new(), &device)
+        .unwrap();
+    let brass = model.predict_batch_with_physics(&[440.0], &brass, &device).unwrap();
+    assert_ne!(steel, brass);
+}
+
+#[test]
+fn test_temperature_input_model_accepts_two_column_input() {
+    let device: NdArrayDevice = Default::default();
+    let model = TrainingConfig::new()
+        .with_temperature_input(true)
+        .model_config()
+        .init::<B>(&device);
+    let input = Tensor::<B, 2>::from_floats(
+        [
+            [440.0, temperature_feature(-10.0)],
+            [440.0, temperature_feature(20.0)],
+            [880.0, temperature_feature(50.0)],
+        ],
+        &device,
+    );
+
+    let output = model.forward(input);
+
+    assert!(model.has_temperature_input());
+    assert_eq!(model.input_size(), 2);
+    assert_eq!(output.dims(), [3, 5]);
+    assert_eq!(temperature_feature(20.0), 0.0);
+    let frequencies = Tensor::<B, 2>::from_floats([[440.0], [880.0]], &device);
+    let hot = PhysicsConfig::new().with_temperature(50.0);
+    assert_eq!(model.with_conditions(frequencies, &hot).dims(), [2, 2]);
+}
@@ -0,0 +1,30 @@
+//! Pythonバインディングに対するユニットテスト (`python`フィーチャー)
+
+use burn::backend::ndarray::{NdArray, NdArrayDevice};
+use burn::module::Module;
+use burn::record::{CompactRecorder, Recorder};
+
+use burn_tuningfork_pinn::model::ModelConfig;
+use burn_tuningfork_pinn::python;
+
+type B = NdArray<f32>;
+
+#[test]
+fn test_predict_returns_five_dimensions() {
+    let device: NdArrayDevice = Default::default();
+    let artifact_dir = std::env::temp_dir().join("tuningfork_pinn_python_predict_test");
+    std::fs::create_dir_all(&artifact_dir).unwrap();
+    let model = ModelConfig::new().init::<B>(&device);
+    CompactRecorder::new()
+        .record(model.into_record(), artifact_dir.join("model"))
+        .unwrap();
+    let artifact_dir_str = artifact_dir.to_str().unwrap();
+
+    let dims = python::predict(440.0, artifact_dir_str).unwrap();
+    let batch = python::predict_batch(vec![440.0, 880.0], artifact_dir_str).unwrap();
+
+    assert_eq!(dims.len(), 5);
+    assert_eq!(batch.len(), 2);
+    assert_eq!(batch[0], dims);
+    std::fs::remove_dir_all(&artifact_dir).unwrap();
+}
@@ -0,0 +1,46 @@
+//! モデルレコードの保存形式に対するユニットテスト
+
+use burn::backend::ndarray::{NdArray, NdArrayDevice};
+use burn::module::Module;
+use burn::tensor::Tensor;
+
+use burn_tuningfork_pinn::model::{ModelConfig, TuningForkPINN};
+use burn_tuningfork_pinn::record::{self, RecordFormat};
+
+type B = NdArray<f32>;
+
+#[test]
+fn test_json_record_reproduces_identical_forward_pass() {
+    let device: NdArrayDevice = Default::default();
+    let path = std::env::temp_dir().join("tuningfork_pinn_json_record_test");
+    let model = ModelConfig::new().init::<B>(&device);
+    let input = Tensor::<B, 2>::from_floats([[200.0], [440.0], [1760.0]], &device);
+    let expected = model.forward(input.clone()).into_data().into_vec::<f32>().unwrap();
+
+    record::save_record::<B, _>(RecordFormat::PrettyJson, model.into_record(), path.clone())
+        .unwrap();
+    let loaded_record = record::load_record::<B, _>(RecordFormat::PrettyJson, path.clone(), &device)
+        .unwrap();
+    let loaded = TuningForkPINN::<B>::new(&device).load_record(loaded_record);
+    let actual = loaded.forward(input).into_data().into_vec::<f32>().unwrap();
+
+    // JSON は単精度で保存されるため、出力は完全に一致する
+    assert_eq!(actual, expected);
+    assert!(path.with_extension("json").exists());
+    std::fs::remove_file(path.with_extension("json")).unwrap();
+}
+
+#[test]
+fn test_record_format_is_detected_from_extension() {
+    assert_eq!(RecordFormat::from_path("./artifacts/model.mpk"), Some(RecordFormat::NamedMpk));
+    assert_eq!(RecordFormat::from_path("./artifacts/model.bin"), Some(RecordFormat::Bincode));
+    assert_eq!(RecordFormat::from_path("./artifacts/model.json"), Some(RecordFormat::PrettyJson));
+    assert_eq!(RecordFormat::from_path("./artifacts/model"), None);
+}
+
+#[test]
+fn test_record_format_from_str() {
+    assert_eq!("json".parse::<RecordFormat>(), Ok(RecordFormat::PrettyJson));
+    assert_eq!("bincode".parse::<RecordFormat>(), Ok(RecordFormat::Bincode));
+    assert!("yaml".parse::<RecordFormat>().is_err());
+}
@@ -0,0 +1,48 @@
+//! 推論サーバーに対するユニットテスト (`server`フィーチャー)
+
+use axum::body::{Body, to_bytes};
+use axum::http::{Request, StatusCode};
+use burn::backend::ndarray::{NdArray, NdArrayDevice};
+use tower::ServiceExt;
+
+use burn_tuningfork_pinn::infer::ForkDimensions;
+use burn_tuningfork_pinn::model::ModelConfig;
+use burn_tuningfork_pinn::physics::PhysicsConfig;
+use burn_tuningfork_pinn::server::{self, AppState, ErrorResponse};
+
+type B = NdArray<f32>;
+
+/// `uri`へのGETリクエストをルーターで処理し、ステータスと本文を返します。
+fn get(uri: &str) -> (StatusCode, Vec<u8>) {
+    let device: NdArrayDevice = Default::default();
+    let model = ModelConfig::new().init::<B>(&device);
+    let state = AppState::from_model(model, PhysicsConfig::new(), device).unwrap();
+    let router = server::router(state);
+    let request = Request::builder().uri(uri).body(Body::empty()).unwrap();
+
+    tokio::runtime::Runtime::new().unwrap().block_on(async {
+        let response = router.oneshot(request).await.unwrap();
+        let status = response.status();
+        let body = to_bytes(response.into_body(), usize::MAX).await.unwrap();
+        (status, body.to_vec())
+    })
+}
+
+#[test]
+fn test_predict_returns_dimensions_as_json() {
+    let (status, body) = get("/predict?freq=440");
+
+    assert_eq!(status, StatusCode::OK);
+    let dims: ForkDimensions = serde_json::from_slice(&body).unwrap();
+    assert!(dims.handle_length > 0.0);
+    assert!(dims.prong_gap > 0.0);
+}
+
+#[test]
+fn test_predict_rejects_invalid_frequency() {
+    let (status, body) = get("/predict?freq=-1");
+
+    assert_eq!(status, StatusCode::BAD_REQUEST);
+    let error: ErrorResponse = serde_json::from_slice(&body).unwrap();
+    assert!(error.error.contains("frequency"), "{}", error.error);
+}
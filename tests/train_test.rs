@@ -0,0 +1,796 @@
+//! 学習設定とデータパイプラインに対するユニットテスト
+
+
+use burn_tuningfork_pinn::constants::physics::*;
+use burn::backend::Autodiff;
+use burn::backend::ndarray::{NdArray, NdArrayDevice};
+use burn::config::Config;
+use burn::data::dataloader::batcher::Batcher;
+use burn::data::dataset::Dataset;
+use burn::grad_clipping::GradientClippingConfig;
+use burn::lr_scheduler::LrScheduler;
+use burn::module::Module;
+use burn::record::{CompactRecorder, Recorder};
+use burn::tensor::Tensor;
+use burn::tensor::backend::Backend;
+use burn::train::{TrainStep, ValidStep};
+use burn_tuningfork_pinn::model::{ModelConfig, TuningForkPINN};
+use burn_tuningfork_pinn::physics::{
+    CrossSectionKind, FrequencyLossKind, frequency_from_dims_with_config, tuning_fork_loss,
+};
+use burn_tuningfork_pinn::train::{
+    FixedDataset, FrequencySplit, GridFrequencyDataset, LrSchedule, NormalizingBatcher, Optimizer,
+    PENALTY_WARMUP_START, PinnOutput, PlateauTracker, SamplingMode, TRAIN_BATCHES_PER_EPOCH,
+    TrainError, TrainingConfig, TuningForkBatcher, TuningForkDataset, best_checkpoint_path,
+    dry_run, evaluate_tolerance, frequency_bin, init_model, warm_start,
+};
+use burn_tuningfork_pinn::record::RecordFormat;
+
+type B = NdArray<f32>;
+
+#[test]
+fn test_training_config_threads_penalty_weights_into_loss_config() {
+    let config = TrainingConfig::new().with_penalty_weight_ratio(2.0);
+
+    let loss_config = config.loss_config();
+
+    assert_eq!(loss_config.penalty_weight_ratio, 2.0);
+    assert_eq!(loss_config.penalty_weight_range, PENALTY_WEIGHT_RANGE);
+    assert_eq!(loss_config.penalty_weight_other, PENALTY_WEIGHT_OTHER);
+}
+
+#[test]
+fn test_rectangular_cross_section_widens_model_output() {
+    let config = TrainingConfig::new();
+    assert_eq!(config.model_config().output_size, 5);
+
+    let config = config.with_cross_section(CrossSectionKind::Rectangular);
+
+    assert_eq!(config.model_config().output_size, 6);
+    assert_eq!(config.loss_config().physics.cross_section, CrossSectionKind::Rectangular);
+}
+
+#[test]
+fn test_artifact_dir_defaults_to_artifacts() {
+    let config = TrainingConfig::new();
+    assert_eq!(config.artifact_dir, "./artifacts");
+
+    let config = config.with_artifact_dir("./artifacts_440".to_string());
+    assert_eq!(config.artifact_dir, "./artifacts_440");
+}
+
+#[test]
+fn test_training_config_round_trips_through_json() {
+    let path = std::env::temp_dir().join("tuningfork_pinn_config_round_trip.json");
+    let config = TrainingConfig::new()
+        .with_material(Material::Brass)
+        .with_mode(3)
+        .with_normalize_frequency_loss(false)
+        .with_train_freq_min(300.0)
+        .with_grad_clip(Some(1.0));
+
+    config.save(&path).unwrap();
+    let loaded = TrainingConfig::load(&path).unwrap();
+
+    assert_eq!(loaded.material, Material::Brass);
+    assert_eq!(loaded.mode, 3);
+    assert!(!loaded.normalize_frequency_loss);
+    assert_eq!(loaded.train_freq_min, 300.0);
+    assert_eq!(loaded.grad_clip, Some(1.0));
+    assert_eq!(loaded.to_string(), config.to_string());
+    std::fs::remove_file(&path).unwrap();
+}
+
+#[test]
+fn test_validate_accepts_default_frequency_ranges() {
+    let config = TrainingConfig::new();
+
+    assert!(config.validate().is_ok());
+}
+
+#[test]
+fn test_validate_rejects_inverted_frequency_range() {
+    let config = TrainingConfig::new()
+        .with_train_freq_min(1000.0)
+        .with_train_freq_max(500.0);
+
+    assert!(matches!(config.validate(), Err(TrainError::InvalidConfig(_))));
+}
+
+#[test]
+fn test_validate_rejects_non_positive_frequency_range() {
+    let config = TrainingConfig::new()
+        .with_train_freq_min(-10.0)
+        .with_train_freq_max(100.0);
+
+    assert!(matches!(config.validate(), Err(TrainError::InvalidConfig(_))));
+}
+
+#[test]
+fn test_validate_rejects_non_positive_huber_delta() {
+    let config = TrainingConfig::new()
+        .with_frequency_loss(FrequencyLossKind::Huber { delta: 0.0 });
+
+    assert!(matches!(config.validate(), Err(TrainError::InvalidConfig(_))));
+}
+
+#[test]
+fn test_validate_rejects_zero_epochs_or_batch_size() {
+    let zero_epochs = TrainingConfig::new().with_num_epochs(0);
+    let zero_batch = TrainingConfig::new().with_batch_size(0);
+    // ワーカー数の0は同期的な読み込みを表す
+    let zero_workers = TrainingConfig::new().with_num_workers(0);
+
+    assert!(matches!(zero_epochs.validate(), Err(TrainError::InvalidConfig(_))));
+    assert!(matches!(zero_batch.validate(), Err(TrainError::InvalidConfig(_))));
+    assert!(zero_workers.validate().is_ok());
+}
+
+#[test]
+fn test_validate_rejects_non_positive_learning_rate() {
+    let config = TrainingConfig::new().with_learning_rate(0.0);
+
+    assert!(matches!(config.validate(), Err(TrainError::InvalidConfig(_))));
+}
+
+#[test]
+fn test_validate_rejects_empty_hidden_layers() {
+    let no_units = TrainingConfig::new().with_hidden_size(0);
+    let no_layers = TrainingConfig::new().with_num_hidden_layers(0);
+
+    assert!(matches!(no_units.validate(), Err(TrainError::InvalidConfig(_))));
+    assert!(matches!(no_layers.validate(), Err(TrainError::InvalidConfig(_))));
+}
+
+#[test]
+fn test_validate_rejects_dropout_outside_unit_interval() {
+    let negative = TrainingConfig::new().with_dropout(-0.1);
+    let one = TrainingConfig::new().with_dropout(1.0);
+
+    assert!(matches!(negative.validate(), Err(TrainError::InvalidConfig(_))));
+    assert!(matches!(one.validate(), Err(TrainError::InvalidConfig(_))));
+}
+
+#[test]
+fn test_validate_rejects_non_positive_softplus_beta() {
+    for beta in [0.0, -1.0, f64::NAN, f64::INFINITY] {
+        let config = TrainingConfig::new().with_softplus_beta(beta);
+
+        assert!(matches!(config.validate(), Err(TrainError::InvalidConfig(_))));
+    }
+    assert_eq!(TrainingConfig::new().model_config().softplus_beta, 1.0);
+}
+
+#[test]
+fn test_validate_rejects_unsupported_mode() {
+    let config = TrainingConfig::new().with_mode(4);
+
+    assert!(matches!(config.validate(), Err(TrainError::InvalidConfig(_))));
+}
+
+#[test]
+fn test_validation_frequencies_are_held_out_from_training_range() {
+    let freq_range = (200.0, 1800.0);
+    let train = TuningForkDataset {
+        size: 1000,
+        freq_range,
+        split: FrequencySplit::Train,
+        sampling: SamplingMode::Uniform,
+        seed: 0,
+    };
+    let valid = TuningForkDataset {
+        size: 1000,
+        freq_range,
+        split: FrequencySplit::Valid,
+        sampling: SamplingMode::Uniform,
+        seed: 0,
+    };
+
+    for index in 0..valid.len() {
+        let freq = valid.get(index).unwrap();
+        assert!(freq >= freq_range.0 && freq <= freq_range.1, "{freq} out of range");
+        assert!(FrequencySplit::Valid.contains_bin(frequency_bin(freq_range, freq)));
+    }
+    for index in 0..train.len() {
+        let freq = train.get(index).unwrap();
+        assert!(freq >= freq_range.0 && freq <= freq_range.1, "{freq} out of range");
+        assert!(FrequencySplit::Train.contains_bin(frequency_bin(freq_range, freq)));
+    }
+}
+
+#[test]
+fn test_log_uniform_sampling_is_uniform_in_log_frequency() {
+    let freq_range = (200.0, 1800.0);
+    let dataset = |split, sampling| TuningForkDataset {
+        size: 10_000,
+        freq_range,
+        split,
+        sampling,
+        seed: 3,
+    };
+    let fraction_in = |dataset: &TuningForkDataset, low: f32, high: f32| {
+        let count = (0..dataset.len())
+            .map(|i| dataset.get(i).unwrap())
+            .filter(|freq| (low..high).contains(freq))
+            .count();
+        count as f32 / dataset.len() as f32
+    };
+    let log_uniform = dataset(FrequencySplit::Full, SamplingMode::LogUniform);
+    let uniform = dataset(FrequencySplit::Full, SamplingMode::Uniform);
+
+    // 幾何平均 (600Hz) より低い周波数は、対数で一様なら半分、周波数で一様なら1/4
+    assert!((fraction_in(&log_uniform, 200.0, 600.0) - 0.5).abs() < 0.02);
+    assert!((fraction_in(&uniform, 200.0, 600.0) - 0.25).abs() < 0.02);
+    // 対数で一様なら、各オクターブにほぼ同数 (ln 2 / ln 9) のサンプルが入る
+    let per_octave = 2f32.ln() / 9f32.ln();
+    for (low, high) in [(200.0, 400.0), (400.0, 800.0), (800.0, 1600.0)] {
+        let fraction = fraction_in(&log_uniform, low, high);
+        assert!((fraction - per_octave).abs() < 0.02, "{low}-{high} Hz: {fraction}");
+    }
+
+    // 学習用の分割でも、検証用のビンを避けつつ低い周波数のサンプルが多くなる
+    let train = dataset(FrequencySplit::Train, SamplingMode::LogUniform);
+    for index in 0..train.len() {
+        let freq = train.get(index).unwrap();
+        assert!(FrequencySplit::Train.contains_bin(frequency_bin(freq_range, freq)));
+    }
+    assert!(fraction_in(&train, 200.0, 600.0) > 0.45);
+}
+
+#[test]
+fn test_dataset_with_same_seed_is_reproducible() {
+    let dataset = |seed| TuningForkDataset {
+        size: 100,
+        freq_range: (200.0, 1800.0),
+        split: FrequencySplit::Train,
+        sampling: SamplingMode::Uniform,
+        seed,
+    };
+    let collect = |dataset: &TuningForkDataset| -> Vec<f32> {
+        (0..dataset.len()).map(|i| dataset.get(i).unwrap()).collect()
+    };
+
+    let first = collect(&dataset(7));
+    let second = collect(&dataset(7));
+    let other = collect(&dataset(8));
+
+    assert_eq!(first, second);
+    assert_ne!(first, other);
+}
+
+#[test]
+fn test_grid_dataset_is_evenly_spaced_over_range() {
+    let dataset = GridFrequencyDataset {
+        size: 17,
+        freq_min: 200.0,
+        freq_max: 1800.0,
+    };
+
+    let freqs: Vec<f32> = (0..dataset.len()).map(|i| dataset.get(i).unwrap()).collect();
+
+    assert_eq!(freqs.first(), Some(&200.0));
+    assert!((freqs.last().unwrap() - 1800.0).abs() < 1e-3);
+    for pair in freqs.windows(2) {
+        assert!(((pair[1] - pair[0]) - 100.0).abs() < 1e-3);
+    }
+    assert_eq!(dataset.get(17), None);
+}
+
+#[test]
+fn test_fixed_dataset_frequencies_flow_through_batcher() {
+    let device: NdArrayDevice = Default::default();
+    let dataset = FixedDataset::new(vec![261.63, 440.0, 523.25]);
+    let batcher = TuningForkBatcher::<B>::new(device);
+
+    let items: Vec<f32> = (0..dataset.len()).map(|i| dataset.get(i).unwrap()).collect();
+    let batch = batcher.batch(items, &device);
+
+    assert_eq!(dataset.get(3), None);
+    assert_eq!(batch.frequencies.dims(), [3, 1]);
+    let values = batch.frequencies.into_data().into_vec::<f32>().unwrap();
+    assert_eq!(values, dataset.frequencies);
+}
+
+#[test]
+fn test_normalizing_batcher_matches_hand_normalized_values() {
+    let device: NdArrayDevice = Default::default();
+    let batcher = NormalizingBatcher::<B>::new(device, 1000.0, 400.0);
+
+    let batch = batcher.batch(vec![200.0, 1000.0, 1800.0], &device);
+
+    assert_eq!(batch.dims(), [3, 1]);
+    let values = batch.into_data().into_vec::<f32>().unwrap();
+    assert_eq!(values, vec![-2.0, 0.0, 2.0]);
+}
+
+#[test]
+fn test_normalizing_batcher_from_model_applies_model_normalization() {
+    let device: NdArrayDevice = Default::default();
+    let model = TrainingConfig::new().model_config().init::<B>(&device);
+    let batcher = NormalizingBatcher::<B>::from_model(device, &model);
+    let freqs = vec![261.63, 440.0, 1500.0];
+
+    let batched = batcher.batch(freqs.clone(), &device);
+    let input = Tensor::<B, 1>::from_floats(freqs.as_slice(), &device).reshape([-1, 1]);
+    let expected = model.normalize_input(input);
+
+    assert_eq!(batcher.normalization(), model.input_normalization());
+    let diff = (batched - expected).abs().max().into_scalar();
+    assert!(diff < 1e-6);
+}
+
+#[test]
+fn test_plateau_tracker_stops_after_patience_epochs_without_improvement() {
+    let mut tracker = PlateauTracker::new(2, 0.1);
+
+    assert!(!tracker.update(1, 10.0));
+    assert!(!tracker.update(2, 5.0));
+    // min_delta 以下の改善は停滞とみなす
+    assert!(!tracker.update(3, 4.95));
+    assert!(tracker.update(4, 5.0));
+    assert_eq!(tracker.best(), Some(5.0));
+    // 最良エポックは min_delta に関わらず損失が最小のエポック
+    assert_eq!(tracker.best_epoch(), Some(3));
+    assert_eq!(tracker.last_loss(), Some(5.0));
+}
+
+#[test]
+fn test_plateau_tracker_flags_non_finite_loss_as_divergence() {
+    let device: NdArrayDevice = Default::default();
+    // 寸法にNaNを含む予測からは損失もNaNになる
+    let dims = Tensor::<B, 2>::from_floats([[0.08, 0.01, f32::NAN, 0.005, 0.01]], &device);
+    let target = Tensor::<B, 2>::from_floats([[440.0]], &device);
+    let loss = tuning_fork_loss(dims, target, Material::Steel304).into_scalar() as f64;
+    let mut tracker = PlateauTracker::new(10, 0.0);
+
+    assert!(!tracker.update(1, 1.0));
+    assert!(!tracker.detect_divergence(2, 0.5));
+    assert_eq!(tracker.diverged_epoch(), None);
+    assert!(tracker.update(2, loss));
+    assert!(tracker.detect_divergence(3, f64::INFINITY));
+    // 最初に発散したエポックを保持し、最終損失は有限値のまま
+    assert_eq!(tracker.diverged_epoch(), Some(2));
+    assert_eq!(tracker.last_loss(), Some(1.0));
+
+    let message = TrainError::Diverged(2).to_string();
+    assert!(message.contains("--learning-rate") && message.contains("--grad-clip"));
+}
+
+#[test]
+fn test_plateau_tracker_resets_on_improvement() {
+    let mut tracker = PlateauTracker::new(2, 0.0);
+
+    assert!(!tracker.update(1, 3.0));
+    assert!(!tracker.update(2, 3.0));
+    assert!(!tracker.update(3, 2.0));
+    assert!(!tracker.update(4, 2.0));
+    assert!(tracker.update(5, 2.5));
+    assert_eq!(tracker.best_epoch(), Some(3));
+}
+
+#[test]
+fn test_init_model_resumes_from_saved_record() {
+    let device: NdArrayDevice = Default::default();
+    let path = std::env::temp_dir().join("tuningfork_pinn_resume_test");
+    let path_str = path.to_str().unwrap();
+
+    let saved = ModelConfig::new().init::<B>(&device);
+    CompactRecorder::new()
+        .record(saved.clone().into_record(), path.clone())
+        .unwrap();
+
+    let resumed = init_model::<B>(&ModelConfig::new(), Some(path_str), &device).unwrap();
+    let fresh = init_model::<B>(&ModelConfig::new(), None, &device).unwrap();
+
+    let input = Tensor::<B, 2>::from_floats([[440.0]], &device);
+    let to_vec = |t: Tensor<B, 2>| t.into_data().into_vec::<f32>().unwrap();
+    let saved_out = to_vec(saved.forward(input.clone()));
+    let resumed_out = to_vec(resumed.forward(input.clone()));
+    let fresh_out = to_vec(fresh.forward(input));
+
+    // CompactRecorder は半精度で保存するため、相対誤差で比較する
+    for (resumed, saved) in resumed_out.iter().zip(&saved_out) {
+        assert!((resumed - saved).abs() <= 1e-2 * saved.abs().max(1.0));
+    }
+    assert_ne!(resumed_out, fresh_out);
+
+    std::fs::remove_file(path.with_extension("mpk")).unwrap();
+}
+
+#[test]
+fn test_init_model_with_missing_record_returns_load_error() {
+    let device: NdArrayDevice = Default::default();
+
+    let result = init_model::<B>(&ModelConfig::new(), Some("./nonexistent/model"), &device);
+
+    assert!(matches!(result, Err(TrainError::Load(_))));
+}
+
+#[test]
+fn test_best_checkpoint_path_requires_the_checkpoint_of_the_best_epoch() {
+    let artifact_dir = std::env::temp_dir().join("tuningfork_pinn_best_checkpoint_test");
+    let checkpoint_dir = artifact_dir.join("checkpoint");
+    std::fs::create_dir_all(&checkpoint_dir).unwrap();
+    std::fs::write(checkpoint_dir.join("model-3.mpk"), b"").unwrap();
+    let artifact_dir_str = artifact_dir.to_str().unwrap();
+
+    let found = best_checkpoint_path(artifact_dir_str, 3, RecordFormat::NamedMpk);
+    let pruned = best_checkpoint_path(artifact_dir_str, 2, RecordFormat::NamedMpk);
+    let other_format = best_checkpoint_path(artifact_dir_str, 3, RecordFormat::Bincode);
+
+    assert_eq!(found.unwrap(), format!("{artifact_dir_str}/checkpoint/model-3.mpk"));
+    assert!(matches!(pruned, Err(TrainError::Save(msg)) if msg.contains("epoch 2")));
+    assert!(matches!(other_format, Err(TrainError::Save(_))));
+    std::fs::remove_dir_all(&artifact_dir).unwrap();
+}
+
+#[test]
+fn test_each_lr_schedule_builds_a_scheduler() {
+    let schedules = [
+        LrSchedule::Constant,
+        LrSchedule::Exponential { gamma: 0.9 },
+        LrSchedule::Cosine { min_lr: 1e-6 },
+        LrSchedule::Step { size: 2, gamma: 0.5 },
+    ];
+
+    for schedule in schedules {
+        let mut scheduler = schedule.init(1e-3, 10);
+        let lrs: Vec<f64> = (0..10).map(|_| LrScheduler::step(&mut scheduler)).collect();
+
+        // 最初のステップは常に初期学習率
+        assert!((lrs[0] - 1e-3).abs() < 1e-12, "{schedule:?}");
+        assert!(lrs.iter().all(|lr| lr.is_finite() && *lr > 0.0), "{schedule:?}");
+        assert!(lrs.windows(2).all(|w| w[1] <= w[0]), "{schedule:?}");
+    }
+}
+
+#[test]
+fn test_step_schedule_decays_every_size_iterations() {
+    let schedule = LrSchedule::Step { size: 2, gamma: 0.5 };
+
+    assert_eq!(schedule.learning_rate(1.0, 10, 1), 1.0);
+    assert_eq!(schedule.learning_rate(1.0, 10, 2), 0.5);
+    assert_eq!(schedule.learning_rate(1.0, 10, 5), 0.25);
+}
+
+#[test]
+fn test_warmup_cosine_rises_linearly_then_decays_to_min_lr() {
+    let schedule = LrSchedule::WarmupCosine { warmup_epochs: 2, min_lr: 1e-6 };
+    let warmup_iters = 2 * TRAIN_BATCHES_PER_EPOCH;
+    let num_iters = 10 * TRAIN_BATCHES_PER_EPOCH;
+    let lr = |iteration: usize| schedule.learning_rate(1e-3, num_iters, iteration);
+
+    // エポック0の最初のイテレーションはウォームアップの1ステップ分
+    assert!((lr(0) - 1e-3 / warmup_iters as f64).abs() < 1e-12);
+    // ウォームアップの中間で半分
+    assert!((lr(warmup_iters / 2 - 1) - 0.5e-3).abs() < 1e-12);
+    // ウォームアップの終わりで設定した学習率に達し、以降は減衰する
+    assert!((lr(warmup_iters - 1) - 1e-3).abs() < 1e-12);
+    assert!((lr(warmup_iters) - 1e-3).abs() < 1e-12);
+    assert!(lr(warmup_iters + 100) < 1e-3);
+    // 学習の終わりで最小の学習率
+    assert!((lr(num_iters) - 1e-6).abs() < 1e-12);
+}
+
+#[test]
+fn test_lr_schedule_parses_from_cli_string() {
+    assert_eq!("constant".parse(), Ok(LrSchedule::Constant));
+    assert_eq!(
+        "exponential:0.99".parse(),
+        Ok(LrSchedule::Exponential { gamma: 0.99 })
+    );
+    assert_eq!(
+        "cosine:0.000001".parse(),
+        Ok(LrSchedule::Cosine { min_lr: 1e-6 })
+    );
+    assert_eq!(
+        "step:100:0.5".parse(),
+        Ok(LrSchedule::Step { size: 100, gamma: 0.5 })
+    );
+    assert_eq!(
+        "warmup-cosine:5:0.000001".parse(),
+        Ok(LrSchedule::WarmupCosine { warmup_epochs: 5, min_lr: 1e-6 })
+    );
+    assert!("linear".parse::<LrSchedule>().is_err());
+}
+
+#[test]
+fn test_grad_clip_is_applied_to_optimizer_config() {
+    let config = TrainingConfig::new().with_grad_clip(Some(1.0));
+
+    let optimizer = config.adam_config();
+
+    assert!(config.validate().is_ok());
+    assert!(matches!(
+        optimizer.grad_clipping,
+        Some(GradientClippingConfig::Norm(max_norm)) if max_norm == 1.0
+    ));
+}
+
+#[test]
+fn test_validate_rejects_non_positive_grad_clip() {
+    let config = TrainingConfig::new().with_grad_clip(Some(0.0));
+
+    assert!(matches!(config.validate(), Err(TrainError::InvalidConfig(_))));
+}
+
+#[test]
+fn test_weight_decay_is_applied_to_optimizer_config() {
+    let config = TrainingConfig::new().with_weight_decay(Some(1e-4));
+
+    let optimizer = config.adam_config();
+
+    assert!(config.validate().is_ok());
+    assert_eq!(optimizer.weight_decay.map(|decay| decay.penalty), Some(1e-4));
+}
+
+#[test]
+fn test_validate_rejects_negative_weight_decay() {
+    let config = TrainingConfig::new().with_weight_decay(Some(-1e-4));
+
+    assert!(matches!(config.validate(), Err(TrainError::InvalidConfig(_))));
+}
+
+#[test]
+fn test_optimizer_parses_from_cli_string() {
+    assert_eq!("adam".parse(), Ok(Optimizer::Adam));
+    assert_eq!(
+        "adamw:0.01".parse(),
+        Ok(Optimizer::AdamW { weight_decay: 0.01 })
+    );
+    assert_eq!("sgd:0.9".parse(), Ok(Optimizer::Sgd { momentum: 0.9 }));
+    assert!("rmsprop".parse::<Optimizer>().is_err());
+}
+
+#[test]
+fn test_config_saved_before_optimizer_choice_loads_as_adam() {
+    let path = std::env::temp_dir().join("tuningfork_pinn_legacy_optimizer_config.json");
+    let mut json = serde_json::to_value(TrainingConfig::new()).unwrap();
+    // オプティマイザを選べるようになる前は、Adamの設定がそのまま保存されていた
+    json["optimizer"] = serde_json::json!({
+        "beta_1": 0.9,
+        "beta_2": 0.999,
+        "epsilon": 1e-5,
+        "weight_decay": null,
+        "grad_clipping": null
+    });
+    std::fs::write(&path, json.to_string()).unwrap();
+
+    let config = TrainingConfig::load(&path).unwrap();
+
+    assert_eq!(config.optimizer, Optimizer::Adam);
+    std::fs::remove_file(&path).unwrap();
+}
+
+#[test]
+fn test_optimizer_round_trips_through_config_file() {
+    let path = std::env::temp_dir().join("tuningfork_pinn_optimizer_config.json");
+    for optimizer in [
+        Optimizer::Adam,
+        Optimizer::AdamW { weight_decay: 1e-3 },
+        Optimizer::Sgd { momentum: 0.9 },
+    ] {
+        TrainingConfig::new()
+            .with_optimizer(optimizer.clone())
+            .save(&path)
+            .unwrap();
+
+        assert_eq!(TrainingConfig::load(&path).unwrap().optimizer, optimizer);
+    }
+    std::fs::remove_file(&path).unwrap();
+}
+
+#[test]
+fn test_validate_rejects_weight_decay_with_adamw() {
+    let config = TrainingConfig::new()
+        .with_optimizer(Optimizer::AdamW { weight_decay: 1e-4 })
+        .with_weight_decay(Some(1e-4));
+
+    assert!(matches!(config.validate(), Err(TrainError::InvalidConfig(_))));
+}
+
+/// 1回の学習ステップでモデルの重みが更新されることを確認するヘルパー関数。
+fn assert_training_step_updates_weights<O>(mut optimizer: O)
+where
+    O: burn::optim::Optimizer<TuningForkPINN<Autodiff<B>>, Autodiff<B>>,
+{
+    let device: NdArrayDevice = Default::default();
+    let model = ModelConfig::new().init::<Autodiff<B>>(&device);
+    let batch = TuningForkBatcher::<Autodiff<B>>::new(device)
+        .batch(vec![300.0, 440.0, 880.0], &device);
+    let output_weight = |model: &TuningForkPINN<Autodiff<B>>| {
+        let weight = model.linear_layers().last().unwrap().weight.val();
+        weight.into_data().to_vec::<f32>().unwrap()
+    };
+    let before = output_weight(&model);
+
+    let output = TrainStep::step(&model, batch);
+    let model = optimizer.step(1e-3, model, output.grads);
+
+    assert_ne!(output_weight(&model), before);
+}
+
+#[test]
+fn test_each_optimizer_runs_a_training_step() {
+    let optimizers = [
+        Optimizer::Adam,
+        Optimizer::AdamW { weight_decay: 1e-4 },
+        Optimizer::Sgd { momentum: 0.9 },
+    ];
+
+    for optimizer in optimizers {
+        let config = TrainingConfig::new().with_optimizer(optimizer.clone());
+        assert!(config.validate().is_ok(), "optimizer: {optimizer:?}");
+        match optimizer {
+            Optimizer::Adam => assert_training_step_updates_weights(config.adam_config().init()),
+            Optimizer::AdamW { weight_decay } => {
+                assert_training_step_updates_weights(config.adamw_config(weight_decay).init())
+            }
+            Optimizer::Sgd { momentum } => {
+                assert_training_step_updates_weights(config.sgd_config(momentum).init())
+            }
+        }
+    }
+}
+
+#[test]
+fn test_freeze_hidden_updates_only_output_layer() {
+    let device: NdArrayDevice = Default::default();
+    let model = ModelConfig::new()
+        .init::<Autodiff<B>>(&device)
+        .freeze_hidden_layers();
+    let batch =
+        TuningForkBatcher::<Autodiff<B>>::new(device).batch(vec![300.0, 440.0, 880.0], &device);
+    let weights = |model: &TuningForkPINN<Autodiff<B>>| -> Vec<Vec<f32>> {
+        model
+            .linear_layers()
+            .map(|layer| layer.weight.val().into_data().to_vec::<f32>().unwrap())
+            .collect()
+    };
+    let before = weights(&model);
+
+    let output = TrainStep::step(&model, batch);
+    let mut optimizer = TrainingConfig::new().adam_config().init();
+    let model = burn::optim::Optimizer::step(&mut optimizer, 1e-3, model, output.grads);
+
+    let after = weights(&model);
+    let (output_before, hidden_before) = before.split_last().unwrap();
+    let (output_after, hidden_after) = after.split_last().unwrap();
+    assert_eq!(hidden_after, hidden_before);
+    assert_ne!(output_after, output_before);
+}
+
+#[test]
+fn test_valid_step_skips_the_random_consistency_perturbation() {
+    let device: NdArrayDevice = Default::default();
+    let model = ModelConfig::new().init::<B>(&device);
+    let loss_config = TrainingConfig::new().with_consistency_weight(1.0).loss_config();
+    let batcher = TuningForkBatcher::<B>::new(device).with_loss_config(loss_config);
+    let frequencies = vec![300.0, 440.0, 880.0];
+
+    let first = ValidStep::step(&model, batcher.batch(frequencies.clone(), &device));
+    let second = ValidStep::step(&model, batcher.batch(frequencies, &device));
+
+    let loss = |output: &PinnOutput<B>| output.regression.loss.clone().into_scalar();
+    assert_eq!(loss(&first), loss(&second));
+    assert_eq!(first.components.consistency_penalty.into_scalar(), 0.0);
+}
+
+#[test]
+fn test_penalty_warmup_interpolates_weights() {
+    let device: NdArrayDevice = Default::default();
+    let config = TrainingConfig::new().with_penalty_warmup_epochs(10);
+    let batcher = TuningForkBatcher::<B>::new(device)
+        .with_loss_config(config.loss_config())
+        .with_penalty_warmup(config.penalty_warmup_epochs, 1);
+
+    let configs: Vec<_> = (0..=10)
+        .map(|_| batcher.batch(vec![440.0], &device).loss_config)
+        .collect();
+    let (start, middle, end) = (&configs[0], &configs[5], &configs[10]);
+
+    let ratio = config.penalty_weight_ratio;
+    assert!((start.penalty_weight_ratio - ratio * PENALTY_WARMUP_START).abs() < 1e-6 * ratio);
+    let expected_middle = ratio * (PENALTY_WARMUP_START + (1.0 - PENALTY_WARMUP_START) * 0.5);
+    assert!((middle.penalty_weight_ratio - expected_middle).abs() < 1e-6 * ratio);
+    assert_eq!(end.penalty_weight_ratio, ratio);
+    assert_eq!(end.penalty_weight_range, config.penalty_weight_range);
+    assert_eq!(end.penalty_weight_other, config.penalty_weight_other);
+}
+
+#[test]
+fn test_batcher_advances_penalty_warmup_per_epoch() {
+    let device: NdArrayDevice = Default::default();
+    let config = TrainingConfig::new();
+    let batcher = TuningForkBatcher::<B>::new(device)
+        .with_loss_config(config.loss_config())
+        .with_penalty_warmup(2, 1);
+
+    let weights: Vec<f32> = (0..3)
+        .map(|_| batcher.batch(vec![440.0], &device).loss_config.penalty_weight_range)
+        .collect();
+
+    let full = config.penalty_weight_range;
+    assert!((weights[0] - full * PENALTY_WARMUP_START).abs() < 1e-6 * full);
+    assert!(weights[0] < weights[1] && weights[1] < weights[2]);
+    assert_eq!(weights[2], full);
+}
+
+#[test]
+fn test_batcher_cycles_materials_per_batch() {
+    let device: NdArrayDevice = Default::default();
+    let batcher = TuningForkBatcher::<B>::new(device).with_material_cycle(true);
+
+    let materials: Vec<Material> = (0..Material::ALL.len() + 1)
+        .map(|_| batcher.batch(vec![440.0], &device).loss_config.physics.material)
+        .collect();
+
+    assert_eq!(materials[..Material::ALL.len()], Material::ALL);
+    assert_eq!(materials[Material::ALL.len()], Material::ALL[0]);
+}
+
+#[test]
+fn test_dry_run_does_not_train_or_write_artifacts() {
+    let artifact_dir = std::env::temp_dir().join("tuningfork_pinn_dry_run_test");
+    if artifact_dir.exists() {
+        std::fs::remove_dir_all(&artifact_dir).unwrap();
+    }
+    let config = TrainingConfig::new()
+        .with_num_epochs(1000)
+        .with_artifact_dir(artifact_dir.to_str().unwrap().to_string());
+
+    let start = std::time::Instant::now();
+    dry_run::<Autodiff<B>>(&config, None, Default::default()).unwrap();
+
+    assert!(start.elapsed() < std::time::Duration::from_secs(10));
+    assert!(!artifact_dir.join("model.mpk").exists());
+    assert!(!artifact_dir.exists());
+}
+
+#[test]
+fn test_dry_run_rejects_invalid_config() {
+    let config = TrainingConfig::new().with_batch_size(0);
+
+    let result = dry_run::<Autodiff<B>>(&config, None, Default::default());
+
+    assert!(matches!(result, Err(TrainError::InvalidConfig(_))));
+}
+
+#[test]
+fn test_warm_start_predicts_closer_to_target_than_random_init() {
+    let device = NdArrayDevice::default();
+    let config = TrainingConfig::new();
+    let physics = config.loss_config().physics;
+    B::seed(&device, 42);
+    let random = config.model_config().init::<B>(&device);
+    let warm = warm_start(random.clone(), &config, &device).unwrap();
+
+    let error_at = |model: &TuningForkPINN<B>, freq: f32| {
+        let dims = model.predict_batch(&[freq], &device).unwrap().remove(0);
+        (frequency_from_dims_with_config(&dims, &physics) - freq).abs()
+    };
+    let (warm_error, random_error) = (error_at(&warm, 440.0), error_at(&random, 440.0));
+    assert!(
+        warm_error < random_error,
+        "warm start error {warm_error} Hz should be below random init error {random_error} Hz"
+    );
+
+    // 出力層の当てはめにより、初期の予測は中音域の周波数に追従する
+    let freqs = [300.0, 440.0, 600.0, 880.0];
+    let total_error =
+        |model: &TuningForkPINN<B>| freqs.iter().map(|&freq| error_at(model, freq)).sum::<f32>();
+    assert!(total_error(&warm) < total_error(&random));
+}
+
+#[test]
+fn test_evaluate_tolerance_uses_held_out_grid() {
+    let device = NdArrayDevice::default();
+    let config = TrainingConfig::new().with_tolerance_hz(f32::INFINITY);
+    let model = config.model_config().init::<B>(&device);
+
+    let report = evaluate_tolerance(&model, &config, &device);
+
+    // 20個のビンのうち4個が検証用のため、グリッドの約2割を評価する
+    assert!((30..=50).contains(&report.num_predictions), "{report}");
+    assert_eq!(report.percent_within(), 100.0);
+}